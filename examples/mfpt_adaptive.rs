@@ -0,0 +1,95 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (gamma) (exp_dim) (strength) (num_agent)
+    // Time Iterator arguments : (dt_min) (dt_max) (accuracy) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "0.2", "2", "1.0", "10", "1e-4", "1e-1", "0.1", "1e5", "100", "1", "12314123", "datas/adaptive"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Adaptive_EXP_Agent", TimeVecAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveExpAgent,
+        AdaptiveStep, VariableSimulation);
+
+    if dim != exp_dim{
+        panic!("Invalid arguments input : system dimension is differ from interaction dimension");
+    }
+    let coeff_diff : f64 = match mtype{
+        MoveType::Brownian(coeff_diff) => coeff_diff,
+        _ => panic!("Invalid arguments input : only Brownian motion is supported"),
+    };
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              917_299_259f64 * strength +
+                              367_276_621f64 * gamma +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut distance : f64;
+    let mut displacement = Position::new(vec![0f64; dim]);
+    let mut forces = vec![Position::new(vec![0f64; dim]); num_agent];
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        let mut iter = timeiter.into_diff();
+
+        'outer : loop{
+            // 가장 강한 pair force
+            for f in forces.iter_mut(){
+                f.clear();
+            }
+            for i in 0..num_agent{
+                for j in i + 1..num_agent{
+                    distance = vec_agents[i].mutual_displacement_to_vec(&vec_agents[j], &mut displacement)?;
+                    displacement.mut_scalar_mul(vec_agents[i].force(distance));
+                    forces[i].mut_sub(&displacement);
+                    forces[j].mut_add(&displacement);
+                }
+            }
+            let max_force : f64 = forces.iter().map(|f| f.norm()).fold(0f64, f64::max);
+
+            // target과 벽까지의 최소 거리
+            let mut min_distance : f64 = f64::INFINITY;
+            for agent in vec_agents.iter(){
+                min_distance = min_distance.min(target.distance(&agent.pos)? - target_size)
+                                           .min(sys.signed_distance(&agent.pos)?);
+            }
+
+            // 현재 상태로 다음 step의 dt를 정한다.
+            iter.adapt(coeff_diff, min_distance, max_force)?;
+            let (time, dt) = match iter.next(){
+                Some(x) => x,
+                None => break,
+            };
+
+            for (agent, single_move) in vec_agents.iter_mut().zip(forces.iter_mut()){
+                single_move.mut_scalar_mul(dt);
+                agent.random_move_to_vec(&mut rng, dt, single_move)?;
+                sys.check_bc(&mut agent.pos, single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time + dt;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data with number of steps and range of time steps used
+        write!(&mut writer, "{0:.5e} {1} {2:.5e} {3:.5e}\n", fpt, iter.timeiter.num_step, iter.timeiter.dt_smallest, iter.timeiter.dt_largest)
+            .map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
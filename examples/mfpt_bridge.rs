@@ -0,0 +1,61 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt_min) (dt_max) (length) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "1", "1e-3", "1e-1", "100", "0", "100", "1", "12314123", "datas/bridge"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Bridge_Indep_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveIndepAgent,
+        ExponentialStep, VariableSimulation);
+
+    let coeff_diff : f64 = match mtype{
+        MoveType::Brownian(coeff_diff) => coeff_diff,
+        _ => panic!("Invalid arguments input : only Brownian motion is supported"),
+    };
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut prev_pos = Position::new(vec![0f64; dim]);
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for agent in vec_agents.iter_mut(){
+                prev_pos.clone_from(&agent.pos);
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, dt, &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                // step 동안 target을 지났다가 되돌아온 경로도 찾은 것으로 본다.
+                if target.check_find_bridge(&mut rng, &prev_pos, &agent.pos, coeff_diff, dt)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,79 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (bctype) (sys_size) (center) (reflection)
+    //      ex) P:R, 10:0.5, 0:0, Approximate -> periodic channel of width 1 along x
+    //      sys_size, center는 target_pos처럼 파일 이름에 들어가지 않으므로 크기가 다르면 output_dir을 나누어야 한다.
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["P:R", "10:0.5", "0:0", "Approximate", "0:0", "0.3", "1.0", "Uniform", "1", "1e-3", "0", "100", "1", "12314123", "datas/channel"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Channel_Indep_Agent", TimeVecAnalysis,
+        ContRectSystem, ContBulkTarget, ContPassiveIndepAgent,
+        ConstStep, VariableSimulation);
+
+    let coeff_diff : f64 = match mtype{
+        MoveType::Brownian(coeff_diff) => coeff_diff,
+        _ => panic!("Invalid arguments input : only Brownian motion is supported"),
+    };
+
+    // Hash seed and generate random number generator
+    let dim : usize = sys.dim;
+    let seed : u128 = seed + (628_398_227f64 * sys_size.iter().sum::<f64>() +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+    let mut alive : Vec<bool> = vec![true; num_agent];
+
+    for _i in 0..num_ensemble{
+        // 모든 agent가 흡수하는 면에서 사라지면 target을 찾지 못한 것으로 기록한다.
+        let mut fpt : f64 = 0f64;
+        let mut found : bool = false;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        alive.iter_mut().for_each(|x| *x = true);
+        let mut num_alive : usize = num_agent;
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for (agent, is_alive) in vec_agents.iter_mut().zip(alive.iter_mut()){
+                if !*is_alive{
+                    continue;
+                }
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, dt, &mut single_move)?;
+                if sys.check_bc_absorb(&mut rng, &mut agent.pos, &mut single_move, coeff_diff, dt)?{
+                    *is_alive = false;
+                    num_alive -= 1;
+                    if num_alive == 0{
+                        fpt = time;
+                        break 'outer;
+                    }
+                    continue;
+                }
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    found = true;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data with outcome (1 : target found, 0 : all agents are lost) and number of lost agents
+        write!(&mut writer, "{0:.5e} {1} {2}\n", fpt, found as usize, num_agent - num_alive)
+            .map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
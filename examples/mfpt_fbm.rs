@@ -0,0 +1,57 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (hurst) (max_horizon) (num_agent)
+    // Time Iterator arguments : (dt) (tmax). tmax / dt와 max_horizon 중 작은 만큼의 increment를 미리 생성한다.
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "0.4", "65536", "1", "1e-2", "5e2", "100", "1", "12314123", "datas/fbm"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_FBM_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveFBMAgent,
+        ConstStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              367_276_621f64 * hurst +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+    for agent in &mut vec_agents{
+        agent.set_time_grid(&timeiter)?;                // ConstStep의 grid에 맞춰 fGn을 생성
+    }
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for agent in vec_agents.iter_mut(){
+                single_move.clear();
+                agent.evolve_to_vec(&mut rng, dt, &mut single_move)?;         // 미리 생성한 increment
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,87 @@
+// use std::env;
+use moledyn::prelude::*;
+
+// domain의 최소 반지름과 한 step의 diffusion length의 비
+const DOMAIN_RATIO : f64 = 3f64;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "10", "1e-3", "0", "100", "1", "12314123", "datas/gfrd"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_GFRD_Indep_Agent", TimeVecAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveIndepAgent,
+        ConstStep, VariableSimulation);
+
+    let coeff_diff : f64 = match mtype{
+        MoveType::Brownian(coeff_diff) => coeff_diff,
+        _ => panic!("Invalid arguments input : only Brownian motion is supported"),
+    };
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let dt : f64 = timeiter.dt();
+    let tmax : f64 = timeiter.tmax();
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+    let mut gfrd = GFRD::new(dim, coeff_diff, DOMAIN_RATIO)?;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+        let mut time : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        gfrd.renew(num_agent);
+
+        'outer : while time <= tmax{
+            // 모든 agent가 domain 안에 있으면 가장 빠른 exit time 직전까지 건너뛴다.
+            if let Some(t_next) = gfrd.next_event_time(){
+                time = time.max(t_next - dt);
+            }
+
+            for idx in 0..num_agent{
+                // target, 벽, 다른 agent (또는 그 domain)까지의 거리
+                let pos : &Position<f64> = &vec_agents[idx].pos;
+                let mut free_radius : f64 = (target.distance(pos)? - target_size).min(sys_size - pos.norm());
+                for (j, other) in vec_agents.iter().enumerate(){
+                    if j == idx{
+                        continue;
+                    }
+                    free_radius = free_radius.min(match gfrd.domain(j){
+                        Some(domain) => pos.distance(&domain.center)? - domain.radius,
+                        None => 0.5 * pos.distance(&other.pos)?,
+                    });
+                }
+
+                single_move.clear();
+                gfrd.step_to_vec(&mut rng, idx, time, dt, free_radius, &vec_agents[idx].pos, &mut single_move)?;
+                let agent = &mut vec_agents[idx];
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time + dt;
+                    break 'outer;
+                }
+            }
+            time += dt;
+        }
+
+        // Export FPT data with number of domains and Brownian steps used
+        write!(&mut writer, "{0:.5e} {1} {2}\n", fpt, gfrd.num_domain(), gfrd.num_step())
+            .map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,77 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (gamma) (exp_dim) (strength) (num_agent)
+    // Time Iterator arguments : (dt_min) (dt_max) (length) (tmax)
+    // Integrator Simulation arguments : (num_ensemble) (scheme) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "0.2", "2", "1.0", "10", "1e-5", "1e-2", "100", "1e5", "100", "SO15", "1", "12314123", "datas/integrator"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Integrator_EXP_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveExpAgent,
+        ExponentialStep, IntegratorSimulation);
+
+    if dim != exp_dim{
+        panic!("Invalid arguments input : system dimension is differ from interaction dimension");
+    }
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              917_299_259f64 * strength +
+                              367_276_621f64 * gamma +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut distance : f64;
+    let mut displacement = Position::new(vec![0f64; dim]);
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut forces = vec![Position::new(vec![0f64; dim]); num_agent];
+    let mut vec_agents = vec_agents;
+    let mut integrator = simulation.integrator(num_agent);
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        integrator.renew();
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            // 현재 위치에서의 interaction force. step 동안 고정된 drift로 integrator에 넘겨준다.
+            for f in forces.iter_mut(){
+                f.clear();
+            }
+            for i in 0..num_agent{
+                for j in i + 1..num_agent{
+                    distance = vec_agents[i].mutual_displacement_to_vec(&vec_agents[j], &mut displacement)?;
+                    displacement.mut_scalar_mul(vec_agents[i].force(distance));
+                    forces[i].mut_sub(&displacement);
+                    forces[j].mut_add(&displacement);
+                }
+            }
+
+            for (idx, agent) in vec_agents.iter_mut().enumerate(){
+                single_move.clear();
+                integrator.step_to_vec(&mut rng, idx, agent, &forces[idx], dt, &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        writeln!(&mut writer, "{0:.5e}", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    Ok(())
+}
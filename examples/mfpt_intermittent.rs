@@ -0,0 +1,58 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (speed) (rate_diff) (rate_ball) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "10.0", "1.0", "2.0", "1", "1e-3", "0", "100", "1", "12314123", "datas/intermittent"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Intermittent_Agent", TimeVecAnalysis,
+        ContCircSystem, ContBulkTarget, ContActiveIntermittentAgent,
+        ConstStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              367_276_621f64 * (speed + rate_diff + rate_ball) +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+        let mut finder : usize = 0;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for (idx, agent) in vec_agents.iter_mut().enumerate(){
+                single_move.clear();
+                agent.evolve_to_vec(&mut rng, dt, &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if agent.check_find(&target)?{                 // Diffusive phase에서만 target을 찾을 수 있다
+                    fpt = time;
+                    finder = idx;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT and the time spent in each phase by the searcher which found the target
+        let agent = &vec_agents[finder];
+        writeln!(&mut writer, "{0:.5e} {1:.5e} {2:.5e}", fpt,
+               agent.phase_time(SearchPhase::Diffusive), agent.phase_time(SearchPhase::Ballistic)).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    Ok(())
+}
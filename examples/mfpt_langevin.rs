@@ -0,0 +1,77 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (mass) (friction) (field) (int_type) (strength) (num_agent)
+    // Time Iterator arguments : (dt_min) (dt_max) (length) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "1.0", "1.0", "Free", "LennardJones(1.0)", "0.1", "10", "1e-4", "1e-2", "100", "1e5", "100", "1", "12314123", "datas/langevin"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Langevin_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveLangevinAgent,
+        ExponentialStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              917_299_259f64 * strength +
+                              367_276_621f64 * mass +
+                              834_013_537f64 * friction +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut distance : f64;
+    let mut displacement = Position::new(vec![0f64; dim]);
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut forces = vec![Position::new(vec![0f64; dim]); num_agent];
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            // 현재 위치에서의 interaction force
+            if strength != 0f64{
+                for f in forces.iter_mut(){
+                    f.clear();
+                }
+                for i in 0..num_agent{
+                    for j in i + 1..num_agent{
+                        distance = vec_agents[i].mutual_displacement_to_vec(&vec_agents[j], &mut displacement)?;
+                        displacement.mut_scalar_mul(vec_agents[i].force(distance));
+                        forces[i].mut_sub(&displacement);
+                        forces[j].mut_add(&displacement);
+                    }
+                }
+                for (agent, f) in vec_agents.iter_mut().zip(forces.iter()){
+                    agent.add_force(f)?;
+                }
+            }
+
+            for agent in vec_agents.iter_mut(){
+                single_move.clear();
+                agent.evolve_to_vec(&mut rng, dt, &mut single_move)?;
+                sys.check_bc_with_velocity(&mut agent.pos, &mut single_move, &mut agent.vel)?;   // 벽에서 velocity도 반사
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
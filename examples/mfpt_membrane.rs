@@ -0,0 +1,56 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    //      ex) 5, 3 -> 반지름 5인 세포의 막 (2D 구면)
+    // Target arguments : (target_pos) (target_size)
+    //      ex) 0:0:5, 0.5 -> north pole에 있는 geodesic radius 0.5인 receptor
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["5", "3", "0:0:5", "0.5", "1.0", "Uniform", "1", "1e-3", "1000", "100", "1", "12314123", "datas/membrane"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Membrane_Indep_Agent", TimeAnalysis,
+        ContSphereSurfaceSystem, ContSurfaceTarget, ContPassiveIndepAgent,
+        ConstStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for time in timeiter.into_iter().skip(1){
+            for agent in vec_agents.iter_mut(){
+                // 3차원 변위를 뽑고, 막의 접평면 성분만 geodesic을 따라 움직인다.
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, timeiter.dt(), &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
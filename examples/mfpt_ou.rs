@@ -0,0 +1,54 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (tau) (trap) (num_agent)
+    // Time Iterator arguments : (dt_min) (dt_max) (length) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "Uniform", "0.5", "Free", "1", "1e-5", "1e-2", "100", "1e5", "100", "1", "12314123", "datas/ou"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_OU_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveOUAgent,
+        ExponentialStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              367_276_621f64 * tau +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for agent in vec_agents.iter_mut(){
+                single_move.clear();
+                agent.evolve_to_vec(&mut rng, dt, &mut single_move)?;         // noise와 위치를 dt만큼 정확히 적분
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,57 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Reset Simulation arguments : (num_ensemble) (protocol) (reset_rate) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "1.0", "5:0", "1", "1e-3", "1e5", "100", "Poissonian", "0.1", "1", "12314123", "datas/reset"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_RESET_INDEP_Agent", TimeAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveIndepAgent,
+        ConstStep, ResetSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              917_299_259f64 * reset_rate +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+    let mut resetting = Resetting::new(simulation.reset_type(), ResetDest::Initial);
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        resetting.renew(vec_agents.iter().map(|agent| &agent.pos), &mut rng)?;
+
+        'outer : for (time, dt) in timeiter.into_diff().skip(1){
+            for (idx, agent) in vec_agents.iter_mut().enumerate(){
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, dt, &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+                resetting.check_reset(idx, time, &mut agent.pos, &sys, &target, &mut rng)?;
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,55 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_inner) (sys_size) (dim) (reflection)
+    //      ex) 3, 10, 3, Exact -> 반지름 3인 핵을 제외한 반지름 10인 세포. 핵 표면과 세포막 모두에서 정확히 반사시킨다.
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (mtype) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Variable Simulation arguments : (num_ensemble) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["3", "10", "3", "Exact", "0:0:9", "1", "1.0", "Uniform", "1", "1e-3", "1000", "100", "1", "12314123", "datas/shell"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_Shell_Indep_Agent", TimeAnalysis,
+        ContShellSystem, ContBulkTarget, ContPassiveIndepAgent,
+        ConstStep, VariableSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              570_914_867f64 * sys_inner +
+                              543_839_911f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        let mut fpt : f64 = 0f64;
+
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+
+        'outer : for time in timeiter.into_iter().skip(1){
+            for agent in vec_agents.iter_mut(){
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, timeiter.dt(), &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                if target.check_find(&agent.pos)?{
+                    fpt = time;
+                    break 'outer;
+                }
+            }
+        }
+
+        // Export FPT data
+        write!(&mut writer, "{0:.5e}\n", fpt).map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
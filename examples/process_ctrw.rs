@@ -0,0 +1,63 @@
+// use std::env;
+use moledyn::prelude::*;
+
+fn main() -> Result<(), Error>{
+    // System arguments : (sys_size) (dim)
+    // Target arguments : (target_pos) (target_size)
+    // agent arguments : (waiting) (jump) (itype) (num_agent)
+    // Time Iterator arguments : (dt) (tmax)
+    // Process Simulation arguments : (num_ensemble) (period) (idx_set) (seed) (output_dir)
+
+    // let args : Vec<String> = ["10", "2", "0:0", "1", "ML(0.5,1.0)", "Gaussian(0.1)", "0,0", "1", "1e-1", "1e3", "100", "1", "1", "12314123", "datas/ctrw"].iter().map(|x| x.to_string()).collect();
+
+    simulation!("RTS_CTRW_Process", ProcessAnalysis,
+        ContCircSystem, ContBulkTarget, ContPassiveCTRWAgent,
+        ConstStep, ProcessSimulation);
+
+    // Hash seed and generate random number generator
+    let seed : u128 = seed + (628_398_227f64 * sys_size +
+                              431_710_567f64 * dim as f64 +
+                              277_627_711f64 * target_size +
+                              719_236_607f64 * num_agent as f64 +
+                              367_276_621f64 * period +
+                              570_914_867f64 * idx_set as f64).floor() as u128;
+    let mut rng : Pcg64 = rng_seed(seed);
+
+    let mut single_move = Position::new(vec![0f64; dim]);
+    let mut vec_agents = vec_agents;
+
+    for _i in 0..num_ensemble{
+        for agent in &mut vec_agents{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+        }
+        let initial : Vec<Position<f64>> = vec_agents.iter().map(|agent| agent.pos.clone()).collect();
+        let mut next_record : f64 = period;
+
+        for time in timeiter.into_diff().map(|(time, _dt)| time).skip(1){
+            // time까지 일어나는 jump를 하나씩 처리하며 경계 조건 적용
+            for agent in vec_agents.iter_mut(){
+                while agent.next_event_time() <= time{
+                    single_move.clear();
+                    agent.jump_to_vec(&mut rng, &mut single_move)?;
+                    sys.check_bc(&mut agent.pos, &mut single_move)?;
+                }
+            }
+
+            // 일정한 시각마다 평균 제곱 변위 기록
+            if time >= next_record{
+                let mut msd : f64 = 0f64;
+                for (agent, x0) in vec_agents.iter().zip(initial.iter()){
+                    msd += agent.pos.distance(x0)?.powi(2) / num_agent as f64;
+                }
+                write!(&mut writer, "({0:.5e},{1:.5e}) ", time, msd).map_err(Error::make_error_io)?;
+                next_record += period;
+            }
+        }
+
+        // Export process data
+        write!(&mut writer, "\n").map_err(Error::make_error_io)?;
+        writer.flush().map_err(Error::make_error_io)?;
+    }
+
+    return Ok(());
+}
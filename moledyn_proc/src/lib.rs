@@ -73,6 +73,42 @@ fn ident_to_dataset(ident : &proc_macro2::Ident) -> proc_macro2::TokenStream{
             };
             tokens.into()
         },
+        "ContRectSystem" => {
+            let tokens = proc_quote::quote!{
+                ContRectSystem, sys_arg, ContRectSystemArguments, [sys_max(max_half_length), f64, sys_volume[volume], f64, dim(dim), usize]
+            };
+            tokens
+        },
+        "ContShellSystem" => {
+            let tokens = proc_quote::quote!{
+                ContShellSystem, sys_arg, ContShellSystemArguments, [sys_inner, f64, sys_size, f64, dim, usize]
+            };
+            tokens
+        },
+        "ContEllipsoidSystem" => {
+            let tokens = proc_quote::quote!{
+                ContEllipsoidSystem, sys_arg, ContEllipsoidSystemArguments, [sys_max(max_semi_axis), f64, sys_volume[volume], f64, dim(dim), usize]
+            };
+            tokens
+        },
+        "ContPolygonSystem" => {
+            let tokens = proc_quote::quote!{
+                ContPolygonSystem, sys_arg, ContPolygonSystemArguments, [sys_volume[volume], f64, dim(dim), usize]
+            };
+            tokens
+        },
+        "ContMeshSystem" => {
+            let tokens = proc_quote::quote!{
+                ContMeshSystem, sys_arg, ContMeshSystemArguments, [sys_volume[volume], f64, dim(dim), usize]
+            };
+            tokens
+        },
+        "ContSphereSurfaceSystem" => {
+            let tokens = proc_quote::quote!{
+                ContSphereSurfaceSystem, sys_arg, ContSphereSurfaceSystemArguments, [sys_size, f64, dim, usize]
+            };
+            tokens
+        },
         "ContCylindricalSystem" => {
             let tokens = proc_quote::quote!{
                 ContCylindricalSystem, sys_arg, ContCylindricalSystemArguments, [radius, f64, length, f64, dim, usize]
@@ -93,6 +129,12 @@ fn ident_to_dataset(ident : &proc_macro2::Ident) -> proc_macro2::TokenStream{
             };
             tokens.into()
         },
+        "ContSurfaceTarget" => {
+            let tokens = proc_quote::quote!{
+                ContSurfaceTarget, target_arg, ContSurfaceTargetArguments, [target_size, f64]
+            };
+            tokens
+        },
 
         // Agent Types
         "ContPassiveIndepAgent" => {
@@ -119,6 +161,48 @@ fn ident_to_dataset(ident : &proc_macro2::Ident) -> proc_macro2::TokenStream{
             };
             tokens.into()
         },
+        "ContPassiveFieldAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveFieldAgent, agent_arg, ContPassiveFieldAgentArguments, [num_agent, usize]
+            };
+            tokens
+        },
+        "ContPassiveHeteroAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveHeteroAgent, agent_arg, ContPassiveHeteroAgentArguments, [num_agent, usize]
+            };
+            tokens
+        },
+        "ContPassiveOUAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveOUAgent, agent_arg, ContPassiveOUAgentArguments, [tau, f64, num_agent, usize]
+            };
+            tokens
+        },
+        "ContPassiveFBMAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveFBMAgent, agent_arg, ContPassiveFBMAgentArguments, [hurst, f64, num_agent, usize]
+            };
+            tokens
+        },
+        "ContPassiveCTRWAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveCTRWAgent, agent_arg, ContPassiveCTRWAgentArguments, [num_agent, usize]
+            };
+            tokens
+        },
+        "ContActiveIntermittentAgent" => {
+            let tokens = proc_quote::quote!{
+                ContActiveIntermittentAgent, agent_arg, ContActiveIntermittentAgentArguments, [speed, f64, rate_diff, f64, rate_ball, f64, num_agent, usize]
+            };
+            tokens
+        },
+        "ContPassiveLangevinAgent" => {
+            let tokens = proc_quote::quote!{
+                ContPassiveLangevinAgent, agent_arg, ContPassiveLangevinAgentArguments, [mass, f64, friction, f64, strength, f64, num_agent, usize]
+            };
+            tokens
+        },
 
         // Timestep Types
         "ConstStep" => {
@@ -133,6 +217,12 @@ fn ident_to_dataset(ident : &proc_macro2::Ident) -> proc_macro2::TokenStream{
             };
             tokens.into()
         },
+        "AdaptiveStep" => {
+            let tokens = proc_quote::quote!{
+                AdaptiveStep, time_arg, AdaptiveStepArguments, [dt_min, f64, dt_max, f64, accuracy, f64, tmax, f64]
+            };
+            tokens
+        },
 
         // Simulation types
         "VariableSimulation" => {
@@ -153,6 +243,18 @@ fn ident_to_dataset(ident : &proc_macro2::Ident) -> proc_macro2::TokenStream{
             };
             tokens.into()
         },
+        "ResetSimulation" => {
+            let tokens = proc_quote::quote!{
+                ResetSimulation, sim_arg, ResetSimulationArguments, [idx_set, usize], key [reset_rate, f64]
+            };
+            tokens
+        },
+        "IntegratorSimulation" => {
+            let tokens = proc_quote::quote!{
+                IntegratorSimulation, sim_arg, IntegratorSimulationArguments, [idx_set, usize]
+            };
+            tokens
+        },
         _ => {
             let tokens = proc_quote::quote!{
                 #ident
@@ -210,10 +312,29 @@ impl ParsedArguments{
 
             match ident.to_string().as_str() {
                 "ContCircSystem" |
-                "ContCubicSystem" => {
+                "ContCubicSystem" |
+                "ContSphereSurfaceSystem" => {
                     vec.push(string_to_ident("sys_size"));
                     vec.push(string_to_ident("dim"));
                 },
+                "ContRectSystem" => {
+                    vec.push(string_to_ident("sys_size"));
+                    vec.push(string_to_ident("center"));
+                },
+                "ContShellSystem" => {
+                    vec.push(string_to_ident("sys_inner"));
+                    vec.push(string_to_ident("sys_size"));
+                    vec.push(string_to_ident("dim"));
+                },
+                "ContEllipsoidSystem" => {
+                    vec.push(string_to_ident("sys_size"));
+                },
+                "ContPolygonSystem" => {
+                    vec.push(string_to_ident("vertex_file"));
+                },
+                "ContMeshSystem" => {
+                    vec.push(string_to_ident("mesh_file"));
+                },
                 "ContCylindricalSystem" => {
                     vec.push(string_to_ident("sys_radius"));
                     vec.push(string_to_ident("sys_length"));
@@ -221,7 +342,8 @@ impl ParsedArguments{
                 },
 
                 "ContBoundaryTarget"  |
-                "ContBulkTarget" => {
+                "ContBulkTarget"      |
+                "ContSurfaceTarget" => {
                     vec.push(string_to_ident("target_pos"));
                     vec.push(string_to_ident("target_size"));
                 },
@@ -253,6 +375,58 @@ impl ParsedArguments{
                     vec.push(string_to_ident("strength"));
                     vec.push(string_to_ident("num_agent"));
                 },
+                "ContPassiveFieldAgent" => {
+                    vec.push(string_to_ident("mtype"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("field"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContPassiveHeteroAgent" => {
+                    vec.push(string_to_ident("diffusion"));
+                    vec.push(string_to_ident("convention"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("field"));
+                    vec.push(string_to_ident("scheme"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContPassiveOUAgent" => {
+                    vec.push(string_to_ident("mtype"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("tau"));
+                    vec.push(string_to_ident("trap"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContPassiveFBMAgent" => {
+                    vec.push(string_to_ident("mtype"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("hurst"));
+                    vec.push(string_to_ident("max_horizon"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContPassiveCTRWAgent" => {
+                    vec.push(string_to_ident("waiting"));
+                    vec.push(string_to_ident("jump"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContActiveIntermittentAgent" => {
+                    vec.push(string_to_ident("mtype"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("speed"));
+                    vec.push(string_to_ident("rate_diff"));
+                    vec.push(string_to_ident("rate_ball"));
+                    vec.push(string_to_ident("num_agent"));
+                },
+                "ContPassiveLangevinAgent" => {
+                    vec.push(string_to_ident("mtype"));
+                    vec.push(string_to_ident("itype"));
+                    vec.push(string_to_ident("mass"));
+                    vec.push(string_to_ident("friction"));
+                    vec.push(string_to_ident("field"));
+                    vec.push(string_to_ident("int_type"));
+                    vec.push(string_to_ident("strength"));
+                    vec.push(string_to_ident("num_agent"));
+                },
 
                 // Timestep Types
                 "ConstStep" => {
@@ -265,6 +439,12 @@ impl ParsedArguments{
                     vec.push(string_to_ident("length"));
                     vec.push(string_to_ident("tmax"));
                 },
+                "AdaptiveStep" => {
+                    vec.push(string_to_ident("dt_min"));
+                    vec.push(string_to_ident("dt_max"));
+                    vec.push(string_to_ident("accuracy"));
+                    vec.push(string_to_ident("tmax"));
+                },
 
                 // Simulation types
                 "VariableSimulation" => {
@@ -286,6 +466,21 @@ impl ParsedArguments{
                     vec.push(string_to_ident("seed"));
                     vec.push(string_to_ident("output_dir"));
                 },
+                "ResetSimulation" => {
+                    vec.push(string_to_ident("num_ensemble"));
+                    vec.push(string_to_ident("protocol"));
+                    vec.push(string_to_ident("reset_rate"));
+                    vec.push(string_to_ident("idx_set"));
+                    vec.push(string_to_ident("seed"));
+                    vec.push(string_to_ident("output_dir"));
+                },
+                "IntegratorSimulation" => {
+                    vec.push(string_to_ident("num_ensemble"));
+                    vec.push(string_to_ident("scheme"));
+                    vec.push(string_to_ident("idx_set"));
+                    vec.push(string_to_ident("seed"));
+                    vec.push(string_to_ident("output_dir"));
+                },
 
                 _ => {}
             }
@@ -0,0 +1,372 @@
+// Module for Continous Active Agent with intermittent search strategy
+//
+// 두 phase를 번갈아가며 움직이는 searcher
+//      - Diffusive phase : diffusion coefficient D로 천천히 움직이며 target을 찾을 수 있다. (reactive)
+//      - Ballistic phase : 무작위 방향으로 속력 v로 빠르게 움직이지만 target을 찾을 수 없다. (non-reactive)
+// Diffusive -> Ballistic 은 rate_diff, Ballistic -> Diffusive 는 rate_ball 의 rate로 전환된다.
+// 전환은 Poisson process이므로 dt 안에서의 전환 시각까지 정확하게 나눠서 움직인다.
+// 각 phase에서 보낸 총 시간과 phase 수를 기록한다.
+//
+// Bénichou, O., Loverdo, C., Moreau, M., & Voituriez, R. (2011). Intermittent search strategies. Rev. Mod. Phys. 83, 81.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Stateful};
+use crate::random_mod::{get_uniform, get_gaussian_to_vec_nonstandard, get_direction_to_vec};
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd)]
+pub enum SearchPhase{                   // Phase of intermittent searcher
+    Diffusive,                          // Slow, reactive phase
+    Ballistic,                          // Fast, non-reactive phase
+}
+
+impl SearchPhase{
+    pub fn is_reactive(&self) -> bool{
+        *self == SearchPhase::Diffusive
+    }
+}
+
+impl_fmt_for_type!(SearchPhase,
+    SearchPhase::Diffusive => "Diffusive phase",
+    SearchPhase::Ballistic => "Ballistic phase");
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContActiveIntermittentAgent{       // 연속한 시스템에서 diffusive phase와 ballistic phase를 번갈아 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub mtype : MoveType,                   // Type of random movement in diffusive phase
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub speed : f64,                        // Speed in ballistic phase
+    pub rate_diff : f64,                    // Switching rate from diffusive to ballistic phase
+    pub rate_ball : f64,                    // Switching rate from ballistic to diffusive phase
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub phase : SearchPhase,                // current phase
+    pub direction : Position<f64>,          // direction of ballistic motion
+    pub time_diff : f64,                    // total time spent in diffusive phase
+    pub time_ball : f64,                    // total time spent in ballistic phase
+    pub num_diff : usize,                   // number of diffusive phases entered
+    pub num_ball : usize,                   // number of ballistic phases entered
+}
+
+impl ContActiveIntermittentAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(mtype : MoveType, speed : f64, rate_diff : f64, rate_ball : f64, pos : Position<f64>) -> Self{
+        // mtype : Random walk characteristic in diffusive phase
+        // speed : speed in ballistic phase
+        // rate_diff : switching rate from diffusive to ballistic phase
+        // rate_ball : switching rate from ballistic to diffusive phase
+        // pos : initial position of agent
+
+        let dim : usize = pos.dim();
+        ContActiveIntermittentAgent{
+            agent_type : AgentType::ContinuousActiveIndependent,
+            mtype,
+            itype : InitType::SpecificPosition(pos.clone()),
+            speed,
+            rate_diff,
+            rate_ball,
+            dim,
+            pos,
+            phase : SearchPhase::Diffusive,
+            direction : Position::new(vec![0f64; dim]),
+            time_diff : 0f64,
+            time_ball : 0f64,
+            num_diff : 1,
+            num_ball : 0,
+        }
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   mtype : MoveType, speed : f64, rate_diff : f64, rate_ball : f64) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        let mut agent = ContActiveIntermittentAgent::new(mtype, speed, rate_diff, rate_ball, pos);
+        agent.itype = InitType::Uniform;
+        agent.renew_state(rng)?;
+        Ok(agent)
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 위치와 함께 phase, 기록도 새로 시작한다.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+        self.renew_state(rng)
+    }
+
+    pub fn check_find(&self, target : &dyn TargetCore<f64>) -> Result<bool, Error>{
+        // Reactive phase (diffusive)에서만 target을 찾을 수 있다.
+        if !self.phase.is_reactive(){
+            return Ok(false);
+        }
+        target.check_find(&self.pos)
+    }
+
+    pub fn phase_time(&self, phase : SearchPhase) -> f64{
+        // 주어진 phase에서 보낸 총 시간
+        match phase{
+            SearchPhase::Diffusive => self.time_diff,
+            SearchPhase::Ballistic => self.time_ball,
+        }
+    }
+
+    pub fn diffusive_fraction(&self) -> f64{
+        // stationary state에서 diffusive phase에 있을 확률 rate_ball / (rate_diff + rate_ball)
+        self.rate_ball / (self.rate_diff + self.rate_ball)
+    }
+
+    fn coeff_diff(&self) -> Result<f64, Error>{
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) if coeff_diff >= 0f64 => Ok(coeff_diff),
+            MoveType::Brownian(_) => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    fn switch_phase(&mut self, rng : &mut Pcg64){
+        // 다른 phase로 전환. ballistic phase에 들어갈 때마다 방향을 새로 뽑는다.
+        match self.phase{
+            SearchPhase::Diffusive => {
+                self.phase = SearchPhase::Ballistic;
+                self.num_ball += 1;
+                get_direction_to_vec(rng, &mut self.direction);
+            },
+            SearchPhase::Ballistic => {
+                self.phase = SearchPhase::Diffusive;
+                self.num_diff += 1;
+            },
+        }
+    }
+}
+
+impl_argument_trait!(ContActiveIntermittentAgent, "Agent", ContActiveIntermittentAgentArguments, 6,
+    agent_type, AgentType, AgentType::ContinuousActiveIndependent;
+    mtype, MoveType, "Random walk Characterstic in diffusive phase. ex) 1.0 : diffusion coefficient D",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    speed, f64, "Speed in ballistic phase",
+    rate_diff, f64, "Switching rate from diffusive (reactive) to ballistic phase",
+    rate_ball, f64, "Switching rate from ballistic to diffusive (reactive) phase",
+    num_agent, usize, "Number of Agents");
+
+impl ContActiveIntermittentAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContActiveIntermittentAgentArguments) -> Result<Vec<Self>, Error>{
+        let pos : Position<f64> = match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) => p.clone(),
+            _ => Position::new(vec![]),     // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+        };
+        let mut agent = Self::new(argument.mtype, argument.speed, argument.rate_diff, argument.rate_ball, pos);
+        agent.agent_type = argument.agent_type;
+        agent.itype = argument.itype.clone();
+        let mut agents = vec![agent; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim       = p.dim();
+                agent.direction = Position::new(vec![0f64; p.dim()]);
+                agent.itype     = InitType::SpecificPosition(p.clone());
+                agent.pos       = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContActiveIntermittentAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Active<f64> for ContActiveIntermittentAgent{
+    // Ballistic phase에서의 velocity. Diffusive phase에서는 0
+    fn active_move(&self) -> Position<f64>{
+        let mut velocity : Position<f64> = Position::new(vec![0f64; self.dim]);
+        if self.phase == SearchPhase::Ballistic{
+            velocity.mut_add(&self.direction);
+            velocity.mut_scalar_mul(self.speed);
+        }
+        velocity
+    }
+}
+
+impl Stateful<f64, f64> for ContActiveIntermittentAgent{
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>{
+        // Diffusive phase에서 다시 시작하고 기록을 지운다.
+        if self.speed < 0f64 || self.rate_diff < 0f64 || self.rate_ball < 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        if self.direction.dim() != self.dim{
+            self.direction = Position::new(vec![0f64; self.dim]);
+        }
+        get_direction_to_vec(rng, &mut self.direction);
+
+        self.phase = SearchPhase::Diffusive;
+        self.time_diff = 0f64;
+        self.time_ball = 0f64;
+        self.num_diff = 1;
+        self.num_ball = 0;
+        Ok(())
+    }
+
+    fn evolve_to_vec(&mut self, rng : &mut Pcg64, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // dt 동안 phase 전환을 고려한 displacement를 vec에 더해준다.
+        // rng : Random number generator
+        // dt : Time step size. 매번 달라도 된다.
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim() || self.dim != self.direction.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let coeff_diff : f64 = self.coeff_diff()?;
+
+        let mut remain : f64 = dt;
+        while remain > 0f64{
+            let rate : f64 = match self.phase{
+                SearchPhase::Diffusive => self.rate_diff,
+                SearchPhase::Ballistic => self.rate_ball,
+            };
+            // 전환까지의 시간. rate가 0이면 전환하지 않는다.
+            let wait : f64 = if rate > 0f64 { - get_uniform(rng).ln() / rate } else { f64::INFINITY };
+            let tau : f64 = wait.min(remain);
+
+            match self.phase{
+                SearchPhase::Diffusive => {
+                    get_gaussian_to_vec_nonstandard(rng, vec, 0f64, (2f64 * coeff_diff * tau).sqrt());
+                    self.time_diff += tau;
+                },
+                SearchPhase::Ballistic => {
+                    for i in 0..self.dim{
+                        vec[i] += self.speed * tau * self.direction[i];
+                    }
+                    self.time_ball += tau;
+                },
+            }
+
+            if wait < remain{
+                self.switch_phase(rng);
+            }
+            remain -= tau;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_phase_time() -> Result<(), Error>{
+        // 긴 시간 동안 diffusive phase에 있는 비율은 rate_ball / (rate_diff + rate_ball)
+        let mut rng = rng_seed(12341234);
+        let mut agent = ContActiveIntermittentAgent::new(MoveType::Brownian(1.0), 10.0, 2.0, 3.0, Position::new(vec![0.0, 0.0]));
+        agent.renew_state(&mut rng)?;
+
+        let mut disp = Position::new(vec![0.0, 0.0]);
+        for _i in 0..100000{
+            agent.evolve_to_vec(&mut rng, 0.1, &mut disp)?;
+        }
+        let total : f64 = agent.phase_time(SearchPhase::Diffusive) + agent.phase_time(SearchPhase::Ballistic);
+        assert!((total - 1e4).abs() < 1e-6);
+        assert!((agent.time_diff / total - agent.diffusive_fraction()).abs() < 0.01);
+
+        // 평균 phase 지속시간은 1 / rate
+        assert!((agent.time_diff / agent.num_diff as f64 - 0.5).abs() < 0.01);
+        assert!((agent.time_ball / agent.num_ball as f64 - 1.0 / 3.0).abs() < 0.01);
+
+        agent.renew_state(&mut rng)?;
+        assert_eq!(agent.phase, SearchPhase::Diffusive);
+        assert_eq!(agent.time_ball, 0f64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_find() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let mut agent = ContActiveIntermittentAgent::new(MoveType::Brownian(1.0), 1.0, 0.0, 1.0, Position::new(vec![0.0, 0.0]));
+        agent.renew_state(&mut rng)?;
+        assert_eq!(agent.check_find(&target), Ok(true));
+        assert_eq!(agent.active_move(), Position::new(vec![0.0, 0.0]));
+
+        agent.switch_phase(&mut rng);
+        assert_eq!(agent.phase, SearchPhase::Ballistic);
+        assert_eq!(agent.check_find(&target), Ok(false));
+        assert!((agent.active_move().norm() - 1.0).abs() < 1e-10);
+
+        // rate_ball = 0 이면 ballistic phase에서 벗어나지 않고 직선운동
+        agent.rate_ball = 0f64;
+        let mut disp = Position::new(vec![0.0, 0.0]);
+        agent.evolve_to_vec(&mut rng, 2.0, &mut disp)?;
+        assert!((disp.norm() - 2.0).abs() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContActiveIntermittentAgentArguments::new(MoveType::Brownian(1f64), InitType::Uniform, 5.0, 1.0, 2.0, 3);
+        let agents = ContActiveIntermittentAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].speed, 5.0);
+        assert_eq!(agents[0].rate_ball, 2.0);
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let agent = ContActiveIntermittentAgent::new_uniform(&system, &target, &mut rng, MoveType::Brownian(1f64), 5.0, 1.0, 2.0)?;
+        assert_eq!(agent.direction.dim(), 2);
+        assert!(system.check_inclusion(&agent.pos)?);
+        Ok(())
+    }
+}
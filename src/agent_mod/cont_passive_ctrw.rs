@@ -0,0 +1,286 @@
+// Module for Continous Passive Agent following continuous-time random walk
+//
+// waiting time distribution psi(t)에서 뽑은 시간만큼 기다린 뒤,
+// 독립적으로 정한 jump length distribution에서 뽑은 displacement만큼 순간적으로 점프한다.
+// Event-driven 방식이므로 agent는 다음 jump가 일어나는 시각을 알고 있다.
+//      - next_event_time, jump_to_vec : jump 하나씩 처리. 매 jump마다 check_bc, check_find를 할 수 있다.
+//      - Stateful::evolve_to_vec : (t, t + dt] 사이의 jump들을 모두 더해준다.
+// 따라서 TimeIterator의 시각마다 next_event_time을 보고 jump를 처리한 뒤
+// ProcessAnalysis 등으로 일정한 시각마다 observable을 기록할 수 있다.
+// 모든 agent는 시각 0에 새로 시작하는 (non-aged) CTRW를 따른다.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Stateful};
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveCTRWAgent{        // 연속한 시스템에서 continuous-time random walk로 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub waiting : WaitingType,              // Waiting time distribution
+    pub jump : JumpType,                    // Jump length distribution
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub clock : f64,                        // time until which the agent is evolved
+    pub next_event : f64,                   // time of next jump
+    pub num_jump : usize,                   // number of jumps since renewal
+}
+
+impl ContPassiveCTRWAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(waiting : WaitingType, jump : JumpType, pos : Position<f64>) -> Self{
+        // waiting : waiting time distribution
+        // jump : jump length distribution
+        // pos : initial position of agent
+        // 다음 jump 시각은 renew_state에서 정해진다.
+
+        ContPassiveCTRWAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            waiting,
+            jump,
+            itype : InitType::SpecificPosition(pos.clone()),
+            dim : pos.dim(),
+            pos,
+            clock : 0f64,
+            next_event : f64::INFINITY,
+            num_jump : 0,
+        }
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   waiting : WaitingType, jump : JumpType) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        let mut agent = ContPassiveCTRWAgent::new(waiting, jump, pos);
+        agent.itype = InitType::Uniform;
+        agent.renew_state(rng)?;
+        Ok(agent)
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 위치와 함께 clock, 다음 jump 시각도 새로 정한다.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+        self.renew_state(rng)
+    }
+
+    pub fn next_event_time(&self) -> f64{
+        // 다음 jump가 일어나는 시각
+        self.next_event
+    }
+
+    pub fn jump_to_vec(&mut self, rng : &mut Pcg64, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // 다음 jump를 수행해 displacement를 vec에 더해주고, jump가 일어난 시각을 반환한다.
+        // 이후 clock은 jump 시각이 되고, 다음 jump 시각을 새로 뽑는다.
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let time : f64 = self.next_event;
+        self.jump.jump_to_vec(rng, vec);
+        self.clock = time;
+        self.next_event = time + self.waiting.sample(rng);
+        self.num_jump += 1;
+        Ok(time)
+    }
+}
+
+impl_argument_trait!(ContPassiveCTRWAgent, "Agent", ContPassiveCTRWAgentArguments, 4,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    waiting, WaitingType, "Waiting time distribution. ex) Exp(1.0), PowerLaw(0.5,1.0), ML(0.5,1.0)",
+    jump, JumpType, "Jump length distribution. ex) Gaussian(0.1), Fixed(0.1), PowerLaw(1.5,0.1)",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveCTRWAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveCTRWAgentArguments) -> Result<Vec<Self>, Error>{
+        let pos : Position<f64> = match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) => p.clone(),
+            _ => Position::new(vec![]),     // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+        };
+        let mut agent = Self::new(argument.waiting, argument.jump, pos);
+        agent.agent_type = argument.agent_type;
+        agent.itype = argument.itype.clone();
+        let mut agents = vec![agent; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveCTRWAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Stateful<f64, f64> for ContPassiveCTRWAgent{
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>{
+        // clock을 0으로 돌리고 첫 jump 시각을 뽑는다.
+        self.waiting.check_parameters()?;
+        self.jump.check_parameters()?;
+
+        self.clock = 0f64;
+        self.num_jump = 0;
+        self.next_event = self.waiting.sample(rng);
+        Ok(())
+    }
+
+    fn evolve_to_vec(&mut self, rng : &mut Pcg64, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // (clock, clock + dt] 사이에 일어나는 모든 jump의 displacement를 vec에 더해준다.
+        // 각 jump마다 경계 조건을 적용하려면 next_event_time과 jump_to_vec을 사용한다.
+        // rng : Random number generator
+        // dt : Time step size. 매번 달라도 된다.
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let end : f64 = self.clock + dt;
+        while self.next_event <= end{
+            self.jump_to_vec(rng, vec)?;
+        }
+        self.clock = end;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_event() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let mut agent = ContPassiveCTRWAgent::new(WaitingType::PowerLaw(0.5, 1.0), JumpType::Fixed(1.0),
+                            Position::new(vec![0.0, 0.0]));
+        agent.renew_state(&mut rng)?;
+
+        let mut disp = Position::new(vec![0.0, 0.0]);
+        let first : f64 = agent.next_event_time();
+        assert!(first > 0f64);
+        assert_eq!(agent.jump_to_vec(&mut rng, &mut disp), Ok(first));
+        assert!((disp.norm() - 1f64).abs() < 1e-10);
+        assert!(agent.next_event_time() > first);
+        assert_eq!(agent.num_jump, 1);
+
+        // evolve_to_vec 이후에는 clock 이전의 jump가 남아있지 않다
+        agent.evolve_to_vec(&mut rng, 100.0, &mut disp)?;
+        assert_eq!(agent.clock, first + 100.0);
+        assert!(agent.next_event_time() > agent.clock);
+
+        let mut wrong = Position::new(vec![0.0]);
+        assert_eq!(agent.jump_to_vec(&mut rng, &mut wrong), Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_msd() -> Result<(), Error>{
+        // Exponential waiting time : MSD = 2 sigma^2 t / tau (성분마다 sigma^2 t / tau)
+        // Mittag-Leffler waiting time : MSD = sigma^2 (t / tau0)^alpha / Gamma(1 + alpha) (성분마다)
+        let mut rng = rng_seed(12341234);
+        let n : usize = 20000;
+        let time : f64 = 10f64;
+        let mut timeiter = ExponentialStep::new(1e-2, 1.0, 10)?;
+        timeiter.set_tmax(2f64 * time)?;
+
+        // (waiting, alpha, Gamma(1 + alpha))
+        let cases : Vec<(WaitingType, f64, f64)> = vec![(WaitingType::Exponential(0.5), 1.0, 1.0),
+                                                        (WaitingType::MittagLeffler(0.5, 0.5), 0.5, 0.5 * PI.sqrt())];
+        for (waiting, alpha, gamma) in cases{
+            let mut agent = ContPassiveCTRWAgent::new(waiting, JumpType::Gaussian(1.0), Position::new(vec![0.0]));
+            let mut msd : f64 = 0f64;
+            let mut disp = Position::new(vec![0.0]);
+            for _i in 0..n{
+                agent.renew_state(&mut rng)?;
+                disp.clear();
+                for (t, dt) in timeiter.into_diff().skip(1){
+                    agent.evolve_to_vec(&mut rng, dt, &mut disp)?;
+                    if t >= time{
+                        break;
+                    }
+                }
+                msd += disp[0].powi(2) / n as f64;
+            }
+            let answer : f64 = (agent.clock / 0.5).powf(alpha) / gamma;
+            assert!((msd - answer).abs() < 0.05 * answer, "{} {} {}", waiting, msd, answer);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveCTRWAgentArguments::new(WaitingType::from_str("ML(0.5,1.0)")?,
+                            JumpType::from_str("Gaussian(0.1)")?, InitType::Uniform, 3);
+        let agents = ContPassiveCTRWAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].waiting, WaitingType::MittagLeffler(0.5, 1.0));
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let agent = ContPassiveCTRWAgent::new_uniform(&system, &target, &mut rng,
+                            WaitingType::Exponential(1.0), JumpType::Fixed(0.1))?;
+        assert!(agent.next_event_time() < std::f64::INFINITY);
+        assert!(system.check_inclusion(&agent.pos)?);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 // Module for Continous Passive Independent Agent
 
 use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
 use crate::random_mod::{get_gaussian_vec, get_gaussian_to_vec_nonstandard};
 
 
@@ -91,12 +92,7 @@ impl ContPassiveExpAgent{
                 let (coeff_pot, coeff_force) = Self::coeff(dim, gamma, strength)?;
 
                 let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
-                loop{
-                    sys.random_pos_to_vec(rng, &mut pos)?;   // System 내부의 random position을 받는다
-                    if !target.check_find(&pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                        break;
-                    }
-                }
+                InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
 
                 Ok(ContPassiveExpAgent{
                     agent_type : AgentType::ContinuousPassiveInteracting,
@@ -135,12 +131,7 @@ impl ContPassiveExpAgent{
                 self.coeff_force = coeff_force;
             }
         }
-        loop{
-            sys.random_pos_to_vec(rng, &mut self.pos)?;   // System 내부의 random position을 받는다
-            if !target.check_find(&self.pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                break;
-            }
-        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
 
         Ok(())
     }
@@ -149,7 +140,7 @@ impl ContPassiveExpAgent{
 impl_argument_trait!(ContPassiveExpAgent, "Agent", ContPassiveExpAgentArguments, 6,
     agent_type, AgentType, AgentType::ContinuousPassiveInteracting;
     mtype,  MoveType,       "Random walk Characterstic. ex) 1.0 : Brownian with D=1 / Levy : Levy walk",
-    itype,  InitType<f64>,  "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform",
+    itype,  InitType<f64>,  "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
     gamma,  f64,            "Typical length scale of interaction. ex) 0.1, 0.5",
     exp_dim,    usize,       "Dimension of system ex) 2, 3",
     strength, f64,          "Strength of interaction",
@@ -157,7 +148,7 @@ impl_argument_trait!(ContPassiveExpAgent, "Agent", ContPassiveExpAgentArguments,
 
 impl ContPassiveExpAgent{
     #[allow(dead_code)]
-    pub fn convert_from(argument : &ContPassiveExpAgentArguments) -> Vec<Self>{
+    pub fn convert_from(argument : &ContPassiveExpAgentArguments) -> Result<Vec<Self>, Error>{
         let mut dim : usize;
         let pos : Position<f64>;
         let gamma : f64;
@@ -171,7 +162,11 @@ impl ContPassiveExpAgent{
             InitType::<f64>::SpecificPosition(p) =>{
                 dim = p.dim();
                 pos = p.clone();
-            }
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
         }
 
         let d = argument.exp_dim;
@@ -184,7 +179,7 @@ impl ContPassiveExpAgent{
 
         let (coeff_pot, coeff_force) = Self::coeff(dim, gamma, strength).expect("Feature for dimensions without 2D or 3D is not Provided");
 
-        vec![Self{
+        let mut agents = vec![Self{
             agent_type : AgentType::ContinuousPassiveInteracting,
             int_type: InteractType::Exponential(dim, gamma),
             mtype   : argument.mtype,
@@ -195,7 +190,20 @@ impl ContPassiveExpAgent{
             strength: strength,
             coeff_pot : coeff_pot,
             coeff_force : coeff_force,
-        }; argument.num_agent]
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                if p.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
     }
 }
 
@@ -292,6 +300,23 @@ impl Interaction<f64, f64> for ContPassiveExpAgent{
 
 
 
+impl Drift<f64, f64> for ContPassiveExpAgent{
+    fn coeff_diff(&self) -> Result<f64, Error>{
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) => Ok(coeff_diff),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    fn drift_to_vec(&self, pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // 외부 field가 없으므로 drift는 0. interaction은 Integrator에 force로 따로 준다.
+        if self.exp_dim != pos.dim() || self.exp_dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests{
     #[allow(unused_imports)]
@@ -328,7 +353,7 @@ mod tests{
     fn test_uniform() -> Result<(), Error>{
         use crate::system_mod::cont_circ::ContCircSystem;
         use crate::target_mod::cont_bulk::ContBulkTarget;
-        use crate::random_mod::get_uniform_to_vec_nonstandard;
+        use crate::random_mod::get_ball_to_vec;
 
 
         let mut rng1 = rng_seed(12341234);
@@ -342,8 +367,7 @@ mod tests{
 
         let mut pos = system.position_out_of_system();
         while !system.check_inclusion(&pos)? || target.check_find(&pos)?{
-            pos.clear();
-            get_uniform_to_vec_nonstandard(&mut rng2, &mut pos, -10.0, 10.0);
+            get_ball_to_vec(&mut rng2, &mut pos.coordinate, 10.0);
         }
 
         assert_eq!(agent1?, ContPassiveExpAgent{
@@ -0,0 +1,346 @@
+// Module for Continous Passive Agent following fractional Brownian motion
+//
+// 각 성분이 독립인 fractional Brownian motion
+//      < (x(t) - x(0))^2 > = 2 K t^{2H}            (성분마다)
+// 을 따르는 searcher. H < 1/2 이면 subdiffusion, H > 1/2 이면 superdiffusion.
+// increment 사이에 긴 memory가 있으므로 ConstStep의 grid 위에서 전체 path의 increment를
+// Davies-Harte method로 미리 생성해두고, 매 step마다 하나씩 꺼내 쓴다.
+// 따라서 일정하지 않은 time step (ExponentialStep 등)과는 함께 사용할 수 없다.
+// 한 번에 생성하는 increment의 수는 horizon으로 제한되며, 그보다 오래 걸리는 search는 TooLongHorizon으로 끝난다.
+// 경계에서의 reflection은 Brownian searcher와 마찬가지로 SystemCore::check_bc가 맡는다.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Stateful};
+use crate::random_mod::fgn::FGNGenerator;
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveFBMAgent{         // 연속한 시스템에서 fractional Brownian motion으로 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub mtype : MoveType,                   // Type of random movement. generalized diffusion coefficient K
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub hurst : f64,                        // Hurst exponent
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub dt : f64,                           // time step of grid. set_time_grid 이전에는 0
+    pub max_horizon : usize,                // maximal number of increments generated at once
+    pub horizon : usize,                    // number of increments generated at once
+    pub step : usize,                       // number of increments used
+    increments : Vec<Vec<f64>>,             // increments of each component
+    generator : Option<FGNGenerator>,
+}
+
+impl ContPassiveFBMAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(mtype : MoveType, hurst : f64, max_horizon : usize, pos : Position<f64>) -> Self{
+        // mtype : Random walk characteristic
+        // hurst : Hurst exponent
+        // max_horizon : 한 번에 생성하는 increment의 최대 개수. tmax / dt가 이보다 크면 (tmax가 무한대인 경우 포함) max_horizon step까지만 생성한다.
+        // pos : initial position of agent
+
+        ContPassiveFBMAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::SpecificPosition(pos.clone()),
+            hurst,
+            dim : pos.dim(),
+            pos,
+            dt : 0f64,
+            max_horizon,
+            horizon : 0,
+            step : 0,
+            increments : vec![],
+            generator : None,
+        }
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   mtype : MoveType, hurst : f64, max_horizon : usize) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+        // increment는 set_time_grid 이후 renew_state에서 생성된다.
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        let mut agent = ContPassiveFBMAgent::new(mtype, hurst, max_horizon, pos);
+        agent.itype = InitType::Uniform;
+        Ok(agent)
+    }
+
+    pub fn set_time_grid<T : TimeGrid>(&mut self, timeiter : &T) -> Result<(), Error>{
+        // Time iterator의 grid에 맞춰 increment를 생성할 준비를 한다.
+        // Constant time step이 아닌 경우 NonConstantTimeStep 에러를 반환한다.
+        // tmax까지의 step 수와 max_horizon 중 작은 만큼의 increment를 한 번에 생성한다.
+
+        let dt : f64 = match timeiter.time_type(){
+            TimeType::Constant(dt) => dt,
+            _ => {
+                return Err(Error::make_error_syntax(ErrorCode::NonConstantTimeStep));
+            }
+        };
+        let tmax : f64 = timeiter.tmax();
+        if dt <= 0f64 || tmax.is_nan() || self.max_horizon == 0{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let horizon : usize = if tmax / dt < self.max_horizon as f64{
+            (tmax / dt).floor() as usize + 1
+        }
+        else{
+            self.max_horizon
+        };
+
+        self.dt = dt;
+        self.horizon = horizon;
+        self.generator = Some(FGNGenerator::new(self.hurst, horizon)?);
+        self.increments.clear();
+        self.step = 0;
+        Ok(())
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 위치와 함께 increment의 path도 새로 뽑는다.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+        self.renew_state(rng)
+    }
+
+    fn coeff_diff(&self) -> Result<f64, Error>{
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) if coeff_diff >= 0f64 => Ok(coeff_diff),
+            MoveType::Brownian(_) => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+}
+
+impl_argument_trait!(ContPassiveFBMAgent, "Agent", ContPassiveFBMAgentArguments, 5,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    mtype, MoveType, "Random walk Characterstic. ex) 1.0 : generalized diffusion coefficient K, <x^2> = 2K t^{2H}",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    hurst, f64, "Hurst exponent of fractional Brownian motion. 0 < H < 1",
+    max_horizon, usize, "Maximal number of increments generated at once. Longer searches end with error. ex) 65536",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveFBMAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveFBMAgentArguments) -> Result<Vec<Self>, Error>{
+        let pos : Position<f64> = match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) => p.clone(),
+            _ => Position::new(vec![]),     // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+        };
+        let mut agent = Self::new(argument.mtype, argument.hurst, argument.max_horizon, pos);
+        agent.agent_type = argument.agent_type;
+        agent.itype = argument.itype.clone();
+        let mut agents = vec![agent; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveFBMAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Stateful<f64, f64> for ContPassiveFBMAgent{
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>{
+        // 성분마다 독립인 fGn path를 새로 생성한다. set_time_grid가 먼저 호출되어야 한다.
+        let scale : f64 = (2f64 * self.coeff_diff()?).sqrt() * self.dt.powf(self.hurst);
+        let generator = match &mut self.generator{
+            Some(generator) => generator,
+            None => {
+                return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+            }
+        };
+
+        self.increments.resize(self.dim, vec![]);
+        for path in self.increments.iter_mut(){
+            generator.generate_to_vec(rng, path);
+            for x in path.iter_mut(){
+                *x *= scale;
+            }
+        }
+        self.step = 0;
+        Ok(())
+    }
+
+    fn evolve_to_vec(&mut self, _rng : &mut Pcg64, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // 미리 생성한 path의 다음 increment를 vec에 더해준다.
+        // rng : Random number generator. path는 renew_state에서 생성되므로 사용하지 않는다.
+        // dt : Time step size. set_time_grid에서 정한 grid의 dt와 같아야 한다.
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim() || self.dim != self.increments.len(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if (dt - self.dt).abs() > 1e-10 * self.dt{
+            return Err(Error::make_error_syntax(ErrorCode::NonConstantTimeStep));
+        }
+        if self.step >= self.horizon{
+            return Err(Error::make_error_syntax(ErrorCode::TooLongHorizon));
+        }
+
+        for (i, path) in self.increments.iter().enumerate(){
+            vec[i] += path[self.step];
+        }
+        self.step += 1;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_msd() -> Result<(), Error>{
+        // MSD = 2 K t^{2H}
+        let mut rng = rng_seed(12341234);
+        let (n, coeff_diff, dt) : (usize, f64, f64) = (5000, 0.5, 0.1);
+        let mut timeiter = ConstStep::new(dt)?;
+        timeiter.set_tmax(10.0)?;
+
+        for hurst in vec![0.3, 0.75]{
+            let mut agent = ContPassiveFBMAgent::new(MoveType::Brownian(coeff_diff), hurst, 1000, Position::new(vec![0.0, 0.0]));
+            agent.set_time_grid(&timeiter)?;
+            assert_eq!(agent.horizon, 101);
+
+            let mut msd : Vec<f64> = vec![0f64; 3];
+            let mut disp = Position::new(vec![0.0, 0.0]);
+            for _i in 0..n{
+                agent.renew_state(&mut rng)?;
+                disp.clear();
+                for (j, (_time, dt)) in timeiter.into_diff().skip(1).enumerate(){
+                    agent.evolve_to_vec(&mut rng, dt, &mut disp)?;
+                    match j + 1{
+                        1 => msd[0] += disp[0].powi(2) / n as f64,
+                        10 => msd[1] += disp[0].powi(2) / n as f64,
+                        100 => msd[2] += disp[1].powi(2) / n as f64,
+                        _ => (),
+                    }
+                }
+                assert_eq!(agent.step, 100);
+            }
+
+            for (m, t) in msd.iter().zip(vec![0.1, 1.0, 10.0]){
+                let answer : f64 = 2f64 * coeff_diff * f64::powf(t, 2f64 * hurst);
+                assert!((m - answer).abs() < 0.06 * answer, "{} {} {}", hurst, m, answer);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_grid() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let mut agent = ContPassiveFBMAgent::new(MoveType::Brownian(1.0), 0.3, 1000, Position::new(vec![0.0]));
+        let mut disp = Position::new(vec![0.0]);
+
+        // grid가 정해지지 않음
+        assert_eq!(agent.renew_state(&mut rng), Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+
+        // 일정하지 않은 time step
+        let timeiter = ExponentialStep::new(1e-3, 1e-1, 10)?;
+        assert_eq!(agent.set_time_grid(&timeiter), Err(Error::make_error_syntax(ErrorCode::NonConstantTimeStep)));
+
+        let mut timeiter = ConstStep::new(0.1)?;
+        timeiter.set_tmax(0.3)?;
+        agent.set_time_grid(&timeiter)?;
+        agent.renew_state(&mut rng)?;
+        assert_eq!(agent.evolve_to_vec(&mut rng, 0.2, &mut disp),
+                   Err(Error::make_error_syntax(ErrorCode::NonConstantTimeStep)));
+        for _i in 0..agent.horizon{
+            agent.evolve_to_vec(&mut rng, 0.1, &mut disp)?;
+        }
+        assert_eq!(agent.evolve_to_vec(&mut rng, 0.1, &mut disp),
+                   Err(Error::make_error_syntax(ErrorCode::TooLongHorizon)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveFBMAgentArguments::new(MoveType::Brownian(1f64), InitType::Uniform, 0.3, 1000, 3);
+        let agents = ContPassiveFBMAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].hurst, 0.3);
+        assert_eq!(agents[0].max_horizon, 1000);
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let mut agent = ContPassiveFBMAgent::new_uniform(&system, &target, &mut rng, MoveType::Brownian(1f64), 0.3, 1000)?;
+
+        // tmax가 무한대인 기본 설정에서는 max_horizon만큼 생성한다.
+        let mut timeiter = ConstStep::new(0.01)?;
+        agent.set_time_grid(&timeiter)?;
+        assert_eq!(agent.horizon, 1000);
+        timeiter.set_tmax(0.01 * 5000f64)?;
+        agent.set_time_grid(&timeiter)?;
+        assert_eq!(agent.horizon, 1000);
+        timeiter.set_tmax(1.0)?;
+        agent.set_time_grid(&timeiter)?;
+        assert_eq!(agent.horizon, 101);
+        agent.renew_uniform(&system, &target, &mut rng)?;
+        assert!(system.check_inclusion(&agent.pos)?);
+
+        agent.max_horizon = 0;
+        assert_eq!(agent.set_time_grid(&timeiter), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
+}
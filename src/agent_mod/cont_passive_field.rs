@@ -0,0 +1,288 @@
+// Module for Continous Passive Agent in External Field
+//
+// Overdamped Langevin equation  dx = D F(x) dt + sqrt(2D) dW
+// force F는 k_B T 단위로 주어진다. 즉, 정상상태 분포는 exp(-V(x)) 이다.
+// random_move는 Euler-Maruyama step이며, 다른 scheme은 IntegratorSimulation의 Integrator로 적분한다.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Passive, Drift};
+
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveFieldAgent{        // 연속한 시스템에서 external field를 받으며 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub mtype : MoveType,                   // Type of random movement
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub field : FieldType,                  // External field
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+}
+
+impl ContPassiveFieldAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(mtype : MoveType, field : FieldType, pos : Position<f64>) -> Self{
+        // mtype : Random walk characteristic
+        // field : external field
+        // pos : initial position of agent
+
+        ContPassiveFieldAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::SpecificPosition(pos.clone()),
+            field,
+            dim : pos.dim(),
+            pos,
+        }
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64, mtype : MoveType, field : FieldType) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+        // sys : system configuration
+        // target : target configuration
+        // rng : random number generator
+        // mtype : random walk characteristic
+        // field : external field
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        Ok(ContPassiveFieldAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::Uniform,
+            field,
+            dim : pos.dim(),
+            pos,
+        })
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 있는 agent를 재활용하도록 하자.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+
+        Ok(())
+    }
+}
+
+impl_argument_trait!(ContPassiveFieldAgent, "Agent", ContPassiveFieldAgentArguments, 4,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    mtype, MoveType, "Random walk Characterstic. ex) 1.0 : Brownian with D=1 / Levy : Levy walk",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    field, FieldType, "External field in unit of k_B T. ex) Free, Drift(1:0), Harmonic(0:0,1.0), Radial(0:0,0.5)",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveFieldAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveFieldAgentArguments) -> Result<Vec<Self>, Error>{
+        let dim : usize;
+        let pos : Position<f64>;
+
+        match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) =>{
+                dim = p.dim();
+                pos = p.clone();
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
+        }
+        let mut agents = vec![Self{
+            agent_type      : argument.agent_type,
+            mtype           : argument.mtype,
+            itype           : argument.itype.clone(),
+            field           : argument.field.clone(),
+            dim,
+            pos,
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveFieldAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Passive<f64, f64> for ContPassiveFieldAgent{
+    fn random_move(&self, rng : &mut Pcg64, dt : f64) -> Result<Position<f64>, Error>{
+        // drift를 포함한 한 step의 displacement를 반환
+        // rng : random number generator
+        // dt : time stpe size
+
+        let mut mv : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_move_to_vec(rng, dt, &mut mv)?;
+        Ok(mv)
+    }
+
+    fn random_move_to_vec(&self, rng: &mut Pcg64, dt: f64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // drift를 포함한 한 step의 displacement를 주어진 vec 행렬에 더해준다.
+        // rng : Random number generator
+        // dt : Time step size
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim(){    // agent가 움직이는 공간의 dimension과 주어진 vec의 dimension이 다르면?
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        // scheme은 simulation argument에서만 고르도록, agent 혼자 움직일 때는 Euler-Maruyama를 사용한다.
+        let force : Position<f64> = Position::new(vec![0f64; self.dim]);
+        Integrator::new(IntegratorType::EulerMaruyama, 1).step_to_vec(rng, 0, self, &force, dt, vec)
+    }
+}
+
+impl Drift<f64, f64> for ContPassiveFieldAgent{
+    fn coeff_diff(&self) -> Result<f64, Error>{
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) => Ok(coeff_diff),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    fn drift_to_vec(&self, pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // D F(x) 를 vec에 더해준다.
+        let mut force : Position<f64> = Position::new(vec![0f64; pos.dim()]);
+        self.field.force_to_vec(pos, &mut force)?;
+        force.mut_scalar_mul(self.coeff_diff()?);
+        vec.mut_add(&force);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+    use crate::random_mod::get_gaussian_vec;
+
+    #[test]
+    fn test_new(){
+        let pos = Position::<f64>::new(vec![0.0, 0.0]);
+        let field = FieldType::Drift(Position::new(vec![1.0, 0.0]));
+        let agent1 = ContPassiveFieldAgent::new(MoveType::Brownian(1f64), field.clone(), pos.clone());
+        assert_eq!(agent1, ContPassiveFieldAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype   : MoveType::Brownian(1f64),
+            itype   : InitType::SpecificPosition(pos.clone()),
+            field   : field,
+            dim     : 2,
+            pos     : pos.clone(),
+        });
+    }
+
+    #[test]
+    fn test_drift() -> Result<(), Error>{
+        // 평균 displacement는 D F t
+        let mut rng = rng_seed(12341234);
+        let n : usize = 100000;
+        let dt : f64 = 0.1;
+
+        let field = FieldType::Drift(Position::new(vec![2.0, 0.0]));
+        let agent = ContPassiveFieldAgent::new(MoveType::Brownian(0.5), field.clone(), Position::new(vec![0.0, 0.0]));
+        let mut mean = Position::new(vec![0.0, 0.0]);
+        let mut mv = Position::new(vec![0.0, 0.0]);
+        for _i in 0..n{
+            mv.clear();
+            agent.random_move_to_vec(&mut rng, dt, &mut mv)?;
+            mean.mut_add(&mv);
+        }
+        mean.mut_scalar_mul(1f64 / n as f64);
+        assert!((mean[0] - 0.1).abs() < 0.01);
+        assert!(mean[1].abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_harmonic_relaxation() -> Result<(), Error>{
+        // 노이즈가 없다면 x(dt) = exp(-D k dt). EM은 1차, Heun은 2차까지 맞춘다
+        let mut rng = rng_seed(12341234);
+        let field = FieldType::Harmonic(Position::new(vec![0.0]), 1.0);
+        let dt : f64 = 0.1;
+
+        let agent = ContPassiveFieldAgent::new(MoveType::Brownian(1.0), field.clone(), Position::new(vec![1.0]));
+        let zero = Position::new(vec![0.0]);
+
+        let mut rng1 = rng_seed(1234);
+        let mut rng2 = rng_seed(1234);
+        let noise : f64 = (2f64 * dt).sqrt() * get_gaussian_vec(&mut rng_seed(1234), 1)[0];
+        assert!((agent.random_move(&mut rng1, dt)?[0] - (- dt + noise)).abs() < 1e-10);
+
+        // 다른 scheme은 Integrator로 적분
+        let mut heun = Position::new(vec![0.0]);
+        Integrator::new(IntegratorType::Heun, 1).step_to_vec(&mut rng2, 0, &agent, &zero, dt, &mut heun)?;
+        assert!((heun[0] - (- dt + 0.5 * dt * dt - 0.5 * dt * noise + noise)).abs() < 1e-10);
+
+        let mut vec = Position::new(vec![0.0, 0.0]);
+        assert_eq!(agent.random_move_to_vec(&mut rng, dt, &mut vec),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveFieldAgentArguments::new(MoveType::Brownian(1f64), InitType::Uniform,
+                            FieldType::from_str("Harmonic(0:0,2.0)")?, 3);
+        let agents = ContPassiveFieldAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].field, FieldType::Harmonic(Position::new(vec![0.0, 0.0]), 2.0));
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+// Module for Continous Passive Agent with Heterogeneous Diffusion
+//
+// Overdamped Langevin equation  dx = D(x) F(x) dt + alpha div D(x) dt + sqrt(2 D(x)) dW  (Ito form)
+// D(x)는 anisotropic tensor이거나 위치에 따라 변하는 scalar일 수 있다.
+// alpha는 noise convention에 따라 Ito : 0, Stratonovich : 1/2, Isothermal : 1 이다.
+// force F는 k_B T 단위로 주어진다.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Passive};
+use crate::random_mod::{get_gaussian_to_vec};
+
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveHeteroAgent{       // 연속한 시스템에서 위치에 따라 다른 diffusion을 가지며 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub diffusion : DiffusionType,          // Diffusion tensor or profile
+    pub convention : NoiseConvention,       // Interpretation of multiplicative noise
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub field : FieldType,                  // External field
+    pub scheme : IntegratorType,            // Numerical scheme for integration
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub noise_factor : Vec<f64>,            // Cholesky factor of diffusion tensor. dimension이 정해질 때 한번만 계산한다.
+}
+
+impl ContPassiveHeteroAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(diffusion : DiffusionType, convention : NoiseConvention, field : FieldType,
+               scheme : IntegratorType, pos : Position<f64>) -> Result<Self, Error>{
+        // diffusion : diffusion tensor or profile
+        // convention : noise convention
+        // field : external field
+        // scheme : numerical scheme
+        // pos : initial position of agent
+
+        let noise_factor : Vec<f64> = diffusion.noise_factor(pos.dim())?;
+        Ok(ContPassiveHeteroAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            diffusion,
+            convention,
+            itype : InitType::SpecificPosition(pos.clone()),
+            field,
+            scheme,
+            dim : pos.dim(),
+            pos,
+            noise_factor,
+        })
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   diffusion : DiffusionType, convention : NoiseConvention, field : FieldType,
+                   scheme : IntegratorType) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+        let noise_factor : Vec<f64> = diffusion.noise_factor(pos.dim())?;
+
+        Ok(ContPassiveHeteroAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            diffusion,
+            convention,
+            itype : InitType::Uniform,
+            field,
+            scheme,
+            dim : pos.dim(),
+            pos,
+            noise_factor,
+        })
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 있는 agent를 재활용하도록 하자.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+                self.noise_factor = self.diffusion.noise_factor(self.dim)?;
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+
+        Ok(())
+    }
+
+    pub fn drift_to_vec(&self, pos : &Position<f64>, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // Ito 형식의 drift (D(x) F(x) + alpha div D(x)) dt 를 vec에 더해준다.
+        let mut drift : Position<f64> = Position::new(vec![0f64; self.dim]);
+        let mut force : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.field.force_to_vec(pos, &mut force)?;
+        self.diffusion.mobility_to_vec(pos, &force, &mut drift)?;
+
+        let alpha : f64 = self.convention.alpha();
+        if alpha != 0f64 && !self.diffusion.is_constant(){
+            let mut grad : Position<f64> = Position::new(vec![0f64; self.dim]);
+            self.diffusion.gradient_to_vec(pos, &mut grad)?;
+            grad.mut_scalar_mul(alpha);
+            drift.mut_add(&grad);
+        }
+
+        drift.mut_scalar_mul(dt);
+        vec.mut_add(&drift);
+        Ok(())
+    }
+}
+
+impl_argument_trait!(ContPassiveHeteroAgent, "Agent", ContPassiveHeteroAgentArguments, 6,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    diffusion, DiffusionType, "Diffusion. ex) 1.0 : Isotropic / Diagonal(1:1:10), Tensor(1:0.5:0.5:2) : Anisotropic / Layered(axis,x0,D1,D2,width), Region(center,radius,D_in,D_out,width) : Space-dependent",
+    convention, NoiseConvention, "Convention of multiplicative noise. ex) Ito, Stratonovich, Isothermal",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    field, FieldType, "External field in unit of k_B T. ex) Free, Drift(1:0), Harmonic(0:0,1.0), Radial(0:0,0.5)",
+    scheme, IntegratorType, "Numerical scheme. ex) EM : Euler-Maruyama / Heun : Stochastic Heun",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveHeteroAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveHeteroAgentArguments) -> Result<Vec<Self>, Error>{
+        let dim : usize;
+        let pos : Position<f64>;
+
+        match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) =>{
+                dim = p.dim();
+                pos = p.clone();
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
+        }
+        let mut agents = vec![Self{
+            agent_type      : argument.agent_type,
+            diffusion       : argument.diffusion.clone(),
+            convention      : argument.convention,
+            itype           : argument.itype.clone(),
+            field           : argument.field.clone(),
+            scheme          : argument.scheme,
+            dim,
+            pos,
+            noise_factor    : Vec::new(),
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+
+        // dimension을 아직 모르면 (Uniform 등) renew_uniform에서 확인한다.
+        for agent in agents.iter_mut().filter(|agent| agent.dim != 0){
+            agent.noise_factor = argument.diffusion.noise_factor(agent.dim)?;
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveHeteroAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Passive<f64, f64> for ContPassiveHeteroAgent{
+    fn random_move(&self, rng : &mut Pcg64, dt : f64) -> Result<Position<f64>, Error>{
+        // drift를 포함한 한 step의 displacement를 반환
+        // rng : random number generator
+        // dt : time stpe size
+
+        let mut mv : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_move_to_vec(rng, dt, &mut mv)?;
+        Ok(mv)
+    }
+
+    fn random_move_to_vec(&self, rng: &mut Pcg64, dt: f64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // drift를 포함한 한 step의 displacement를 주어진 vec 행렬에 더해준다.
+        // noise의 크기는 step 시작점에서 계산하고 (Ito), convention의 차이는 drift에 포함된다.
+        // rng : Random number generator
+        // dt : Time step size
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim(){    // agent가 움직이는 공간의 dimension과 주어진 vec의 dimension이 다르면?
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut xi : Position<f64> = Position::new(vec![0f64; self.dim]);
+        let mut noise : Position<f64> = Position::new(vec![0f64; self.dim]);
+        get_gaussian_to_vec(rng, &mut xi);
+        self.diffusion.noise_to_vec(&self.noise_factor, &self.pos, &xi, dt, &mut noise)?;
+
+        match self.scheme{
+            IntegratorType::EulerMaruyama => {
+                self.drift_to_vec(&self.pos, dt, vec)?;
+            },
+            IntegratorType::Heun => {
+                // predictor : y = x + a(x) dt + noise
+                // corrector : x' = x + (a(x) + a(y)) dt / 2 + noise
+                let mut predictor : Position<f64> = noise.clone();
+                self.drift_to_vec(&self.pos, dt, &mut predictor)?;
+                predictor.mut_add(&self.pos);
+
+                self.drift_to_vec(&self.pos, 0.5 * dt, vec)?;
+                self.drift_to_vec(&predictor, 0.5 * dt, vec)?;
+            },
+            IntegratorType::LeimkuhlerMatthews | IntegratorType::StrongOrder15 => {
+                // additive noise를 가정한 scheme이므로 위치에 따라 변하는 noise에는 사용할 수 없다.
+                return Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided));
+            },
+        }
+        vec.mut_add(&noise);
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_anisotropic_variance() -> Result<(), Error>{
+        // 공분산은 2 D dt
+        let mut rng = rng_seed(12341234);
+        let n : usize = 100000;
+        let dt : f64 = 0.1;
+
+        let agent = ContPassiveHeteroAgent::new(DiffusionType::Tensor(Position::new(vec![1.0, 0.5, 0.5, 2.0])),
+                        NoiseConvention::Ito, FieldType::Free, IntegratorType::EulerMaruyama, Position::new(vec![0.0, 0.0]))?;
+        let mut cov : Vec<f64> = vec![0f64; 4];
+        let mut mv = Position::new(vec![0.0, 0.0]);
+        for _i in 0..n{
+            mv.clear();
+            agent.random_move_to_vec(&mut rng, dt, &mut mv)?;
+            for j in 0..4{
+                cov[j] += mv[j / 2] * mv[j % 2] / n as f64;
+            }
+        }
+        let answer : Vec<f64> = vec![0.2, 0.1, 0.1, 0.4];
+        for j in 0..4{
+            assert!((cov[j] - answer[j]).abs() < 0.01);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_noise_induced_drift() -> Result<(), Error>{
+        // 평균 displacement는 alpha grad D dt
+        let mut rng = rng_seed(12341234);
+        let n : usize = 200000;
+        let dt : f64 = 0.1;
+        let diffusion = DiffusionType::Layered(0, 0.0, 0.5, 1.5, 1.0);          // grad D = 0.5 at x = 0
+
+        for (convention, answer) in vec![(NoiseConvention::Ito, 0f64),
+                                        (NoiseConvention::Stratonovich, 0.25 * dt),
+                                        (NoiseConvention::Isothermal, 0.5 * dt)]{
+            let agent = ContPassiveHeteroAgent::new(diffusion.clone(), convention, FieldType::Free,
+                            IntegratorType::EulerMaruyama, Position::new(vec![0.0]))?;
+            let mut drift = Position::new(vec![0.0]);
+            agent.drift_to_vec(&agent.pos, dt, &mut drift)?;
+            assert!((drift[0] - answer).abs() < 1e-12);
+
+            let mut mean : f64 = 0f64;
+            for _i in 0..n{
+                mean += agent.random_move(&mut rng, dt)?[0] / n as f64;
+            }
+            assert!((mean - answer).abs() < 5e-3);               // standard error ~ 1e-3
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveHeteroAgentArguments::new(DiffusionType::from_str("Diagonal(1:1:10)")?,
+                            NoiseConvention::from_str("Isothermal")?, InitType::Uniform,
+                            FieldType::Free, IntegratorType::EulerMaruyama, 2);
+        let agents = ContPassiveHeteroAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[1].diffusion, DiffusionType::Diagonal(Position::new(vec![1.0, 1.0, 10.0])));
+        assert_eq!(agents[1].convention, NoiseConvention::Isothermal);
+
+        // 양의 정부호가 아닌 tensor는 받지 않는다.
+        let tensor = DiffusionType::Tensor(Position::new(vec![1.0, 2.0, 2.0, 1.0]));
+        let argument = ContPassiveHeteroAgentArguments::new(tensor.clone(), NoiseConvention::Ito,
+                            InitType::SpecificPosition(Position::new(vec![0.0, 0.0])),
+                            FieldType::Free, IntegratorType::EulerMaruyama, 2);
+        assert_eq!(ContPassiveHeteroAgent::convert_from(&argument).err(),
+                   Some(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(ContPassiveHeteroAgent::new(tensor, NoiseConvention::Ito, FieldType::Free,
+                            IntegratorType::EulerMaruyama, Position::new(vec![0.0, 0.0])).err(),
+                   Some(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+
+        // factor는 tensor가 정해질 때 한번만 계산된다.
+        let argument = ContPassiveHeteroAgentArguments::new(DiffusionType::Tensor(Position::new(vec![4.0, 2.0, 2.0, 5.0])),
+                            NoiseConvention::Ito, InitType::SpecificPosition(Position::new(vec![0.0, 0.0])),
+                            FieldType::Free, IntegratorType::EulerMaruyama, 1);
+        let agents = ContPassiveHeteroAgent::convert_from(&argument)?;
+        assert_eq!(agents[0].noise_factor, vec![2.0, 0.0, 1.0, 2.0]);
+        Ok(())
+    }
+}
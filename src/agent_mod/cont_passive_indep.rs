@@ -1,7 +1,8 @@
 // Module for Continous Passive Independent Agent
 
 use crate::prelude::*;
-use crate::agent_mod::{Passive};
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Passive, Drift};
 use crate::random_mod::{get_gaussian_vec, get_gaussian_to_vec_nonstandard};
 
 
@@ -39,12 +40,7 @@ impl ContPassiveIndepAgent{
         // mtype : random walk characteristic
 
         let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
-        loop{
-            sys.random_pos_to_vec(rng, &mut pos)?;   // System 내부의 random position을 받는다
-            if !target.check_find(&pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                break;
-            }
-        }
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
 
         Ok(ContPassiveIndepAgent{
             agent_type : AgentType::ContinuousPassiveIndependent,
@@ -67,12 +63,7 @@ impl ContPassiveIndepAgent{
                 self.dim = self.pos.dim();
             }
         }
-        loop{
-            sys.random_pos_to_vec(rng, &mut self.pos)?;   // System 내부의 random position을 받는다
-            if !target.check_find(&self.pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                break;
-            }
-        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
 
         Ok(())
     }
@@ -81,12 +72,12 @@ impl ContPassiveIndepAgent{
 impl_argument_trait!(ContPassiveIndepAgent, "Agent", ContPassiveIndepAgentArguments, 3,
     agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
     mtype, MoveType, "Random walk Characterstic. ex) 1.0 : Brownian with D=1 / Levy : Levy walk",
-    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
     num_agent, usize, "Number of Agents");
 
 impl ContPassiveIndepAgent{
     #[allow(dead_code)]
-    pub fn convert_from(argument : &ContPassiveIndepAgentArguments) -> Vec<Self>{
+    pub fn convert_from(argument : &ContPassiveIndepAgentArguments) -> Result<Vec<Self>, Error>{
         let dim : usize;
         let pos : Position<f64>;
 
@@ -98,16 +89,30 @@ impl ContPassiveIndepAgent{
             InitType::<f64>::SpecificPosition(p) =>{
                 dim = p.dim();
                 pos = p.clone();
-            }
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
         }
-        vec![Self{
+        let mut agents = vec![Self{
             agent_type   : argument.agent_type,
             mtype           : argument.mtype,
             itype           : argument.itype.clone(),
             dim             : dim,
             pos             : pos,
-        }; argument.num_agent]
-
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
     }
 }
 
@@ -193,6 +198,23 @@ impl Passive<f64, f64> for ContPassiveIndepAgent{
 }
 
 
+impl Drift<f64, f64> for ContPassiveIndepAgent{
+    fn coeff_diff(&self) -> Result<f64, Error>{
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) => Ok(coeff_diff),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    fn drift_to_vec(&self, pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // 외부 field가 없으므로 drift는 0. interaction은 Integrator에 force로 따로 준다.
+        if self.dim != pos.dim() || self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests{
     #[allow(unused_imports)]
@@ -215,7 +237,7 @@ mod tests{
     fn test_uniform() -> Result<(), Error>{
         use crate::system_mod::cont_circ::ContCircSystem;
         use crate::target_mod::cont_bulk::ContBulkTarget;
-        use crate::random_mod::get_uniform_to_vec_nonstandard;
+        use crate::random_mod::get_ball_to_vec;
 
 
         let mut rng1 = rng_seed(12341234);
@@ -229,8 +251,7 @@ mod tests{
 
         let mut pos = system.position_out_of_system();
         while !system.check_inclusion(&pos)? || target.check_find(&pos)?{
-            pos.clear();
-            get_uniform_to_vec_nonstandard(&mut rng2, &mut pos, -10.0, 10.0);
+            get_ball_to_vec(&mut rng2, &mut pos.coordinate, 10.0);
         }
 
         assert_eq!(agent1?, ContPassiveIndepAgent{
@@ -243,4 +264,40 @@ mod tests{
 
         Ok(())
     }
+
+    #[test]
+    fn test_convert_from_file() -> Result<(), Error>{
+        use crate::system_mod::cont_circ::ContCircSystem;
+        use crate::target_mod::cont_bulk::ContBulkTarget;
+        use std::env::temp_dir;
+
+        let path = temp_dir().join("moledyn_test_indep_init.csv");
+        let path = path.to_str().unwrap();
+        fs::write(path, "1.0,2.0\n-3.0,0.5\n20.0,0.0\n").map_err(Error::make_error_io)?;
+
+        let argument = ContPassiveIndepAgentArguments::new(MoveType::Brownian(1f64),
+                            InitType::FromFile(path.to_string()), 2);
+        let mut agents = ContPassiveIndepAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[1].pos, Position::new(vec![-3.0, 0.5]));
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        agents[0].pos.mut_add(&Position::new(vec![1.0, 1.0]));
+        agents[0].renew_uniform(&system, &target, &mut rng)?;
+        assert_eq!(agents[0].pos, Position::new(vec![1.0, 2.0]));
+
+        // 시스템 밖의 위치는 error
+        let argument = ContPassiveIndepAgentArguments::new(MoveType::Brownian(1f64),
+                            InitType::FromFile(path.to_string()), 3);
+        let mut agents = ContPassiveIndepAgent::convert_from(&argument)?;
+        assert_eq!(agents[2].renew_uniform(&system, &target, &mut rng),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+
+        // 파일이 없으면 panic하지 않고 error
+        fs::remove_file(path).map_err(Error::make_error_io)?;
+        assert!(ContPassiveIndepAgent::convert_from(&argument).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,457 @@
+// Module for Continous Passive Agent following underdamped Langevin dynamics
+//
+// 질량 m, friction coefficient zeta를 갖는 관성 있는 searcher
+//      dx = v dt
+//      m dv = (F(x) - zeta v) dt + sqrt(2 zeta kT) dW,         kT = zeta D  (Einstein relation)
+// 긴 시간에서는 diffusion coefficient D인 Brownian motion이 되고, 짧은 시간 m / zeta 이하에서는 ballistic하다.
+// BAOAB splitting scheme으로 적분한다.
+//      B : v += F dt / 2m,   A : x += v dt / 2,   O : v = exp(- zeta dt / m) v + sqrt(kT / m (1 - exp(-2 zeta dt / m))) xi
+// 한 step의 마지막 B와 다음 step의 처음 B는 같은 위치의 force를 사용하므로 합쳐서 다음 step의 처음에 적용한다.
+// 따라서 vel은 마지막 half kick이 적용되기 전의 velocity이고, synchronize로 맞춰줄 수 있다.
+// 외부 field는 agent가 직접 계산하고, 다른 agent와의 interaction force는 add_force로 더해준다.
+// 경계에서는 SystemCore::check_bc_with_velocity로 위치와 velocity를 함께 반사시킨다.
+//
+// Leimkuhler, B., & Matthews, C. (2013). Rational construction of stochastic numerical methods for molecular sampling.
+// Applied Mathematics Research eXpress, 2013(1), 34-56.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Stateful};
+use crate::random_mod::{get_gaussian_to_vec_nonstandard};
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveLangevinAgent{    // 연속한 시스템에서 underdamped Langevin dynamics를 따라 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub mtype : MoveType,                   // Type of random movement. long time diffusion coefficient D
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub mass : f64,                         // mass of agent
+    pub friction : f64,                     // friction coefficient zeta
+    pub field : FieldType,                  // External force field
+    pub int_type : InteractType,            // Type of interaction between agents
+    pub strength : f64,                     // strength of interaction. 0이면 interaction 없음
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub vel : Position<f64>,                // velocity of agent, before the last half kick
+    pub force : Position<f64>,              // interaction force accumulated at current position
+    pub dt_prev : f64,                      // time step of previous step, whose last half kick is not applied yet
+}
+
+impl ContPassiveLangevinAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(mtype : MoveType, mass : f64, friction : f64, field : FieldType, pos : Position<f64>) -> Self{
+        // mtype : long time diffusion coefficient
+        // mass : mass of agent
+        // friction : friction coefficient
+        // field : external force field
+        // pos : initial position of agent
+        // 처음에는 정지해있다. renew_state에서 Maxwell distribution으로 velocity를 뽑는다.
+
+        let dim : usize = pos.dim();
+        ContPassiveLangevinAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::SpecificPosition(pos.clone()),
+            mass,
+            friction,
+            field,
+            int_type : InteractType::default(),
+            strength : 0f64,
+            dim,
+            pos,
+            vel : Position::new(vec![0f64; dim]),
+            force : Position::new(vec![0f64; dim]),
+            dt_prev : 0f64,
+        }
+    }
+
+    pub fn set_interaction(&mut self, int_type : InteractType, strength : f64){
+        // interaction을 설정한다. strength가 0이 아니면 Interacting agent가 된다.
+        self.int_type = int_type;
+        self.strength = strength;
+        self.agent_type = if strength == 0f64 { AgentType::ContinuousPassiveIndependent }
+                          else { AgentType::ContinuousPassiveInteracting };
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   mtype : MoveType, mass : f64, friction : f64, field : FieldType) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+        // velocity는 Maxwell distribution에서 뽑는다.
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        let mut agent = ContPassiveLangevinAgent::new(mtype, mass, friction, field, pos);
+        agent.itype = InitType::Uniform;
+        agent.renew_state(rng)?;
+        Ok(agent)
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 위치와 함께 velocity도 새로 뽑는다.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+        self.renew_state(rng)
+    }
+
+    pub fn temperature(&self) -> Result<f64, Error>{
+        // kT = zeta D
+        match self.mtype{
+            MoveType::Brownian(coeff_diff) if coeff_diff >= 0f64 => Ok(self.friction * coeff_diff),
+            MoveType::Brownian(_) => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    pub fn relaxation_time(&self) -> f64{
+        // velocity relaxation time m / zeta. 이보다 짧은 시간에서는 ballistic
+        self.mass / self.friction
+    }
+
+    pub fn add_force(&mut self, force : &Position<f64>) -> Result<(), Error>{
+        // 현재 위치에서 받는 interaction force를 더해준다. 다음 evolve_to_vec, synchronize에서 사용된 후 지워진다.
+        if force.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.force.mut_add(force);
+        Ok(())
+    }
+
+    fn total_force(&self) -> Result<Position<f64>, Error>{
+        // 현재 위치에서의 external force와 interaction force의 합
+        let mut force : Position<f64> = self.force.clone();
+        self.field.force_to_vec(&self.pos, &mut force)?;
+        Ok(force)
+    }
+
+    pub fn synchronize(&mut self) -> Result<(), Error>{
+        // 아직 적용되지 않은 마지막 half kick을 적용해 vel을 현재 위치에서의 velocity로 맞춘다.
+        // interaction force는 다음 step에서 다시 사용하므로 지우지 않는다.
+        if self.dt_prev > 0f64{
+            let force : Position<f64> = self.total_force()?;
+            for i in 0..self.dim{
+                self.vel[i] += force[i] * self.dt_prev / (2f64 * self.mass);
+            }
+            self.dt_prev = 0f64;
+        }
+        Ok(())
+    }
+
+    pub fn kinetic_energy(&self) -> f64{
+        // m v^2 / 2
+        0.5 * self.mass * self.vel.norm().powi(2)
+    }
+
+    fn check_parameters(&self) -> Result<(), Error>{
+        if self.mass <= 0f64 || self.friction < 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        if let Some(dim) = self.field.dim(){
+            if dim != self.dim{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl_argument_trait!(ContPassiveLangevinAgent, "Agent", ContPassiveLangevinAgentArguments, 8,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    mtype, MoveType, "Random walk Characterstic. ex) 1.0 : long time diffusion coefficient D = kT / zeta",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    mass, f64, "Mass of agent",
+    friction, f64, "Friction coefficient zeta. Velocity relaxes in time mass / zeta",
+    field, FieldType, "External force field. ex) Free, Drift(1:0), Harmonic(0:0,1.0), Radial(0:0,1.0)",
+    int_type, InteractType, "Type of interaction. ex) Exponential(2,0.1), Coulomb2D, Coulomb3D, LennardJones(1.0)",
+    strength, f64, "Strength of interaction. 0 for independent agents",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveLangevinAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveLangevinAgentArguments) -> Result<Vec<Self>, Error>{
+        let pos : Position<f64> = match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) => p.clone(),
+            _ => Position::new(vec![]),     // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+        };
+        let mut agent = Self::new(argument.mtype, argument.mass, argument.friction, argument.field.clone(), pos);
+        agent.set_interaction(argument.int_type, argument.strength);
+        agent.itype = argument.itype.clone();
+        let mut agents = vec![agent; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.vel   = Position::new(vec![0f64; p.dim()]);
+                agent.force = Position::new(vec![0f64; p.dim()]);
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveLangevinAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Interaction<f64, f64> for ContPassiveLangevinAgent{
+    // 양수인 force는 척력
+    fn potential(&self, r : f64) -> f64{
+        let s : f64 = self.strength;
+        match self.int_type{
+            InteractType::Exponential(_dim, gamma) => s * (- r / gamma).exp(),
+            InteractType::Coulomb2D => - s * r.ln() / (2f64 * PI),
+            InteractType::Coulomb3D => s / (4f64 * PI * r),
+            InteractType::LennardJones(ptl_size) => {
+                let x6 : f64 = (ptl_size / r).powi(6);
+                4f64 * s * x6 * (x6 - 1f64)
+            },
+        }
+    }
+
+    fn force(&self, r : f64) -> f64{
+        let s : f64 = self.strength;
+        match self.int_type{
+            InteractType::Exponential(_dim, gamma) => s / gamma * (- r / gamma).exp(),
+            InteractType::Coulomb2D => s / (2f64 * PI * r),
+            InteractType::Coulomb3D => s / (4f64 * PI * r * r),
+            InteractType::LennardJones(ptl_size) => {
+                let x : f64 = ptl_size / r;
+                let x6 : f64 = x.powi(6);
+                24f64 * s / ptl_size * x6 * x * (2f64 * x6 - 1f64)
+            },
+        }
+    }
+}
+
+impl Stateful<f64, f64> for ContPassiveLangevinAgent{
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>{
+        // velocity를 Maxwell distribution N(0, kT / m)에서 뽑는다.
+        self.check_parameters()?;
+        let stddev : f64 = (self.temperature()? / self.mass).sqrt();
+
+        self.vel = Position::new(vec![0f64; self.dim]);
+        get_gaussian_to_vec_nonstandard(rng, &mut self.vel, 0f64, stddev);
+        self.force = Position::new(vec![0f64; self.dim]);
+        self.dt_prev = 0f64;
+        Ok(())
+    }
+
+    fn evolve_to_vec(&mut self, rng : &mut Pcg64, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // BAOAB 한 step의 displacement를 vec에 더해준다. (이전 step의 마지막 B를 포함)
+        // interaction force는 add_force로 미리 더해두어야 한다.
+        // rng : Random number generator
+        // dt : Time step size. 매번 달라도 된다.
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim() || self.dim != self.vel.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let temperature : f64 = self.temperature()?;
+
+        // B
+        let force : Position<f64> = self.total_force()?;
+        let kick : f64 = (self.dt_prev + dt) / (2f64 * self.mass);
+        for i in 0..self.dim{
+            self.vel[i] += force[i] * kick;
+        }
+
+        // A
+        for i in 0..self.dim{
+            vec[i] += 0.5 * dt * self.vel[i];
+        }
+
+        // O
+        let c1 : f64 = (- self.friction * dt / self.mass).exp();
+        let c2 : f64 = (temperature / self.mass * (1f64 - c1 * c1)).sqrt();
+        self.vel.mut_scalar_mul(c1);
+        get_gaussian_to_vec_nonstandard(rng, &mut self.vel, 0f64, c2);
+
+        // A
+        for i in 0..self.dim{
+            vec[i] += 0.5 * dt * self.vel[i];
+        }
+
+        self.force.clear();
+        self.dt_prev = dt;
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_msd() -> Result<(), Error>{
+        // Free particle : MSD = 2D (t - tau (1 - exp(-t / tau))),  tau = m / zeta
+        let mut rng = rng_seed(12341234);
+        let n : usize = 10000;
+        let (coeff_diff, mass, friction, dt) : (f64, f64, f64, f64) = (1.0, 2.0, 1.0, 0.01);
+
+        let mut agent = ContPassiveLangevinAgent::new(MoveType::Brownian(coeff_diff), mass, friction,
+                            FieldType::Free, Position::new(vec![0.0]));
+        let tau : f64 = agent.relaxation_time();
+        let mut msd : Vec<f64> = vec![0f64; 2];
+        let mut disp = Position::new(vec![0.0]);
+        for _i in 0..n{
+            agent.renew_state(&mut rng)?;
+            disp.clear();
+            for j in 1..=2000{
+                agent.evolve_to_vec(&mut rng, dt, &mut disp)?;
+                match j{
+                    50 => msd[0] += disp[0].powi(2) / n as f64,
+                    2000 => msd[1] += disp[0].powi(2) / n as f64,
+                    _ => (),
+                }
+            }
+        }
+
+        for (m, t) in msd.iter().zip(vec![0.5, 20.0]){
+            let answer : f64 = 2f64 * coeff_diff * (t - tau * (1f64 - f64::exp(- t / tau)));
+            assert!((m - answer).abs() < 0.05 * answer, "{} {} {}", t, m, answer);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_equipartition() -> Result<(), Error>{
+        // Harmonic trap : <x^2> = kT / k, <v^2> = kT / m
+        let mut rng = rng_seed(12341234);
+        let (coeff_diff, mass, friction, k) : (f64, f64, f64, f64) = (0.5, 2.0, 1.0, 4.0);
+        let mut agent = ContPassiveLangevinAgent::new(MoveType::Brownian(coeff_diff), mass, friction,
+                            FieldType::Harmonic(Position::new(vec![0.0, 0.0]), k), Position::new(vec![0.0, 0.0]));
+        let temperature : f64 = agent.temperature()?;
+        agent.renew_state(&mut rng)?;
+
+        let n : usize = 200000;
+        let mut disp = Position::new(vec![0.0, 0.0]);
+        let (mut var_x, mut var_v) = (0f64, 0f64);
+        for _i in 0..n{
+            disp.clear();
+            agent.evolve_to_vec(&mut rng, 0.1, &mut disp)?;
+            agent.pos.mut_add(&disp);
+            var_x += agent.pos[0].powi(2) / n as f64;
+            var_v += agent.vel[1].powi(2) / n as f64;
+        }
+        assert!((var_x - temperature / k).abs() < 0.05 * temperature / k, "{}", var_x);
+        assert!((var_v - temperature / mass).abs() < 0.05 * temperature / mass, "{}", var_v);
+        Ok(())
+    }
+
+    #[test]
+    fn test_force_and_reflection() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let mut agent = ContPassiveLangevinAgent::new(MoveType::Brownian(0.0), 1.0, 0.0,
+                            FieldType::Free, Position::new(vec![4.5, 0.0]));
+        agent.renew_state(&mut rng)?;
+        assert_eq!(agent.vel, Position::new(vec![0.0, 0.0]));
+
+        // 일정한 force F = 2 에서 dt = 1 이후 v = 2, x = 1 (T = 0, zeta = 0)
+        let mut disp = Position::new(vec![0.0, 0.0]);
+        agent.add_force(&Position::new(vec![2.0, 0.0]))?;
+        agent.evolve_to_vec(&mut rng, 1.0, &mut disp)?;
+        assert_eq!(disp, Position::new(vec![1.0, 0.0]));
+        agent.add_force(&Position::new(vec![2.0, 0.0]))?;
+        agent.synchronize()?;
+        assert_eq!(agent.vel, Position::new(vec![2.0, 0.0]));
+
+        // 벽에서 위치와 velocity 모두 반사
+        let sys = ContCircSystem::new(5.0, 2);
+        sys.check_bc_with_velocity(&mut agent.pos, &mut disp, &mut agent.vel)?;
+        assert!((&agent.pos - &Position::new(vec![4.5, 0.0])).norm() < 1e-10);
+        assert!((&agent.vel - &Position::new(vec![-2.0, 0.0])).norm() < 1e-10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interaction(){
+        let mut agent = ContPassiveLangevinAgent::new(MoveType::Brownian(1.0), 1.0, 1.0, FieldType::Free,
+                            Position::new(vec![0.0, 0.0]));
+        agent.set_interaction(InteractType::LennardJones(1.0), 2.0);
+        assert_eq!(agent.agent_type, AgentType::ContinuousPassiveInteracting);
+
+        let lj = ContPassiveLJAgent::new(InteractType::LennardJones(1.0), MoveType::Brownian(1.0),
+                            Position::new(vec![0.0, 0.0]), 2.0);
+        for r in vec![0.9, 1.0, 1.5]{
+            assert!((agent.potential(r) - lj.potential(r)).abs() < 1e-10);
+            assert!((agent.force(r) - lj.force(r)).abs() < 1e-10);
+        }
+
+        // force = - dV / dr
+        for int_type in vec![InteractType::Exponential(2, 0.5), InteractType::Coulomb2D, InteractType::Coulomb3D]{
+            agent.set_interaction(int_type, 1.0);
+            let h : f64 = 1e-6;
+            let numeric : f64 = - (agent.potential(1.0 + h) - agent.potential(1.0 - h)) / (2f64 * h);
+            assert!((agent.force(1.0) - numeric).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveLangevinAgentArguments::new(MoveType::Brownian(1f64), InitType::Uniform, 2.0, 0.5,
+                            FieldType::Free, InteractType::LennardJones(1.0), 0.0, 3);
+        let agents = ContPassiveLangevinAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].relaxation_time(), 4.0);
+        assert_eq!(agents[0].agent_type, AgentType::ContinuousPassiveIndependent);
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let agent = ContPassiveLangevinAgent::new_uniform(&system, &target, &mut rng, MoveType::Brownian(1f64),
+                            2.0, 0.5, FieldType::Free)?;
+        assert_eq!(agent.vel.dim(), 2);
+        assert!(agent.vel.norm() > 0f64);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 // Module for Continous Passive Independent Agent
 
 use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
 use crate::random_mod::{get_gaussian_vec, get_gaussian_to_vec_nonstandard};
 
 
@@ -49,12 +50,7 @@ impl ContPassiveMergeAgent{
         // alpha    : exponent of diffusion decrease
 
         let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
-        loop{
-            sys.random_pos_to_vec(rng, &mut pos)?;   // System 내부의 random position을 받는다
-            if !target.check_find(&pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                break;
-            }
-        }
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
 
         Ok(ContPassiveMergeAgent{
             agent_type : AgentType::ContinuousPassiveInteracting,
@@ -82,12 +78,7 @@ impl ContPassiveMergeAgent{
                 self.dim = self.pos.dim();
             }
         }
-        loop{
-            sys.random_pos_to_vec(rng, &mut self.pos)?;   // System 내부의 random position을 받는다
-            if !target.check_find(&self.pos)?{            // 그 random position이 target과 이미 만났는가 확인
-                break;
-            }
-        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
 
         self.size = 1;
         match self.mtype{
@@ -108,14 +99,14 @@ impl_argument_trait!(ContPassiveMergeAgent, "Agent", ContPassiveMergeAgentArgume
     agent_type, AgentType, AgentType::ContinuousPassiveInteracting,
     size,   usize,          1;
     mtype,  MoveType,       "Random walk Characterstic. ex) 1.0 : Brownian with D=1 / Levy : Levy walk",
-    itype,  InitType<f64>,  "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform",
+    itype,  InitType<f64>,  "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
     ptl_radius, f64,            "Radius of particle. When they collide, they merge. ex) 0.1",
     alpha,  f64,            "Exponent of diffusion decrease. D ~ n^alpha ex) 1.0",
     num_agent, usize, "Number of Agent");
 
 impl ContPassiveMergeAgent{
     #[allow(dead_code)]
-    pub fn convert_from(argument : &ContPassiveMergeAgentArguments) -> Vec<Self>{
+    pub fn convert_from(argument : &ContPassiveMergeAgentArguments) -> Result<Vec<Self>, Error>{
         let dim : usize;
         let pos : Position<f64>;
 
@@ -127,9 +118,13 @@ impl ContPassiveMergeAgent{
             InitType::<f64>::SpecificPosition(p) =>{
                 dim = p.dim();
                 pos = p.clone();
-            }
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
         }
-        vec![Self{
+        let mut agents = vec![Self{
             agent_type   : argument.agent_type,
             mtype           : argument.mtype,
             itype           : argument.itype.clone(),
@@ -138,7 +133,18 @@ impl ContPassiveMergeAgent{
             ptl_radius          : argument.ptl_radius,
             size            : 1,
             alpha           : argument.alpha,
-        }; argument.num_agent]
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
     }
 }
 
@@ -283,7 +289,7 @@ mod tests{
     fn test_uniform() -> Result<(), Error>{
         use crate::system_mod::cont_circ::ContCircSystem;
         use crate::target_mod::cont_bulk::ContBulkTarget;
-        use crate::random_mod::get_uniform_to_vec_nonstandard;
+        use crate::random_mod::get_ball_to_vec;
 
 
         let mut rng1 = rng_seed(12341234);
@@ -297,8 +303,7 @@ mod tests{
 
         let mut pos = system.position_out_of_system();
         while !system.check_inclusion(&pos)? || target.check_find(&pos)?{
-            pos.clear();
-            get_uniform_to_vec_nonstandard(&mut rng2, &mut pos, -10.0, 10.0);
+            get_ball_to_vec(&mut rng2, &mut pos.coordinate, 10.0);
         }
 
         assert_eq!(agent1?, ContPassiveMergeAgent{
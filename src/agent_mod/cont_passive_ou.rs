@@ -0,0 +1,418 @@
+// Module for Continous Passive Agent driven by Ornstein-Uhlenbeck noise
+//
+// 각 성분마다
+//      d eta = - eta / tau dt + sqrt(2D) / tau dW          (exponentially correlated noise)
+//      d y   = (- mu y + eta) dt,      y = x - c,  mu = D k  (harmonic trap이 없다면 mu = 0)
+// tau -> 0 이면 diffusion coefficient D인 Brownian motion (trap이 있다면 ContPassiveFieldAgent의 Harmonic)이 된다.
+// (eta, y)는 linear gaussian process이므로 임의의 dt에 대해 정확하게 적분한다.
+
+use crate::prelude::*;
+use crate::agent_mod::types::read_init_positions;
+use crate::agent_mod::{Stateful};
+use crate::random_mod::{get_gaussian};
+
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct OUTransition{                    // (eta, y)의 dt 동안의 transition
+    decay_noise : f64,                  // eta' = decay_noise * eta + ...
+    decay_pos : f64,                    // y' = gain * eta + decay_pos * y + ...
+    gain : f64,
+    var_noise : f64,                    // Var(eta')
+    cov : f64,                          // Cov(eta', y')
+    var_pos : f64,                      // Var(y')
+}
+
+impl OUTransition{
+    fn new(lambda : f64, mu : f64, sigma2 : f64, dt : f64) -> Self{
+        // lambda : 1 / tau
+        // mu : relaxation rate of position
+        // sigma2 : variance rate of noise, 2D / tau^2
+        // dt : time step
+
+        // E(a) = int_0^dt exp(-a u) du
+        let e = |a : f64| if a * dt < 1e-300 { dt } else { - (- a * dt).exp_m1() / a };
+        let decay_noise : f64 = (- lambda * dt).exp();
+        let decay_pos : f64 = (- mu * dt).exp();
+        let var_noise : f64 = sigma2 * e(2f64 * lambda);
+
+        let (gain, cov, var_pos) : (f64, f64, f64);
+        if (lambda + mu) * dt < 1e-4{
+            // g(u) = u - (lambda + mu) u^2 / 2 + ... 에서 상쇄가 일어나므로 전개식 사용
+            gain    = dt - 0.5 * (lambda + mu) * dt * dt;
+            cov     = sigma2 * (dt * dt / 2f64 - (3f64 * lambda + mu) * dt.powi(3) / 6f64);
+            var_pos = sigma2 * (dt.powi(3) / 3f64 - (lambda + mu) * dt.powi(4) / 4f64);
+        }
+        else if (lambda - mu).abs() < 1e-6 * lambda.max(mu){
+            // g(u) = u exp(- lambda u)
+            let a : f64 = 2f64 * lambda;
+            let ex : f64 = (- a * dt).exp();
+            gain    = dt * decay_noise;
+            cov     = sigma2 * (1f64 - ex * (1f64 + a * dt)) / a.powi(2);
+            var_pos = sigma2 * (2f64 - ex * (a * a * dt * dt + 2f64 * a * dt + 2f64)) / a.powi(3);
+        }
+        else{
+            // g(u) = (exp(- mu u) - exp(- lambda u)) / (lambda - mu)
+            let diff : f64 = lambda - mu;
+            gain    = (decay_pos - decay_noise) / diff;
+            cov     = sigma2 * (e(lambda + mu) - e(2f64 * lambda)) / diff;
+            var_pos = sigma2 * (e(2f64 * mu) - 2f64 * e(lambda + mu) + e(2f64 * lambda)) / diff.powi(2);
+        }
+
+        OUTransition{
+            decay_noise,
+            decay_pos,
+            gain,
+            var_noise,
+            cov,
+            var_pos : var_pos.max(0f64),
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPassiveOUAgent{           // 연속한 시스템에서 Ornstein-Uhlenbeck noise를 받으며 Passive하게 움직이는 agent
+    pub agent_type : AgentType,               // Type of agent
+    pub mtype : MoveType,                   // Type of random movement. white noise 극한에서의 diffusion coefficient
+    pub itype : InitType<f64>,              // Type of Initialization
+    pub tau : f64,                          // Correlation time of noise
+    pub trap : FieldType,                   // Free or Harmonic trap binding position
+    pub dim : usize,                        // dimension of space containing agent
+    pub pos : Position<f64>,                // position of agent
+    pub noise : Position<f64>,              // current value of noise, eta
+}
+
+impl ContPassiveOUAgent{
+    // 모든 정보를 제공했을 경우, 새 agent struct를 반환하는 함수
+    pub fn new(mtype : MoveType, tau : f64, trap : FieldType, pos : Position<f64>) -> Self{
+        // mtype : Random walk characteristic
+        // tau : correlation time of noise
+        // trap : Free, or Harmonic trap
+        // pos : initial position of agent
+
+        ContPassiveOUAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::SpecificPosition(pos.clone()),
+            tau,
+            trap,
+            dim : pos.dim(),
+            noise : Position::new(vec![0f64; pos.dim()]),
+            pos,
+        }
+    }
+
+    pub fn new_uniform(sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>, rng : &mut Pcg64,
+                   mtype : MoveType, tau : f64, trap : FieldType) -> Result<Self, Error>{
+        // system과 target이 주어져 있는 상황에서 시스템 domain 안에서 초기위치를 uniform하게 뽑아 agent를 정의해주는 함수
+        // noise는 stationary distribution에서 뽑는다.
+
+        let mut pos : Position<f64> = sys.position_out_of_system();  // 초기값을 위해 무조건 시스템 밖의 벡터를 받도록 한다
+        InitType::<f64>::Uniform.init_to_vec(sys, target, rng, &mut pos)?;     // System 내부, target 밖의 uniform한 위치
+
+        let mut agent = ContPassiveOUAgent{
+            agent_type : AgentType::ContinuousPassiveIndependent,
+            mtype,
+            itype : InitType::Uniform,
+            tau,
+            trap,
+            dim : pos.dim(),
+            noise : Position::new(vec![0f64; pos.dim()]),
+            pos,
+        };
+        agent.renew_state(rng)?;
+        Ok(agent)
+    }
+
+    pub fn renew_uniform(&mut self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                   rng : &mut Pcg64) -> Result<(), Error>{
+        // 위치와 함께 noise도 새로 뽑는다.
+
+        match sys.position_out_of_system_to_vec(&mut self.pos){
+            Ok(()) => (),
+            Err(_) => {
+                self.pos = sys.position_out_of_system();
+                self.dim = self.pos.dim();
+            }
+        }
+        self.itype.init_to_vec(sys, target, rng, &mut self.pos)?;     // Initialization type에 맞는 초기 위치
+        self.renew_state(rng)
+    }
+
+    fn parameters(&self) -> Result<(f64, f64, f64), Error>{
+        // (lambda, mu, sigma2)
+        let coeff_diff : f64 = match self.mtype{
+            MoveType::Brownian(coeff_diff) => coeff_diff,
+            _ => {
+                return Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided));
+            }
+        };
+        if self.tau <= 0f64 || coeff_diff < 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let lambda : f64 = 1f64 / self.tau;
+        let mu : f64 = match &self.trap{
+            FieldType::Free => 0f64,
+            FieldType::Harmonic(center, k) if *k >= 0f64 => {
+                if center.dim() != self.dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                coeff_diff * k
+            },
+            FieldType::Harmonic(_, _) => {
+                return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+            },
+            _ => {
+                return Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided));
+            },
+        };
+        Ok((lambda, mu, 2f64 * coeff_diff * lambda * lambda))
+    }
+}
+
+impl_argument_trait!(ContPassiveOUAgent, "Agent", ContPassiveOUAgentArguments, 5,
+    agent_type, AgentType, AgentType::ContinuousPassiveIndependent;
+    mtype, MoveType, "Random walk Characterstic. ex) 1.0 : diffusion coefficient D in white noise limit",
+    itype, InitType<f64>, "Initialization method. ex) 0,0 : All at 0,0 / Uniform : Uniform / init.xyz : From file / Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)",
+    tau, f64, "Correlation time of Ornstein-Uhlenbeck noise",
+    trap, FieldType, "Harmonic trap binding position. ex) Free, Harmonic(0:0,1.0)",
+    num_agent, usize, "Number of Agents");
+
+impl ContPassiveOUAgent{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPassiveOUAgentArguments) -> Result<Vec<Self>, Error>{
+        let dim : usize;
+        let pos : Position<f64>;
+
+        match &argument.itype{
+            InitType::<f64>::SpecificPosition(p) =>{
+                dim = p.dim();
+                pos = p.clone();
+            },
+            _ => {
+                dim = 0;                            // renew_uniform에서, 혹은 아래에서 agent마다 파일의 위치로 다시 설정
+                pos = Position::new(vec![]);
+            },
+        }
+        let mut agents = vec![Self{
+            agent_type      : argument.agent_type,
+            mtype           : argument.mtype,
+            itype           : argument.itype.clone(),
+            tau             : argument.tau,
+            trap            : argument.trap.clone(),
+            dim,
+            noise           : Position::new(vec![0f64; dim]),
+            pos,
+        }; argument.num_agent];
+
+        if let InitType::<f64>::FromFile(path) = &argument.itype{
+            // 파일에 적힌 위치를 각 agent의 초기 위치로 사용
+            let positions = read_init_positions(path, argument.num_agent)?;
+            for (agent, p) in agents.iter_mut().zip(positions){
+                agent.dim   = p.dim();
+                agent.noise = Position::new(vec![0f64; p.dim()]);
+                agent.itype = InitType::SpecificPosition(p.clone());
+                agent.pos   = p;
+            }
+        }
+        Ok(agents)
+    }
+}
+
+impl AgentCore<f64> for ContPassiveOUAgent{
+    fn pos(&self) -> &Position<f64>{
+        &self.pos
+    }
+
+    // Mutual displacement
+    fn mutual_displacement(&self, other : &Self) -> Result<(Position<f64>, f64), Error>{
+        if self.dim != other.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut disp : Position<f64> = &other.pos - &self.pos;
+        let distance : f64 = disp.norm();
+        disp.mut_scalar_mul(1f64 / distance);
+        Ok((disp, distance))
+    }
+
+    fn mutual_displacement_to_vec(&self, other : &Self, vec : &mut Position<f64>) -> Result<f64, Error>{
+        // return distance, and direction vector on vec
+        if self.dim != other.dim || self.dim != vec.dim() {
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut s = 0f64;
+        for i in 0..self.dim{
+            let x = self.pos.coordinate[i];
+            let y = other.pos.coordinate[i];
+
+            vec[i] = y - x;
+            s += (y - x).powi(2);
+        }
+
+        let distance : f64 = s.sqrt();
+        vec.mut_scalar_mul(1f64 / distance);
+        Ok(distance)
+    }
+
+    fn mutual_distance(&self, other : &Self) -> Result<f64, Error>{
+        self.pos().distance(other.pos())
+    }
+}
+
+impl Stateful<f64, f64> for ContPassiveOUAgent{
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>{
+        // noise를 stationary distribution N(0, D / tau)에서 뽑는다.
+        let (lambda, _mu, sigma2) = self.parameters()?;
+        let stddev : f64 = (sigma2 / (2f64 * lambda)).sqrt();
+
+        if self.noise.dim() != self.dim{
+            self.noise = Position::new(vec![0f64; self.dim]);
+        }
+        for x in self.noise.iter_mut(){
+            *x = stddev * get_gaussian(rng);
+        }
+        Ok(())
+    }
+
+    fn evolve_to_vec(&mut self, rng : &mut Pcg64, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // noise를 dt만큼 정확히 적분하고, 그 동안의 displacement를 vec에 더해준다.
+        // rng : Random number generator
+        // dt : Time step size. ExponentialStep처럼 매번 달라도 된다.
+        // vec : 값을 저장할 벡터
+        if self.dim != vec.dim() || self.dim != self.noise.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let (lambda, mu, sigma2) = self.parameters()?;
+        let tr : OUTransition = OUTransition::new(lambda, mu, sigma2, dt);
+
+        // (eta', y')의 noise를 Cholesky 분해로 생성
+        let a : f64 = tr.var_noise.sqrt();
+        let b : f64 = if a > 0f64 { tr.cov / a } else { 0f64 };
+        let c : f64 = (tr.var_pos - b * b).max(0f64).sqrt();
+
+        for i in 0..self.dim{
+            let y : f64 = match &self.trap{
+                FieldType::Harmonic(center, _k) => self.pos[i] - center[i],
+                _ => 0f64,
+            };
+            let eta : f64 = self.noise[i];
+            let (r1, r2) : (f64, f64) = (get_gaussian(rng), get_gaussian(rng));
+
+            self.noise[i] = tr.decay_noise * eta + a * r1;
+            vec[i] += tr.gain * eta + (tr.decay_pos - 1f64) * y + b * r1 + c * r2;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    #[allow(unused_imports)]
+    use super::*;
+
+    fn compose(tr : &OUTransition) -> OUTransition{
+        // 같은 transition을 두 번 적용한 결과. A2 = A A, Q2 = A Q A^T + Q
+        let (a, g, b) = (tr.decay_noise, tr.gain, tr.decay_pos);
+        let (qe, qc, qy) = (tr.var_noise, tr.cov, tr.var_pos);
+        OUTransition{
+            decay_noise : a * a,
+            decay_pos : b * b,
+            gain : g * a + b * g,
+            var_noise : a * a * qe + qe,
+            cov : a * (g * qe + b * qc) + qc,
+            var_pos : g * g * qe + 2f64 * g * b * qc + b * b * qy + qy,
+        }
+    }
+
+    #[test]
+    fn test_transition_composition(){
+        // 정확한 적분이라면 dt 두 번과 2dt 한 번은 같은 transition이어야 한다
+        let cases : Vec<(f64, f64, f64)> = vec![(2.0, 0.0, 1.0), (2.0, 0.5, 1.0), (1.0, 1.0, 1.0), (0.5, 3.0, 2.0)];
+        for (lambda, mu, dt) in cases{
+            for scale in vec![1.0, 1e-3, 1e-6]{
+                let dt = dt * scale;
+                let tr1 = compose(&OUTransition::new(lambda, mu, 2.0, dt));
+                let tr2 = OUTransition::new(lambda, mu, 2.0, 2.0 * dt);
+                for (x, y) in vec![(tr1.decay_noise, tr2.decay_noise), (tr1.decay_pos, tr2.decay_pos),
+                                   (tr1.gain, tr2.gain), (tr1.var_noise, tr2.var_noise),
+                                   (tr1.cov, tr2.cov), (tr1.var_pos, tr2.var_pos)]{
+                    assert!((x - y).abs() <= 1e-6 * y.abs().max(1e-300), "{} {} {} : {} {}", lambda, mu, dt, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_msd() -> Result<(), Error>{
+        // stationary noise에서 시작한 free OU particle의 MSD = 2D (t - tau (1 - exp(-t/tau)))
+        let mut rng = rng_seed(12341234);
+        let n : usize = 20000;
+        let (coeff_diff, tau) : (f64, f64) = (1.0, 0.5);
+        let steps : Vec<f64> = vec![1e-3, 1e-2, 0.05, 0.1, 0.2, 0.5, 0.147];       // 일정하지 않은 dt
+        let time : f64 = steps.iter().sum();
+
+        let mut agent = ContPassiveOUAgent::new(MoveType::Brownian(coeff_diff), tau, FieldType::Free,
+                            Position::new(vec![0.0]));
+        let mut msd : f64 = 0f64;
+        let mut disp = Position::new(vec![0.0]);
+        for _i in 0..n{
+            agent.renew_state(&mut rng)?;
+            disp.clear();
+            for dt in &steps{
+                agent.evolve_to_vec(&mut rng, *dt, &mut disp)?;
+            }
+            msd += disp[0] * disp[0] / n as f64;
+        }
+        let answer : f64 = 2f64 * coeff_diff * (time - tau * (1f64 - (- time / tau).exp()));
+        assert!((msd - answer).abs() < 0.05 * answer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_trap_stationary() -> Result<(), Error>{
+        // Harmonic trap 안의 stationary variance = 1 / (k (1 + D k tau))
+        let mut rng = rng_seed(12341234);
+        let n : usize = 20000;
+        let (coeff_diff, tau, k) : (f64, f64, f64) = (1.0, 0.5, 2.0);
+
+        let mut agent = ContPassiveOUAgent::new(MoveType::Brownian(coeff_diff), tau,
+                            FieldType::Harmonic(Position::new(vec![1.0]), k), Position::new(vec![1.0]));
+        let mut var : f64 = 0f64;
+        let mut disp = Position::new(vec![0.0]);
+        for _i in 0..n{
+            agent.noise[0] = 0f64;
+            disp.clear();
+            agent.evolve_to_vec(&mut rng, 100.0, &mut disp)?;
+            var += disp[0] * disp[0] / n as f64;
+        }
+        let answer : f64 = 1f64 / (k * (1f64 + coeff_diff * k * tau));
+        assert!((var - answer).abs() < 0.05 * answer);
+
+        agent.trap = FieldType::Drift(Position::new(vec![1.0]));
+        assert_eq!(agent.evolve_to_vec(&mut rng, 0.1, &mut disp),
+                   Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let argument = ContPassiveOUAgentArguments::new(MoveType::Brownian(1f64), InitType::Uniform,
+                            0.5, FieldType::from_str("Harmonic(0:0,2.0)")?, 3);
+        let agents = ContPassiveOUAgent::convert_from(&argument)?;
+        assert_eq!(agents.len(), 3);
+        assert_eq!(agents[0].tau, 0.5);
+
+        let mut rng = rng_seed(12341234);
+        let system = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let agent = ContPassiveOUAgent::new_uniform(&system, &target, &mut rng, MoveType::Brownian(1f64),
+                            0.5, FieldType::Free)?;
+        assert_eq!(agent.noise.dim(), 2);
+        assert!(agent.noise.norm() > 0f64);
+        Ok(())
+    }
+}
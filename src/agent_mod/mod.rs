@@ -30,6 +30,22 @@ pub trait Passive<T, F>{         // Functions for Passive ptls
     fn random_move_to_vec(&self, rng: &mut Pcg64, dt: F, vec: &mut Position<T>) -> Result<(), Error>;
 }
 
+pub trait Stateful<T, F>{        // Functions for ptls carrying their own internal state (noise, velocity, phase, ...)
+    // Renew internal state for new ensemble
+    fn renew_state(&mut self, rng : &mut Pcg64) -> Result<(), Error>;
+
+    // Evolve internal state over dt, and add resulting movement to vector
+    fn evolve_to_vec(&mut self, rng : &mut Pcg64, dt : F, vec : &mut Position<T>) -> Result<(), Error>;
+}
+
+pub trait Drift<T, F>{           // Functions for ptls following overdamped Langevin equation dx = a(x) dt + sqrt(2D) dW
+    // Diffusion coefficient of additive noise
+    fn coeff_diff(&self) -> Result<F, Error>;
+
+    // add drift velocity a(x) at given position to vector
+    fn drift_to_vec(&self, pos : &Position<T>, vec : &mut Position<T>) -> Result<(), Error>;
+}
+
 pub trait Active<T>{          // Functions for Active ptls
     // Active motion
     fn active_move(&self) -> Position<T>;
@@ -59,7 +75,10 @@ pub mod cont_passive_indep;     // 연속 시스템에서 Passive하게 움직
 pub mod cont_passive_merge;     // 연속 시스템에서 Passive하게 움직이는 서로 합쳐질 수 있는 searcher
 pub mod cont_passive_exp;       // 연속 시스템에서 Passive하게 움직이며, exponential interaction을 주고 받는 Searcher
 pub mod cont_passive_lj;        // 연속 시스템에서 Passive하게 움직이며, Lennard-Jones interaction을 주고 받는 Searcher
-
-
-
-
+pub mod cont_passive_field;     // 연속 시스템에서 external field를 받으며 Passive하게 움직이는 searcher
+pub mod cont_passive_hetero;    // 연속 시스템에서 anisotropic, 혹은 위치에 따라 다른 diffusion으로 Passive하게 움직이는 searcher
+pub mod cont_passive_ou;        // 연속 시스템에서 Ornstein-Uhlenbeck noise를 받으며 Passive하게 움직이는 searcher
+pub mod cont_passive_fbm;       // 연속 시스템에서 fractional Brownian motion으로 Passive하게 움직이는 searcher
+pub mod cont_passive_ctrw;      // 연속 시스템에서 continuous-time random walk로 Passive하게 움직이는 searcher
+pub mod cont_passive_langevin;  // 연속 시스템에서 관성을 갖고 underdamped Langevin dynamics로 Passive하게 움직이는 searcher
+pub mod cont_active_intermittent;   // 연속 시스템에서 diffusive phase와 ballistic phase를 번갈아 움직이는 searcher
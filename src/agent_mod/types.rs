@@ -203,6 +203,12 @@ impl Default for MoveType{
 pub enum InitType<T>{
     SpecificPosition(Position<T>),      // Initiate searcher at specific position
     Uniform,               // Initiate searchers with uniform distribution
+    FromFile(String),      // Initiate searchers at positions written in XYZ/CSV file
+    Shell(T),              // Initiate searchers uniformly on a shell at distance r0 from target
+    Gaussian(Position<T>, T),   // Initiate searchers in gaussian cloud around a point with given stddev
+    HalfSpace(usize, T),   // Initiate searchers uniformly in half space x_axis > x0
+    Annulus(T, T),         // Initiate searchers uniformly in annulus r1 < |x - target| < r2
+    Boltzmann(FieldType, T),    // Initiate searchers with Boltzmann distribution of external field at given temperature
 }
 
 
@@ -214,6 +220,18 @@ impl<T : Display> Display for InitType<T>{
                 write!(f, "Initialize all agents at {}", pos),
             InitType::Uniform =>
                 write!(f, "Initialize agents uniformly"),
+            InitType::FromFile(path) =>
+                write!(f, "Initialize agents from file {}", path),
+            InitType::Shell(r0) =>
+                write!(f, "Initialize agents on shell at distance {} from target", r0),
+            InitType::Gaussian(pos, stddev) =>
+                write!(f, "Initialize agents with gaussian around {} with stddev {}", pos, stddev),
+            InitType::HalfSpace(axis, x0) =>
+                write!(f, "Initialize agents uniformly in half space with axis {} above {}", axis, x0),
+            InitType::Annulus(r1, r2) =>
+                write!(f, "Initialize agents uniformly in annulus between {} and {} from target", r1, r2),
+            InitType::Boltzmann(field, temperature) =>
+                write!(f, "Initialize agents with Boltzmann distribution of {} at temperature {}", field, temperature),
         }
     }
 }
@@ -226,15 +244,49 @@ impl<T : FromStr + Default + Clone> FromStr for InitType<T>{
         if split.len() == 1{
             match split[0]{
                 "Uniform" => Ok(InitType::<T>::Uniform),
+                string if string.starts_with("FromFile(") && string.ends_with(')') => {
+                    Ok(InitType::<T>::FromFile(string[9..string.len() - 1].to_string()))
+                },
+                string if is_init_file(string) => Ok(InitType::<T>::FromFile(string.to_string())),
+                string if string.contains('(') && !string.starts_with('(') => {
+                    // Shell(2.0), Gaussian(0:0,0.5), HalfSpace(0,1.0), Annulus(2.0,5.0), Boltzmann(Harmonic(0:0,1.0),1.0)
+                    let (name, args) = split_args(string)?;
+                    match (name, args.len()){
+                        ("Shell", 1) => Ok(InitType::<T>::Shell(parse_arg(args[0])?)),
+                        ("Gaussian", 2) => Ok(InitType::<T>::Gaussian(parse_arg(args[0])?, parse_arg(args[1])?)),
+                        ("HalfSpace", 2) => Ok(InitType::<T>::HalfSpace(parse_arg(args[0])?, parse_arg(args[1])?)),
+                        ("Annulus", 2) => Ok(InitType::<T>::Annulus(parse_arg(args[0])?, parse_arg(args[1])?)),
+                        ("Boltzmann", 2) => Ok(InitType::<T>::Boltzmann(parse_arg(args[0])?, parse_arg(args[1])?)),
+                        _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+                    }
+                },
                 _ => {
                     split[0].parse::<Position<T>>().map(|pos| InitType::<T>::SpecificPosition(pos))
                 },
             }
         }
         else{
-            match split[1]{
-                "all" => Ok(InitType::<T>::SpecificPosition(split[4].parse::<Position<T>>().expect("Failed to parse"))),
-                "agents" => Ok(InitType::<T>::Uniform),
+            let n = split.len();
+            if n < 3{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+            }
+            match (split[1], split[2]){
+                ("all", _) => Ok(InitType::<T>::SpecificPosition(split[4].parse::<Position<T>>().expect("Failed to parse"))),
+                ("agents", "uniformly") if n == 3 => Ok(InitType::<T>::Uniform),
+                ("agents", "uniformly") if n == 11 && split[4] == "half" => {
+                    Ok(InitType::<T>::HalfSpace(parse_arg(split[8])?, parse_arg(split[10])?))
+                },
+                ("agents", "uniformly") if n == 11 && split[4] == "annulus" => {
+                    Ok(InitType::<T>::Annulus(parse_arg(split[6])?, parse_arg(split[8])?))
+                },
+                ("agents", "from") if n > 4 => Ok(InitType::<T>::FromFile(split[4..].join(" "))),
+                ("agents", "on") if n == 9 => Ok(InitType::<T>::Shell(parse_arg(split[6])?)),
+                ("agents", "with") if n == 9 && split[3] == "gaussian" => {
+                    Ok(InitType::<T>::Gaussian(parse_arg(split[5])?, parse_arg(split[8])?))
+                },
+                ("agents", "with") if n > 9 && split[3] == "Boltzmann" => {
+                    Ok(InitType::<T>::Boltzmann(parse_arg(&split[6..n - 3].join(" "))?, parse_arg(split[n - 1])?))
+                },
                 _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
             }
         }
@@ -247,6 +299,944 @@ impl<T> Default for InitType<T>{
     }
 }
 
+// Rejection sampling에서 후보를 뽑는 최대 횟수
+const MAX_REJECTION : usize = 1000000;
+
+impl InitType<f64>{
+    pub fn init_to_vec(&self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                       rng : &mut Pcg64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // Initialization type에 맞게 agent의 초기 위치를 vec에 기입
+        // sys      : system configuration
+        // target   : target configuration
+        // rng      : random number generator
+        // vec      : 초기 위치를 적을 mutable reference. system과 같은 dimension이어야 한다.
+
+        match self{
+            InitType::Uniform => {
+                loop{
+                    sys.random_pos_to_vec(rng, vec)?;       // System 내부의 random position을 받는다
+                    if !target.check_find(vec)?{            // 그 random position이 target과 이미 만났는가 확인
+                        break;
+                    }
+                }
+            },
+            InitType::SpecificPosition(pos) => {
+                if !sys.check_inclusion(pos)?{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+                }
+                vec.coordinate.clone_from(&pos.coordinate);
+            },
+            InitType::FromFile(_) => {
+                // 파일의 위치들은 convert_from에서 각 agent의 SpecificPosition으로 변환되어야 한다.
+                return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+            },
+            _ => {
+                // 나머지 분포들은 후보를 뽑은 뒤, system 안쪽이고 target 밖인 경우만 받아들인다.
+                self.check_parameters(vec.dim(), target)?;
+                for _i in 0..MAX_REJECTION{
+                    if self.candidate_to_vec(sys, target, rng, vec)?
+                            && sys.check_inclusion(vec)? && !target.check_find(vec)?{
+                        return Ok(());
+                    }
+                }
+                // 분포의 대부분이 system 밖이나 target 안쪽에 있는 경우
+                return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+            },
+        }
+        Ok(())
+    }
+
+    fn check_parameters(&self, dim : usize, target : &dyn TargetCore<f64>) -> Result<(), Error>{
+        // 분포의 parameter들이 올바른지 확인
+        let valid : bool = match self{
+            InitType::Shell(r0) => *r0 > 0f64 && target.target_pos().dim() == dim,
+            InitType::Gaussian(center, stddev) => *stddev > 0f64 && center.dim() == dim,
+            InitType::HalfSpace(axis, _x0) => *axis < dim,
+            InitType::Annulus(r1, r2) => 0f64 <= *r1 && *r1 < *r2 && target.target_pos().dim() == dim,
+            InitType::Boltzmann(field, temperature) => *temperature > 0f64 && field.dim().map_or(true, |d| d == dim),
+            _ => true,
+        };
+        if !valid{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        Ok(())
+    }
+
+    fn candidate_to_vec(&self, sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                        rng : &mut Pcg64, vec : &mut Position<f64>) -> Result<bool, Error>{
+        // Rejection sampling을 위한 후보 위치를 vec에 기입.
+        // 후보가 분포의 조건을 만족하지 않으면 false를 반환
+
+        use crate::random_mod::{get_uniform, get_gamma, get_direction_to_vec, get_gaussian_to_vec_nonstandard};
+
+        match self{
+            InitType::Shell(r0) => {
+                get_direction_to_vec(rng, vec);
+                vec.mut_scalar_mul(*r0);
+                vec.mut_add(target.target_pos());
+            },
+            InitType::Gaussian(center, stddev) => {
+                vec.coordinate.clone_from(&center.coordinate);
+                get_gaussian_to_vec_nonstandard(rng, vec, 0f64, *stddev);
+            },
+            InitType::HalfSpace(axis, x0) => {
+                sys.random_pos_to_vec(rng, vec)?;
+                return Ok(vec[*axis] > *x0);
+            },
+            InitType::Annulus(r1, r2) => {
+                // 반지름의 d제곱이 uniform해야 두 구면 사이에서 uniform한 분포가 된다.
+                let d : f64 = vec.dim() as f64;
+                let u : f64 = get_uniform(rng);
+                let r : f64 = (r1.powf(d) + u * (r2.powf(d) - r1.powf(d))).powf(1f64 / d);
+                get_direction_to_vec(rng, vec);
+                vec.mut_scalar_mul(r);
+                vec.mut_add(target.target_pos());
+            },
+            InitType::Boltzmann(field, temperature) => {
+                match field{
+                    FieldType::Free => {
+                        sys.random_pos_to_vec(rng, vec)?;
+                    },
+                    FieldType::Harmonic(center, k) if *k > 0f64 => {
+                        // exp(- k |x - c|^2 / 2T) : stddev sqrt(T / k)의 gaussian
+                        vec.coordinate.clone_from(&center.coordinate);
+                        get_gaussian_to_vec_nonstandard(rng, vec, 0f64, (temperature / k).sqrt());
+                    },
+                    FieldType::Radial(center, strength) if *strength > 0f64 => {
+                        // r^(d-1) exp(- f r / T) : shape d, scale T / f인 gamma distribution
+                        let r : f64 = get_gamma(rng, vec.dim() as f64, temperature / strength)?;
+                        get_direction_to_vec(rng, vec);
+                        vec.mut_scalar_mul(r);
+                        vec.mut_add(center);
+                    },
+                    FieldType::Drift(force) => {
+                        // exp(F.x / T) : system 안의 uniform한 후보를 bounding box에서의 최댓값 기준으로 rejection
+                        let (lower, upper) = sys.bounding_box()?;
+                        let mut max_work : f64 = 0f64;
+                        for i in 0..force.dim(){
+                            max_work += (force[i] * lower[i]).max(force[i] * upper[i]);
+                        }
+                        sys.random_pos_to_vec(rng, vec)?;
+                        let work : f64 = force.inner_product(vec)?;
+                        return Ok(get_uniform(rng) < ((work - max_work) / temperature).exp());
+                    },
+                    _ => {
+                        // 아래로 유계가 아닌 potential 등은 아직 지원하지 않는다.
+                        return Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided));
+                    }
+                }
+            },
+            _ => {
+                return Err(Error::make_error_syntax(ErrorCode::InvalidType));
+            },
+        }
+        Ok(true)
+    }
+}
+
+fn split_args(string : &str) -> Result<(&str, Vec<&str>), Error>{
+    // "Name(a,b(c,d))" 꼴의 문자열을 이름과 가장 바깥 괄호 안의 인자들로 나눈다.
+    let open = string.find('(').ok_or(Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+    if !string.ends_with(')'){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+    }
+    let inner = &string[open + 1..string.len() - 1];
+
+    let mut args : Vec<&str> = Vec::new();
+    let (mut depth, mut begin) = (0usize, 0usize);
+    for (i, c) in inner.char_indices(){
+        match c{
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(&inner[begin..i]);
+                begin = i + 1;
+            },
+            _ => (),
+        }
+    }
+    args.push(&inner[begin..]);
+    Ok((&string[..open], args))
+}
+
+fn parse_arg<S : FromStr>(string : &str) -> Result<S, Error>{
+    string.parse::<S>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))
+}
+
+// =====================================================================================
+// ===  Implement FieldType ============================================================
+// =====================================================================================
+
+#[derive(Copy, Clone)]
+pub struct ForceFn(pub fn(&Position<f64>, &mut Position<f64>) -> Result<(), Error>);
+// User-supplied force. 주어진 위치에서의 force를 두번째 vector에 더해준다.
+
+impl fmt::Debug for ForceFn{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        write!(f, "ForceFn({:#x})", self.0 as usize)
+    }
+}
+
+impl PartialEq for ForceFn{
+    fn eq(&self, other : &Self) -> bool{
+        self.0 as usize == other.0 as usize
+    }
+}
+
+impl PartialOrd for ForceFn{
+    fn partial_cmp(&self, other : &Self) -> Option<std::cmp::Ordering>{
+        (self.0 as usize).partial_cmp(&(other.0 as usize))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Default)]
+pub enum FieldType{
+    #[default]
+    Free,                               // No external field
+    Drift(Position<f64>),               // Constant force F. V(x) = - F.x
+    Harmonic(Position<f64>, f64),       // Harmonic trap at center with stiffness k. V(x) = k |x - c|^2 / 2
+    Radial(Position<f64>, f64),         // Radial force toward center with strength f (away if f < 0). V(x) = f |x - c|
+    Custom(ForceFn),                    // User-supplied force. argument로 줄 수는 없다.
+}
+
+impl FieldType{
+    pub fn dim(&self) -> Option<usize>{
+        // field가 정의된 공간의 dimension. 모든 dimension에서 정의되면 None
+        match self{
+            FieldType::Free => None,
+            FieldType::Drift(force) => Some(force.dim()),
+            FieldType::Harmonic(center, _k) => Some(center.dim()),
+            FieldType::Radial(center, _f) => Some(center.dim()),
+            FieldType::Custom(_f) => None,
+        }
+    }
+
+    pub fn potential(&self, pos : &Position<f64>) -> Result<f64, Error>{
+        // 주어진 위치에서의 potential energy
+        match self{
+            FieldType::Free => Ok(0f64),
+            FieldType::Drift(force) => Ok(- force.inner_product(pos)?),
+            FieldType::Harmonic(center, k) => Ok(0.5f64 * k * center.distance(pos)?.powi(2)),
+            FieldType::Radial(center, strength) => Ok(strength * center.distance(pos)?),
+            FieldType::Custom(_f) => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    pub fn force_to_vec(&self, pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // 주어진 위치에서의 force F = - grad V 를 vec에 더해준다.
+        // pos : position of agent
+        // vec : force를 더할 vector. pos와 같은 dimension이어야 한다.
+        if pos.dim() != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        match self{
+            FieldType::Free => {},
+            FieldType::Drift(force) => {
+                if force.dim() != pos.dim(){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                vec.mut_add(force);
+            },
+            FieldType::Harmonic(center, k) => {
+                if center.dim() != pos.dim(){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..pos.dim(){
+                    vec[i] -= k * (pos[i] - center[i]);
+                }
+            },
+            FieldType::Radial(center, strength) => {
+                let r : f64 = center.distance(pos)?;
+                if r > 0f64{                                    // 중심에서는 방향이 정의되지 않으므로 0
+                    for i in 0..pos.dim(){
+                        vec[i] -= strength * (pos[i] - center[i]) / r;
+                    }
+                }
+            },
+            FieldType::Custom(f) => {
+                (f.0)(pos, vec)?;
+            },
+        }
+        Ok(())
+    }
+
+    pub fn force(&self, pos : &Position<f64>) -> Result<Position<f64>, Error>{
+        // 주어진 위치에서의 force
+        let mut force : Position<f64> = Position::new(vec![0f64; pos.dim()]);
+        self.force_to_vec(pos, &mut force)?;
+        Ok(force)
+    }
+}
+
+impl Display for FieldType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            FieldType::Free =>
+                write!(f, "No external field"),
+            FieldType::Drift(force) =>
+                write!(f, "Constant drift {}", force),
+            FieldType::Harmonic(center, k) =>
+                write!(f, "Harmonic trap at {} with stiffness {}", center, k),
+            FieldType::Radial(center, strength) =>
+                write!(f, "Radial force toward {} with strength {}", center, strength),
+            FieldType::Custom(_f) =>
+                write!(f, "Custom external field"),
+        }
+    }
+}
+
+impl FromStr for FieldType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.len() == 1{
+            if split[0] == "Free"{
+                return Ok(FieldType::Free);
+            }
+            let (name, args) = split_args(split[0])?;
+            match (name, args.len()){
+                ("Drift", 1) => Ok(FieldType::Drift(parse_arg(args[0])?)),
+                ("Harmonic", 2) => Ok(FieldType::Harmonic(parse_arg(args[0])?, parse_arg(args[1])?)),
+                ("Radial", 2) => Ok(FieldType::Radial(parse_arg(args[0])?, parse_arg(args[1])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+        else{
+            match (split[0], split.len()){
+                ("No", 3) => Ok(FieldType::Free),
+                ("Constant", 3) => Ok(FieldType::Drift(parse_arg(split[2])?)),
+                ("Harmonic", 7) => Ok(FieldType::Harmonic(parse_arg(split[3])?, parse_arg(split[6])?)),
+                ("Radial", 7) => Ok(FieldType::Radial(parse_arg(split[3])?, parse_arg(split[6])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+    }
+}
+
+// =====================================================================================
+// ===  Implement IntegratorType =======================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Default)]
+pub enum IntegratorType{                // Numerical scheme for overdamped Langevin equation
+    #[default]
+    EulerMaruyama,                      // Euler-Maruyama. weak order 1
+    Heun,                               // Stochastic Heun (predictor-corrector). weak order 2 for additive noise
+    LeimkuhlerMatthews,                 // Overdamped limit of BAOAB. 이웃한 step의 noise 평균을 사용하며, 정상상태 분포가 2차까지 정확
+    StrongOrder15,                      // Derivative-free Kloeden-Platen scheme. strong order 1.5 for additive noise
+}
+
+impl_fmt_for_type!(IntegratorType,
+    IntegratorType::EulerMaruyama => "Euler-Maruyama scheme",
+    IntegratorType::Heun => "Stochastic Heun scheme",
+    IntegratorType::LeimkuhlerMatthews => "Leimkuhler-Matthews scheme",
+    IntegratorType::StrongOrder15 => "Strong order 1.5 scheme");
+
+impl FromStr for IntegratorType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim(){
+            "Euler-Maruyama scheme" | "EulerMaruyama" | "EM" => Ok(IntegratorType::EulerMaruyama),
+            "Stochastic Heun scheme" | "Heun" => Ok(IntegratorType::Heun),
+            "Leimkuhler-Matthews scheme" | "LeimkuhlerMatthews" | "LM" | "BAOAB" => Ok(IntegratorType::LeimkuhlerMatthews),
+            "Strong order 1.5 scheme" | "StrongOrder15" | "SO15" | "KP15" => Ok(IntegratorType::StrongOrder15),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+// =====================================================================================
+// ===  Implement DiffusionType ========================================================
+// =====================================================================================
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub enum DiffusionType{
+    Isotropic(f64),                                     // Constant scalar diffusion coefficient
+    Diagonal(Position<f64>),                            // Constant anisotropic diffusion along each axis
+    Tensor(Position<f64>),                              // Constant symmetric diffusion tensor. row-major로 펼친 d x d 행렬
+    Layered(usize, f64, f64, f64, f64),                 // D(x) changes from D1 to D2 across x_axis = x0 with width w
+    Region(Position<f64>, f64, f64, f64, f64),          // D(x) is D_in inside radius R around center, D_out outside, with width w
+}
+
+fn smooth_step(u : f64) -> (f64, f64){
+    // 0에서 1로 부드럽게 변하는 함수 (1 + tanh(u)) / 2 와 그 미분
+    let t : f64 = u.tanh();
+    (0.5 * (1f64 + t), 0.5 * (1f64 - t * t))
+}
+
+fn cholesky(mat : &Position<f64>, dim : usize) -> Result<Vec<f64>, Error>{
+    // 대칭 양의 정부호 행렬 D = L L^T 의 lower triangular L을 row-major로 반환
+    if mat.dim() != dim * dim{
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+    let mut l : Vec<f64> = vec![0f64; dim * dim];
+    for i in 0..dim{
+        for j in 0..i + 1{
+            if (mat[i * dim + j] - mat[j * dim + i]).abs() > 1e-10{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+            }
+            let mut sum : f64 = mat[i * dim + j];
+            for k in 0..j{
+                sum -= l[i * dim + k] * l[j * dim + k];
+            }
+            if i == j{
+                if sum <= 0f64{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+                }
+                l[i * dim + i] = sum.sqrt();
+            }
+            else{
+                l[i * dim + j] = sum / l[j * dim + j];
+            }
+        }
+    }
+    Ok(l)
+}
+
+impl DiffusionType{
+    pub fn check_parameters(&self, dim : usize) -> Result<(), Error>{
+        // 주어진 dimension에서 diffusion이 잘 정의되는지 확인
+        let invalid = Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        match self{
+            DiffusionType::Isotropic(d) => {
+                if *d < 0f64 { return invalid; }
+            },
+            DiffusionType::Diagonal(d) => {
+                if d.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                if d.iter().any(|x| *x < 0f64) { return invalid; }
+            },
+            DiffusionType::Tensor(d) => {
+                cholesky(d, dim)?;
+            },
+            DiffusionType::Layered(axis, _x0, d1, d2, width) => {
+                if *axis >= dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                if *d1 < 0f64 || *d2 < 0f64 || *width <= 0f64 { return invalid; }
+            },
+            DiffusionType::Region(center, radius, d_in, d_out, width) => {
+                if center.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                if *radius < 0f64 || *d_in < 0f64 || *d_out < 0f64 || *width <= 0f64 { return invalid; }
+            },
+        }
+        Ok(())
+    }
+
+    pub fn noise_factor(&self, dim : usize) -> Result<Vec<f64>, Error>{
+        // parameter를 확인하고, noise_to_vec에 넘겨줄 factor를 계산한다.
+        // Tensor는 D = L L^T 인 Cholesky factor L (row-major), 나머지는 필요 없으므로 비어있다.
+        self.check_parameters(dim)?;
+        match self{
+            DiffusionType::Tensor(d) => cholesky(d, dim),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    pub fn is_constant(&self) -> bool{
+        // D가 위치에 의존하지 않는지. 이 경우 noise-induced drift가 없다.
+        matches!(self, DiffusionType::Isotropic(_) | DiffusionType::Diagonal(_) | DiffusionType::Tensor(_))
+    }
+
+    pub fn coefficient(&self, pos : &Position<f64>) -> Result<f64, Error>{
+        // 주어진 위치에서의 scalar diffusion coefficient. tensor인 경우는 정의하지 않는다.
+        match self{
+            DiffusionType::Isotropic(d) => Ok(*d),
+            DiffusionType::Layered(axis, x0, d1, d2, width) => {
+                if *axis >= pos.dim(){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                let (step, _) = smooth_step((pos[*axis] - x0) / width);
+                Ok(d1 + (d2 - d1) * step)
+            },
+            DiffusionType::Region(center, radius, d_in, d_out, width) => {
+                let (step, _) = smooth_step((center.distance(pos)? - radius) / width);
+                Ok(d_in + (d_out - d_in) * step)
+            },
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    pub fn mobility_to_vec(&self, pos : &Position<f64>, force : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // D(x) F 를 vec에 더해준다. (k_B T = 1)
+        let dim : usize = pos.dim();
+        if force.dim() != dim || vec.dim() != dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        match self{
+            DiffusionType::Diagonal(d) => {
+                if d.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..dim{
+                    vec[i] += d[i] * force[i];
+                }
+            },
+            DiffusionType::Tensor(d) => {
+                if d.dim() != dim * dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..dim{
+                    for j in 0..dim{
+                        vec[i] += d[i * dim + j] * force[j];
+                    }
+                }
+            },
+            _ => {
+                let d : f64 = self.coefficient(pos)?;
+                for i in 0..dim{
+                    vec[i] += d * force[i];
+                }
+            },
+        }
+        Ok(())
+    }
+
+    pub fn noise_to_vec(&self, factor : &[f64], pos : &Position<f64>, xi : &Position<f64>, dt : f64, vec : &mut Position<f64>) -> Result<(), Error>{
+        // standard gaussian xi로부터 공분산이 2 D(x) dt인 noise를 만들어 vec에 더해준다.
+        // factor : noise_factor로 미리 계산해둔 값
+        let dim : usize = pos.dim();
+        if xi.dim() != dim || vec.dim() != dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let length : f64 = (2f64 * dt).sqrt();
+        match self{
+            DiffusionType::Diagonal(d) => {
+                if d.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..dim{
+                    vec[i] += length * d[i].sqrt() * xi[i];
+                }
+            },
+            DiffusionType::Tensor(_) => {
+                if factor.len() != dim * dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..dim{
+                    for j in 0..i + 1{
+                        vec[i] += length * factor[i * dim + j] * xi[j];
+                    }
+                }
+            },
+            _ => {
+                let d : f64 = self.coefficient(pos)?;
+                for i in 0..dim{
+                    vec[i] += length * d.sqrt() * xi[i];
+                }
+            },
+        }
+        Ok(())
+    }
+
+    pub fn gradient_to_vec(&self, pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // Divergence of diffusion tensor, 즉 scalar D(x)의 경우 grad D 를 vec에 더해준다.
+        if vec.dim() != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        match self{
+            DiffusionType::Layered(axis, x0, d1, d2, width) => {
+                if *axis >= pos.dim(){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                let (_, dstep) = smooth_step((pos[*axis] - x0) / width);
+                vec[*axis] += (d2 - d1) * dstep / width;
+            },
+            DiffusionType::Region(center, radius, d_in, d_out, width) => {
+                let r : f64 = center.distance(pos)?;
+                if r > 0f64{
+                    let (_, dstep) = smooth_step((r - radius) / width);
+                    let grad : f64 = (d_out - d_in) * dstep / width;
+                    for i in 0..pos.dim(){
+                        vec[i] += grad * (pos[i] - center[i]) / r;
+                    }
+                }
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+impl Display for DiffusionType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            DiffusionType::Isotropic(d) =>
+                write!(f, "Isotropic diffusion with coefficient {}", d),
+            DiffusionType::Diagonal(d) =>
+                write!(f, "Anisotropic diffusion with diagonal tensor {}", d),
+            DiffusionType::Tensor(d) =>
+                write!(f, "Diffusion tensor {}", d),
+            DiffusionType::Layered(axis, x0, d1, d2, width) =>
+                write!(f, "Layered diffusion along axis {} at {} from {} to {} with width {}", axis, x0, d1, d2, width),
+            DiffusionType::Region(center, radius, d_in, d_out, width) =>
+                write!(f, "Diffusion {} inside radius {} around {} and {} outside with width {}", d_in, radius, center, d_out, width),
+        }
+    }
+}
+
+impl FromStr for DiffusionType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.len() == 1{
+            if let Ok(d) = split[0].parse::<f64>(){
+                return Ok(DiffusionType::Isotropic(d));
+            }
+            let (name, args) = split_args(split[0])?;
+            match (name, args.len()){
+                ("Isotropic", 1) => Ok(DiffusionType::Isotropic(parse_arg(args[0])?)),
+                ("Diagonal", 1) => Ok(DiffusionType::Diagonal(parse_arg(args[0])?)),
+                ("Tensor", 1) => Ok(DiffusionType::Tensor(parse_arg(args[0])?)),
+                ("Layered", 5) => Ok(DiffusionType::Layered(parse_arg(args[0])?, parse_arg(args[1])?,
+                                        parse_arg(args[2])?, parse_arg(args[3])?, parse_arg(args[4])?)),
+                ("Region", 5) => Ok(DiffusionType::Region(parse_arg(args[0])?, parse_arg(args[1])?,
+                                        parse_arg(args[2])?, parse_arg(args[3])?, parse_arg(args[4])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+        else{
+            match (split[0], split.len()){
+                ("Isotropic", 5) => Ok(DiffusionType::Isotropic(parse_arg(split[4])?)),
+                ("Anisotropic", 6) => Ok(DiffusionType::Diagonal(parse_arg(split[5])?)),
+                ("Diffusion", 3) => Ok(DiffusionType::Tensor(parse_arg(split[2])?)),
+                ("Layered", 14) => Ok(DiffusionType::Layered(parse_arg(split[4])?, parse_arg(split[6])?,
+                                        parse_arg(split[8])?, parse_arg(split[10])?, parse_arg(split[13])?)),
+                ("Diffusion", 13) => Ok(DiffusionType::Region(parse_arg(split[6])?, parse_arg(split[4])?,
+                                        parse_arg(split[1])?, parse_arg(split[8])?, parse_arg(split[12])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+    }
+}
+
+impl Default for DiffusionType{
+    fn default() -> Self{
+        DiffusionType::Isotropic(1f64)
+    }
+}
+
+
+// =====================================================================================
+// ===  Implement NoiseConvention ======================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Default)]
+pub enum NoiseConvention{              // Interpretation of multiplicative noise sqrt(2 D(x)) dW
+    #[default]
+    Ito,                                // alpha = 0
+    Stratonovich,                       // alpha = 1/2
+    Isothermal,                         // alpha = 1. Hanggi-Klimontovich. 정상상태가 exp(-V)가 된다
+}
+
+impl NoiseConvention{
+    pub fn alpha(&self) -> f64{
+        // Ito 형식으로 적었을 때 noise-induced drift alpha grad D 의 계수
+        match self{
+            NoiseConvention::Ito => 0f64,
+            NoiseConvention::Stratonovich => 0.5f64,
+            NoiseConvention::Isothermal => 1f64,
+        }
+    }
+}
+
+impl_fmt_for_type!(NoiseConvention,
+    NoiseConvention::Ito => "Ito convention",
+    NoiseConvention::Stratonovich => "Stratonovich convention",
+    NoiseConvention::Isothermal => "Isothermal convention");
+
+impl FromStr for NoiseConvention{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim(){
+            "Ito convention" | "Ito" => Ok(NoiseConvention::Ito),
+            "Stratonovich convention" | "Stratonovich" => Ok(NoiseConvention::Stratonovich),
+            "Isothermal convention" | "Isothermal" | "HK" => Ok(NoiseConvention::Isothermal),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+// =====================================================================================
+// ===  Implement WaitingType ==========================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum WaitingType{                   // Waiting time distribution of continuous-time random walk
+    Exponential(f64),                   // Mean waiting time tau. psi(t) = exp(-t / tau) / tau
+    PowerLaw(f64, f64),                 // (alpha, tau0). psi(t) = alpha / tau0 (1 + t / tau0)^{-1-alpha}
+    MittagLeffler(f64, f64),            // (alpha, tau0). Survival probability E_alpha(-(t / tau0)^alpha)
+}
+
+impl WaitingType{
+    pub fn check_parameters(&self) -> Result<(), Error>{
+        // 분포가 잘 정의되는지 확인
+        let valid : bool = match self{
+            WaitingType::Exponential(tau) => *tau > 0f64,
+            WaitingType::PowerLaw(alpha, tau0) => *alpha > 0f64 && *tau0 > 0f64,
+            WaitingType::MittagLeffler(alpha, tau0) => 0f64 < *alpha && *alpha <= 1f64 && *tau0 > 0f64,
+        };
+        if !valid{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        Ok(())
+    }
+
+    pub fn mean(&self) -> f64{
+        // 평균 waiting time. 0 < alpha <= 1인 heavy tail의 경우 무한대
+        match self{
+            WaitingType::Exponential(tau) => *tau,
+            WaitingType::PowerLaw(alpha, tau0) if *alpha > 1f64 => tau0 / (alpha - 1f64),
+            WaitingType::MittagLeffler(alpha, tau0) if *alpha == 1f64 => *tau0,
+            _ => f64::INFINITY,
+        }
+    }
+
+    pub fn sample(&self, rng : &mut Pcg64) -> f64{
+        // waiting time 하나를 뽑는다.
+        use crate::random_mod::get_uniform;
+
+        match self{
+            WaitingType::Exponential(tau) => - tau * get_uniform(rng).ln(),
+            WaitingType::PowerLaw(alpha, tau0) => tau0 * (get_uniform(rng).powf(- 1f64 / alpha) - 1f64),
+            WaitingType::MittagLeffler(alpha, tau0) => {
+                // Kozubowski & Rachev (1999)
+                // T = - tau0 ln U ( sin(alpha pi) / tan(alpha pi V) - cos(alpha pi) )^{1 / alpha}
+                let exp : f64 = - tau0 * get_uniform(rng).ln();
+                if *alpha == 1f64{
+                    return exp;
+                }
+                let (u, api) : (f64, f64) = (get_uniform(rng), alpha * PI);
+                let factor : f64 = api.sin() / (api * u).tan() - api.cos();
+                exp * factor.powf(1f64 / alpha)
+            },
+        }
+    }
+}
+
+impl Display for WaitingType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            WaitingType::Exponential(tau) =>
+                write!(f, "Exponential waiting time with mean {}", tau),
+            WaitingType::PowerLaw(alpha, tau0) =>
+                write!(f, "Power-law waiting time with exponent {} and scale {}", alpha, tau0),
+            WaitingType::MittagLeffler(alpha, tau0) =>
+                write!(f, "Mittag-Leffler waiting time with exponent {} and scale {}", alpha, tau0),
+        }
+    }
+}
+
+impl FromStr for WaitingType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.len() == 1{
+            let (name, args) = split_args(split[0])?;
+            match (name, args.len()){
+                ("Exp", 1) | ("Exponential", 1) => Ok(WaitingType::Exponential(parse_arg(args[0])?)),
+                ("PowerLaw", 2) => Ok(WaitingType::PowerLaw(parse_arg(args[0])?, parse_arg(args[1])?)),
+                ("ML", 2) | ("MittagLeffler", 2) => Ok(WaitingType::MittagLeffler(parse_arg(args[0])?, parse_arg(args[1])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+        else{
+            match (split[0], split.len()){
+                ("Exponential", 6) => Ok(WaitingType::Exponential(parse_arg(split[5])?)),
+                ("Power-law", 9) => Ok(WaitingType::PowerLaw(parse_arg(split[5])?, parse_arg(split[8])?)),
+                ("Mittag-Leffler", 9) => Ok(WaitingType::MittagLeffler(parse_arg(split[5])?, parse_arg(split[8])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+    }
+}
+
+impl Default for WaitingType{
+    fn default() -> Self{
+        WaitingType::Exponential(1f64)
+    }
+}
+
+// =====================================================================================
+// ===  Implement JumpType =============================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum JumpType{                      // Jump length distribution of continuous-time random walk
+    Gaussian(f64),                      // Each component follows N(0, sigma^2)
+    Fixed(f64),                         // Fixed length l with isotropic direction
+    PowerLaw(f64, f64),                 // (mu, l0). Length l0 U^{-1/mu} with isotropic direction. Levy flight for mu < 2
+}
+
+impl JumpType{
+    pub fn check_parameters(&self) -> Result<(), Error>{
+        // 분포가 잘 정의되는지 확인
+        let valid : bool = match self{
+            JumpType::Gaussian(sigma) => *sigma >= 0f64,
+            JumpType::Fixed(length) => *length >= 0f64,
+            JumpType::PowerLaw(mu, l0) => *mu > 0f64 && *l0 > 0f64,
+        };
+        if !valid{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        Ok(())
+    }
+
+    pub fn jump_to_vec(&self, rng : &mut Pcg64, vec : &mut Position<f64>){
+        // jump 하나의 displacement를 vec에 더해준다.
+        use crate::random_mod::{get_uniform, get_direction_to_vec, get_gaussian_to_vec_nonstandard};
+
+        let length : f64 = match self{
+            JumpType::Gaussian(sigma) => {
+                get_gaussian_to_vec_nonstandard(rng, vec, 0f64, *sigma);
+                return;
+            },
+            JumpType::Fixed(length) => *length,
+            JumpType::PowerLaw(mu, l0) => l0 * get_uniform(rng).powf(- 1f64 / mu),
+        };
+
+        let mut direction : Position<f64> = Position::new(vec![0f64; vec.dim()]);
+        get_direction_to_vec(rng, &mut direction);
+        direction.mut_scalar_mul(length);
+        vec.mut_add(&direction);
+    }
+}
+
+impl Display for JumpType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            JumpType::Gaussian(sigma) =>
+                write!(f, "Gaussian jump with stddev {}", sigma),
+            JumpType::Fixed(length) =>
+                write!(f, "Fixed jump with length {}", length),
+            JumpType::PowerLaw(mu, l0) =>
+                write!(f, "Power-law jump with exponent {} and scale {}", mu, l0),
+        }
+    }
+}
+
+impl FromStr for JumpType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.len() == 1{
+            let (name, args) = split_args(split[0])?;
+            match (name, args.len()){
+                ("Gaussian", 1) => Ok(JumpType::Gaussian(parse_arg(args[0])?)),
+                ("Fixed", 1) => Ok(JumpType::Fixed(parse_arg(args[0])?)),
+                ("PowerLaw", 2) | ("Levy", 2) => Ok(JumpType::PowerLaw(parse_arg(args[0])?, parse_arg(args[1])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+        else{
+            match (split[0], split.len()){
+                ("Gaussian", 5) => Ok(JumpType::Gaussian(parse_arg(split[4])?)),
+                ("Fixed", 5) => Ok(JumpType::Fixed(parse_arg(split[4])?)),
+                ("Power-law", 8) => Ok(JumpType::PowerLaw(parse_arg(split[4])?, parse_arg(split[7])?)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+    }
+}
+
+impl Default for JumpType{
+    fn default() -> Self{
+        JumpType::Gaussian(1f64)
+    }
+}
+
+fn is_init_file(string : &str) -> bool{
+    // 초기 위치를 담은 파일로 취급할 확장자
+    string.ends_with(".xyz") || string.ends_with(".csv")
+}
+
+pub fn read_init_positions(path : &str, num_agent : usize) -> Result<Vec<Position<f64>>, Error>{
+    // 파일로부터 agent들의 초기 위치를 읽어온다.
+    // XYZ : (입자 수), (comment), (species x y z ...) 가 반복되는 형식. 마지막 frame을 사용한다.
+    //       comment에 dim=2 와 같이 차원이 적혀 있으면 그 차원만큼만 좌표를 읽고, 아니면 3차원으로 취급.
+    // CSV : 한 줄에 한 agent의 좌표. 쉼표나 공백으로 구분. 숫자가 아닌 header나 #으로 시작하는 줄은 무시.
+    // path         : path of file
+    // num_agent    : number of agents. 파일에 이보다 적은 위치가 있으면 error
+
+    let file = File::open(path).map_err(Error::make_error_io)?;
+    let mut lines : Vec<String> = Vec::new();
+    for line in BufReader::new(file).lines(){
+        lines.push(line.map_err(Error::make_error_io)?);
+    }
+
+    let mut positions : Vec<Position<f64>> = Vec::new();
+    if path.ends_with(".xyz"){
+        let mut idx : usize = 0;
+        while idx < lines.len(){
+            if lines[idx].trim().is_empty(){
+                idx += 1;
+                continue;
+            }
+            let n : usize = lines[idx].trim().parse().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidFormat))?;
+            if idx + 2 + n > lines.len(){
+                return Err(Error::make_error_syntax(ErrorCode::UnexpectedEnd));
+            }
+
+            let mut dim : usize = 3;
+            for token in lines[idx + 1].split_whitespace(){
+                if let Some(d) = token.strip_prefix("dim="){
+                    dim = d.parse().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidFormat))?;
+                }
+            }
+
+            positions.clear();
+            for line in &lines[idx + 2..idx + 2 + n]{
+                let tokens : Vec<&str> = line.split_whitespace().collect();
+                if tokens.len() < dim + 1{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+                }
+                let mut pos : Position<f64> = Position::new(Vec::with_capacity(dim));
+                for x in &tokens[1..=dim]{
+                    pos.push(x.parse().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidFormat))?);
+                }
+                positions.push(pos);
+            }
+            idx += 2 + n;
+        }
+    }
+    else{
+        for line in &lines{
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+            let values : Result<Vec<f64>, _> = line.split(|c : char| c == ',' || c.is_whitespace())
+                                                   .filter(|x| !x.is_empty())
+                                                   .map(|x| x.parse::<f64>()).collect();
+            match values{
+                Ok(v) => positions.push(Position::new(v)),
+                Err(_) if positions.is_empty() => continue,         // header
+                Err(_) => return Err(Error::make_error_syntax(ErrorCode::InvalidFormat)),
+            }
+        }
+    }
+
+    if positions.len() < num_agent{
+        return Err(Error::make_error_syntax(ErrorCode::InvalidFile));
+    }
+    positions.truncate(num_agent);
+    let dim = positions.first().map(|p| p.dim()).unwrap_or(0);
+    if positions.iter().any(|p| p.dim() != dim){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+    Ok(positions)
+}
+
 
 
 
@@ -440,6 +1430,8 @@ mod tests{
             "Initialize all agents at 0,0");
         assert_eq!(format!("{}", InitType::<f64>::Uniform).as_str(),
             "Initialize agents uniformly");
+        assert_eq!(format!("{}", InitType::<f64>::FromFile(String::from("init.csv"))).as_str(),
+            "Initialize agents from file init.csv");
     }
 
     #[test]
@@ -451,6 +1443,258 @@ mod tests{
         let test2 = "Initialize agents uniformly";
         let result2 = Ok(InitType::<f64>::Uniform);
         assert_eq!(InitType::<f64>::from_str(test2), result2);
+
+        let test3 = "Initialize agents from file tests/init.xyz";
+        let result3 = Ok(InitType::<f64>::FromFile(String::from("tests/init.xyz")));
+        assert_eq!(InitType::<f64>::from_str(test3), result3);
+        assert_eq!(InitType::<f64>::from_str("tests/init.xyz"), result3);
+        assert_eq!(InitType::<f64>::from_str("FromFile(tests/init.xyz)"), result3);
+    }
+
+    #[test]
+    fn test_fmt_init_distribution(){
+        assert_eq!(format!("{}", InitType::<f64>::Shell(2.0)).as_str(),
+            "Initialize agents on shell at distance 2 from target");
+        assert_eq!(format!("{}", InitType::<f64>::Gaussian(Position::new(vec![1.0, 0.0]), 0.5)).as_str(),
+            "Initialize agents with gaussian around 1,0 with stddev 0.5");
+        assert_eq!(format!("{}", InitType::<f64>::HalfSpace(0, 1.5)).as_str(),
+            "Initialize agents uniformly in half space with axis 0 above 1.5");
+        assert_eq!(format!("{}", InitType::<f64>::Annulus(2.0, 5.0)).as_str(),
+            "Initialize agents uniformly in annulus between 2 and 5 from target");
+        assert_eq!(format!("{}", InitType::<f64>::Boltzmann(FieldType::Harmonic(Position::new(vec![0.0, 0.0]), 1.0), 0.5)).as_str(),
+            "Initialize agents with Boltzmann distribution of Harmonic trap at 0,0 with stiffness 1 at temperature 0.5");
+    }
+
+    #[test]
+    fn test_fromstr_init_distribution(){
+        let list = vec![InitType::<f64>::Shell(2.0),
+                        InitType::<f64>::Gaussian(Position::new(vec![1.0, 0.0]), 0.5),
+                        InitType::<f64>::HalfSpace(0, 1.5),
+                        InitType::<f64>::Annulus(2.0, 5.0),
+                        InitType::<f64>::Boltzmann(FieldType::Harmonic(Position::new(vec![0.0, 0.0]), 1.0), 0.5),
+                        InitType::<f64>::Boltzmann(FieldType::Free, 1.0)];
+        for itype in list{
+            assert_eq!(InitType::<f64>::from_str(&format!("{}", itype)), Ok(itype.clone()));
+        }
+
+        assert_eq!(InitType::<f64>::from_str("Shell(2.0)"), Ok(InitType::Shell(2.0)));
+        assert_eq!(InitType::<f64>::from_str("Gaussian(1:0,0.5)"),
+            Ok(InitType::Gaussian(Position::new(vec![1.0, 0.0]), 0.5)));
+        assert_eq!(InitType::<f64>::from_str("HalfSpace(0,1.5)"), Ok(InitType::HalfSpace(0, 1.5)));
+        assert_eq!(InitType::<f64>::from_str("Annulus(2.0,5.0)"), Ok(InitType::Annulus(2.0, 5.0)));
+        assert_eq!(InitType::<f64>::from_str("Boltzmann(Radial(0:0,1.0),0.5)"),
+            Ok(InitType::Boltzmann(FieldType::Radial(Position::new(vec![0.0, 0.0]), 1.0), 0.5)));
+        assert_eq!(InitType::<f64>::from_str("(1,0)"), Ok(InitType::SpecificPosition(Position::new(vec![1.0, 0.0]))));
+        assert_eq!(InitType::<f64>::from_str("Shell(2.0,1.0)"), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+    }
+
+    #[test]
+    fn test_fmt_fromstr_field_type(){
+        let list = vec![(FieldType::Free, "No external field"),
+                        (FieldType::Drift(Position::new(vec![1.0, 0.0])), "Constant drift 1,0"),
+                        (FieldType::Harmonic(Position::new(vec![0.0, 0.0]), 2.0), "Harmonic trap at 0,0 with stiffness 2"),
+                        (FieldType::Radial(Position::new(vec![0.0, 0.0]), -1.0), "Radial force toward 0,0 with strength -1")];
+        for (field, string) in list{
+            assert_eq!(format!("{}", field).as_str(), string);
+            assert_eq!(FieldType::from_str(string), Ok(field));
+        }
+
+        assert_eq!(FieldType::from_str("Free"), Ok(FieldType::Free));
+        assert_eq!(FieldType::from_str("Drift(1:0)"), Ok(FieldType::Drift(Position::new(vec![1.0, 0.0]))));
+        assert_eq!(FieldType::from_str("Harmonic(0:0,2.0)"), Ok(FieldType::Harmonic(Position::new(vec![0.0, 0.0]), 2.0)));
+        assert_eq!(FieldType::from_str("Radial(0:0,-1)"), Ok(FieldType::Radial(Position::new(vec![0.0, 0.0]), -1.0)));
+    }
+
+    #[test]
+    fn test_force_field() -> Result<(), Error>{
+        let pos = Position::new(vec![3.0, 4.0]);
+
+        assert_eq!(FieldType::Free.force(&pos), Ok(Position::new(vec![0.0, 0.0])));
+        assert_eq!(FieldType::Drift(Position::new(vec![1.0, -1.0])).force(&pos), Ok(Position::new(vec![1.0, -1.0])));
+        assert_eq!(FieldType::Harmonic(Position::new(vec![1.0, 0.0]), 2.0).force(&pos), Ok(Position::new(vec![-4.0, -8.0])));
+        assert_eq!(FieldType::Radial(Position::new(vec![0.0, 0.0]), 1.0).force(&pos), Ok(Position::new(vec![-0.6, -0.8])));
+        assert_eq!(FieldType::Radial(Position::new(vec![3.0, 4.0]), 1.0).force(&pos), Ok(Position::new(vec![0.0, 0.0])));
+        assert_eq!(FieldType::Drift(Position::new(vec![1.0])).force(&pos),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+
+        // force는 vec에 더해진다
+        let mut vec = Position::new(vec![1.0, 1.0]);
+        FieldType::Drift(Position::new(vec![1.0, -1.0])).force_to_vec(&pos, &mut vec)?;
+        assert_eq!(vec, Position::new(vec![2.0, 0.0]));
+
+        fn shear(pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+            vec[0] += pos[1];
+            Ok(())
+        }
+        let field = FieldType::Custom(ForceFn(shear));
+        assert_eq!(field.force(&pos), Ok(Position::new(vec![4.0, 0.0])));
+        assert_eq!(field, FieldType::Custom(ForceFn(shear)));
+        assert_eq!(format!("{}", field), "Custom external field");
+        assert_eq!(field.potential(&pos), Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fmt_fromstr_diffusion_type(){
+        let list = vec![(DiffusionType::Isotropic(2.0), "Isotropic diffusion with coefficient 2"),
+                        (DiffusionType::Diagonal(Position::new(vec![1.0, 10.0])), "Anisotropic diffusion with diagonal tensor 1,10"),
+                        (DiffusionType::Tensor(Position::new(vec![1.0, 0.5, 0.5, 2.0])), "Diffusion tensor 1,0.5,0.5,2"),
+                        (DiffusionType::Layered(0, 1.0, 1.0, 0.1, 0.5), "Layered diffusion along axis 0 at 1 from 1 to 0.1 with width 0.5"),
+                        (DiffusionType::Region(Position::new(vec![0.0, 0.0]), 2.0, 0.1, 1.0, 0.2),
+                            "Diffusion 0.1 inside radius 2 around 0,0 and 1 outside with width 0.2")];
+        for (diffusion, string) in list{
+            assert_eq!(format!("{}", diffusion).as_str(), string);
+            assert_eq!(DiffusionType::from_str(string), Ok(diffusion));
+        }
+
+        assert_eq!(DiffusionType::from_str("1.5"), Ok(DiffusionType::Isotropic(1.5)));
+        assert_eq!(DiffusionType::from_str("Diagonal(1:1:10)"), Ok(DiffusionType::Diagonal(Position::new(vec![1.0, 1.0, 10.0]))));
+        assert_eq!(DiffusionType::from_str("Tensor(1:0.5:0.5:2)"), Ok(DiffusionType::Tensor(Position::new(vec![1.0, 0.5, 0.5, 2.0]))));
+        assert_eq!(DiffusionType::from_str("Layered(0,1.0,1.0,0.1,0.5)"), Ok(DiffusionType::Layered(0, 1.0, 1.0, 0.1, 0.5)));
+        assert_eq!(DiffusionType::from_str("Region(0:0,2.0,0.1,1.0,0.2)"),
+                   Ok(DiffusionType::Region(Position::new(vec![0.0, 0.0]), 2.0, 0.1, 1.0, 0.2)));
+        assert_eq!(DiffusionType::from_str("Layered(0,1.0)"), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+    }
+
+    #[test]
+    fn test_diffusion_type() -> Result<(), Error>{
+        // Parameter check
+        assert_eq!(DiffusionType::Tensor(Position::new(vec![1.0, 0.5, 0.5, 2.0])).check_parameters(2), Ok(()));
+        assert_eq!(DiffusionType::Tensor(Position::new(vec![1.0, 2.0, 2.0, 1.0])).check_parameters(2),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(DiffusionType::Tensor(Position::new(vec![1.0, 0.5, 0.0, 2.0])).check_parameters(2),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(DiffusionType::Diagonal(Position::new(vec![1.0, 0.5])).check_parameters(3),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        assert_eq!(DiffusionType::Layered(2, 0.0, 1.0, 2.0, 0.1).check_parameters(2),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+
+        // 미분은 수치 미분과 같아야 한다
+        let h : f64 = 1e-6;
+        let pos = Position::new(vec![0.3, 1.7]);
+        for diffusion in vec![DiffusionType::Layered(1, 1.5, 1.0, 0.1, 0.5),
+                              DiffusionType::Region(Position::new(vec![0.0, 1.0]), 0.5, 0.1, 1.0, 0.2)]{
+            let mut grad = Position::new(vec![0.0, 0.0]);
+            diffusion.gradient_to_vec(&pos, &mut grad)?;
+            for i in 0..2{
+                let mut x1 = pos.clone();
+                let mut x2 = pos.clone();
+                x1[i] += h;
+                x2[i] -= h;
+                let numeric = (diffusion.coefficient(&x1)? - diffusion.coefficient(&x2)?) / (2f64 * h);
+                assert!((grad[i] - numeric).abs() < 1e-6);
+            }
+            assert!(!diffusion.is_constant());
+        }
+
+        // Tensor의 noise는 L xi, mobility는 D F
+        let tensor = DiffusionType::Tensor(Position::new(vec![4.0, 2.0, 2.0, 5.0]));
+        let mut vec = Position::new(vec![0.0, 0.0]);
+        let factor : Vec<f64> = tensor.noise_factor(2)?;
+        assert_eq!(factor, vec![2.0, 0.0, 1.0, 2.0]);
+        tensor.noise_to_vec(&factor, &pos, &Position::new(vec![1.0, 1.0]), 0.5, &mut vec)?;
+        assert_eq!(vec, Position::new(vec![2.0, 3.0]));
+        assert_eq!(tensor.noise_to_vec(&[], &pos, &Position::new(vec![1.0, 1.0]), 0.5, &mut vec),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        assert_eq!(DiffusionType::Tensor(Position::new(vec![1.0, 2.0, 2.0, 1.0])).noise_factor(2),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        let mut vec = Position::new(vec![0.0, 0.0]);
+        tensor.mobility_to_vec(&pos, &Position::new(vec![1.0, -1.0]), &mut vec)?;
+        assert_eq!(vec, Position::new(vec![2.0, -3.0]));
+        assert_eq!(tensor.coefficient(&pos), Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+        Ok(())
+    }
+
+    impl_fmt_test!(test_fmt_noise_convention,
+        NoiseConvention::Ito => "Ito convention",
+        NoiseConvention::Stratonovich => "Stratonovich convention",
+        NoiseConvention::Isothermal => "Isothermal convention");
+
+    impl_fromstr_test!(test_fromstr_noise_convention,
+        NoiseConvention,
+        NoiseConvention::Ito => "Ito convention",
+        NoiseConvention::Stratonovich => "Stratonovich convention",
+        NoiseConvention::Isothermal => "Isothermal convention",
+        NoiseConvention::Isothermal => "HK");
+
+    impl_fmt_test!(test_fmt_integrator_type,
+        IntegratorType::EulerMaruyama => "Euler-Maruyama scheme",
+        IntegratorType::Heun => "Stochastic Heun scheme",
+        IntegratorType::LeimkuhlerMatthews => "Leimkuhler-Matthews scheme",
+        IntegratorType::StrongOrder15 => "Strong order 1.5 scheme");
+
+    impl_fromstr_test!(test_fromstr_integrator_type,
+        IntegratorType,
+        IntegratorType::EulerMaruyama => "Euler-Maruyama scheme",
+        IntegratorType::Heun => "Stochastic Heun scheme",
+        IntegratorType::LeimkuhlerMatthews => "Leimkuhler-Matthews scheme",
+        IntegratorType::StrongOrder15 => "Strong order 1.5 scheme",
+        IntegratorType::EulerMaruyama => "EM",
+        IntegratorType::Heun => "Heun",
+        IntegratorType::LeimkuhlerMatthews => "BAOAB",
+        IntegratorType::StrongOrder15 => "SO15");
+
+    #[test]
+    fn test_init_to_vec() -> Result<(), Error>{
+        use crate::system_mod::cont_circ::ContCircSystem;
+        use crate::target_mod::cont_bulk::ContBulkTarget;
+
+        let mut rng = rng_seed(12341234);
+        let sys = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::new(vec![1.0, 0.0]), 1.0);
+        let mut pos = Position::new(vec![0.0; 2]);
+
+        for _i in 0..100{
+            InitType::Shell(3.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            assert!((target.distance(&pos)? - 3.0).abs() < 1e-10);
+
+            InitType::Annulus(2.0, 3.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            let r = target.distance(&pos)?;
+            assert!(2.0 <= r && r <= 3.0);
+
+            InitType::HalfSpace(1, 2.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            assert!(pos[1] > 2.0 && sys.check_inclusion(&pos)?);
+
+            InitType::Gaussian(Position::new(vec![-5.0, 0.0]), 1.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            assert!(sys.check_inclusion(&pos)? && !target.check_find(&pos)?);
+
+            InitType::Boltzmann(FieldType::Radial(Position::new(vec![-5.0, 0.0]), 2.0), 1.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            assert!(sys.check_inclusion(&pos)? && !target.check_find(&pos)?);
+        }
+
+        // exp(x) 분포. 벽 근처에서 10 - x 는 shape 3/2인 gamma 분포를 따르므로 평균은 약 8.5
+        let n : usize = 2000;
+        let mut mean : f64 = 0f64;
+        for _i in 0..n{
+            InitType::Boltzmann(FieldType::Drift(Position::new(vec![1.0, 0.0])), 1.0).init_to_vec(&sys, &target, &mut rng, &mut pos)?;
+            assert!(sys.check_inclusion(&pos)? && !target.check_find(&pos)?);
+            mean += pos[0] / n as f64;
+        }
+        assert!((mean - 8.54).abs() < 0.1, "{}", mean);
+        assert_eq!(InitType::Boltzmann(FieldType::Drift(Position::new(vec![1.0, 0.0, 0.0])), 1.0).init_to_vec(&sys, &target, &mut rng, &mut pos),
+            Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(InitType::HalfSpace(2, 0.0).init_to_vec(&sys, &target, &mut rng, &mut pos),
+            Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_init_positions() -> Result<(), Error>{
+        use std::env::temp_dir;
+
+        let path = temp_dir().join("moledyn_test_init.csv");
+        let path = path.to_str().unwrap();
+        fs::write(path, "x,y\n0.5,0.5\n-1.0,2.0\n3.0,0.0\n").map_err(Error::make_error_io)?;
+        assert_eq!(read_init_positions(path, 2)?,
+            vec![Position::new(vec![0.5, 0.5]), Position::new(vec![-1.0, 2.0])]);
+        assert_eq!(read_init_positions(path, 4), Err(Error::make_error_syntax(ErrorCode::InvalidFile)));
+
+        // 마지막 frame을 읽는다
+        let path = temp_dir().join("moledyn_test_init.xyz");
+        let path = path.to_str().unwrap();
+        fs::write(path, "1\nTime=0 dim=2\nA 0.0 0.0 0.0\n1\nTime=1 dim=2\nA 1.0 -1.0 0.0\n").map_err(Error::make_error_io)?;
+        assert_eq!(read_init_positions(path, 1)?, vec![Position::new(vec![1.0, -1.0])]);
+        Ok(())
     }
 
 
@@ -532,6 +1776,71 @@ mod tests{
         InteractType::from_str(test7).expect("Panic occurs");
     }
 
+    #[test]
+    fn test_fmt_fromstr_waiting_jump_type(){
+        let list = vec![(WaitingType::Exponential(2.0), "Exponential waiting time with mean 2"),
+                        (WaitingType::PowerLaw(0.5, 1.0), "Power-law waiting time with exponent 0.5 and scale 1"),
+                        (WaitingType::MittagLeffler(0.7, 0.1), "Mittag-Leffler waiting time with exponent 0.7 and scale 0.1")];
+        for (waiting, string) in list{
+            assert_eq!(format!("{}", waiting).as_str(), string);
+            assert_eq!(WaitingType::from_str(string), Ok(waiting));
+        }
+        assert_eq!(WaitingType::from_str("Exp(2)"), Ok(WaitingType::Exponential(2.0)));
+        assert_eq!(WaitingType::from_str("PowerLaw(0.5,1)"), Ok(WaitingType::PowerLaw(0.5, 1.0)));
+        assert_eq!(WaitingType::from_str("ML(0.7,0.1)"), Ok(WaitingType::MittagLeffler(0.7, 0.1)));
+
+        let list = vec![(JumpType::Gaussian(0.1), "Gaussian jump with stddev 0.1"),
+                        (JumpType::Fixed(1.0), "Fixed jump with length 1"),
+                        (JumpType::PowerLaw(1.5, 0.1), "Power-law jump with exponent 1.5 and scale 0.1")];
+        for (jump, string) in list{
+            assert_eq!(format!("{}", jump).as_str(), string);
+            assert_eq!(JumpType::from_str(string), Ok(jump));
+        }
+        assert_eq!(JumpType::from_str("Gaussian(0.1)"), Ok(JumpType::Gaussian(0.1)));
+        assert_eq!(JumpType::from_str("Levy(1.5,0.1)"), Ok(JumpType::PowerLaw(1.5, 0.1)));
+        assert_eq!(JumpType::from_str("Fixed(1,2)"), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+    }
+
+    #[test]
+    fn test_waiting_jump_type() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let n : usize = 100000;
+
+        // 평균과 Laplace transform E[exp(-T)] 확인
+        let laplace = |waiting : WaitingType, rng : &mut Pcg64| -> f64{
+            (0..n).map(|_| (- waiting.sample(rng)).exp()).sum::<f64>() / n as f64
+        };
+        let mean : f64 = (0..n).map(|_| WaitingType::Exponential(2.0).sample(&mut rng)).sum::<f64>() / n as f64;
+        assert!((mean - 2.0).abs() < 0.03);
+        let mean : f64 = (0..n).map(|_| WaitingType::PowerLaw(3.0, 1.0).sample(&mut rng)).sum::<f64>() / n as f64;
+        assert!((mean - WaitingType::PowerLaw(3.0, 1.0).mean()).abs() < 0.02);
+        assert!((laplace(WaitingType::MittagLeffler(1.0, 2.0), &mut rng) - 1.0 / 3.0).abs() < 5e-3);
+        for alpha in vec![0.3, 0.7]{
+            // E[exp(- s T)] = 1 / (1 + (s tau0)^alpha)
+            let answer : f64 = 1f64 / (1f64 + 2f64.powf(alpha));
+            assert!((laplace(WaitingType::MittagLeffler(alpha, 2.0), &mut rng) - answer).abs() < 5e-3);
+        }
+        assert_eq!(WaitingType::MittagLeffler(0.5, 1.0).mean(), std::f64::INFINITY);
+        assert_eq!(WaitingType::MittagLeffler(1.5, 1.0).check_parameters(),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+
+        // jump length
+        let mut vec = Position::new(vec![0.0, 0.0, 0.0]);
+        JumpType::Fixed(2.0).jump_to_vec(&mut rng, &mut vec);
+        assert!((vec.norm() - 2.0).abs() < 1e-10);
+        let mut msd : f64 = 0f64;
+        for _i in 0..n{
+            vec.clear();
+            JumpType::Gaussian(0.5).jump_to_vec(&mut rng, &mut vec);
+            msd += vec.norm().powi(2) / n as f64;
+        }
+        assert!((msd - 0.75).abs() < 0.01);
+        vec.clear();
+        JumpType::PowerLaw(1.5, 0.1).jump_to_vec(&mut rng, &mut vec);
+        assert!(vec.norm() >= 0.1);
+        Ok(())
+    }
+
     #[test]
     fn test_from_into(){
         let test = InteractType::Exponential(2, 1.0);
@@ -29,10 +29,24 @@ pub trait DataSet{
 #[macro_export]
 #[allow(unused_macros)]
 macro_rules! construct_dataset {
-    ( $name:ident, $( $struct_type:ty, $arg_name:ident, $arg_type:ty, [$($var:ident, $t:ty),*] );*
-        $(;{$sim_type:ty, $sim_arg_name:ident, $sim_arg_type:ty, [$($sim_var:ident, $sim_t:ty),*]})?) => {
+    (@field $struct_type:ty, $arg_name:ident, $var:ident) => {
+        $arg_name.$var
+    };
+    (@field $struct_type:ty, $arg_name:ident, $var:ident, ($getter:ident)) => {
+        $arg_name.$getter()
+    };
+    (@field $struct_type:ty, $arg_name:ident, $var:ident, [$method:ident]) => {
+        <$struct_type>::convert_from($arg_name).into_result().and_then(|x| x.$method()).unwrap_or(f64::NAN)
+    };
+    ( $name:ident, $( $struct_type:ty, $arg_name:ident, $arg_type:ty, [$($var:ident $(($getter:ident))? $([$method:ident])?, $t:ty),*] );*
+        $(;{$sim_type:ty, $sim_arg_name:ident, $sim_arg_type:ty, [$($sim_var:ident, $sim_t:ty),*]
+            $(, key [$($sim_key:ident, $sim_key_t:ty),*])?})?) => {
+        // sim_var는 ensemble 구분용 (idx_set 등)으로 hash 및 analysis에서 제외되고,
+        // sim_key는 simulation 변수이지만 dataset을 구분하는 데 사용된다 (reset rate 등)
+        // var(getter) 꼴은 argument에서 getter로 계산한 값을 var로 사용한다 (Position 등 Copy가 아닌 argument)
+        // var[method] 꼴은 argument로 만든 system의 method 값을 var로 사용한다 (volume 등). 만들지 못하면 NaN
 
-        define_structure_wo_eq!($name; $($($var, $t,)*)* $($($sim_var, $sim_t,)*)?);
+        define_structure_wo_eq!($name; $($($var, $t,)*)* $($($($sim_key, $sim_key_t,)*)? $($sim_var, $sim_t,)*)?);
 
         impl $name{
             // Since argument infos are different for different data form
@@ -41,10 +55,13 @@ macro_rules! construct_dataset {
             pub fn new($($arg_name : &$arg_type), * $(,$sim_arg_name : &$sim_arg_type)?) -> Self{
                 $name{
                     $(
-                        $($var : $arg_name.$var,
+                        $($var : construct_dataset!(@field $struct_type, $arg_name, $var $(, ($getter))? $(, [$method])?),
                             )*
                     )*
-                    $($(
+                    $($($(
+                        $sim_key : $sim_arg_name.$sim_key,
+                    )*)?
+                    $(
                         $sim_var : $sim_arg_name.$sim_var,
                     )*)?
                 }
@@ -77,7 +94,10 @@ macro_rules! construct_dataset {
                         string.push_str(format!("{}", format_args!("_{}_{}", stringify!($var), self.$var)).as_str());
                     )*
                 )*
-                $($(
+                $($($(
+                    string.push_str(format!("{}", format_args!("_{}_{}", stringify!($sim_key), self.$sim_key)).as_str());
+                )*)?
+                $(
                     string.push_str(format!("{}", format_args!("_{}_{}", stringify!($sim_var), self.$sim_var)).as_str());
                 )*)?
                 string.push_str(".dat");
@@ -92,18 +112,21 @@ macro_rules! construct_dataset {
                         string.push_str(format!("{}", format_args!("_{}_{}", stringify!($var), self.$var)).as_str());
                     )*
                 )*
+                $($($(
+                    string.push_str(format!("{}", format_args!("_{}_{}", stringify!($sim_key), self.$sim_key)).as_str());
+                )*)?)?
                 string.push_str(".dat");
                 return string;
             }
 
-            export_form!(export_form $($(, $var)*)*);
-            export_data!(export_data $($(, $var)*)*);
+            export_form!(export_form $($(, $var)*)* $($($(, $sim_key)*)?)?);
+            export_data!(export_data $($(, $var)*)* $($($(, $sim_key)*)?)?);
         }
 
         impl Copy for $name{
         }
 
-        derive_hash!($name $($(, $var)*)*);
+        derive_hash!($name $($(, $var)*)* $($($(, $sim_key)*)?)?);
 
         impl PartialEq for $name{
             fn eq(&self, other: &Self) -> bool {
@@ -1424,6 +1447,30 @@ mod tests{
         assert_eq!(res, test);
     }
 
+    #[test]
+    fn test_new_with_getter(){
+        use crate::system_mod::cont_rect::{ContRectSystem, ContRectSystemArguments};
+
+        // Position인 argument는 getter로 계산한 scalar, 혹은 system의 method 값을 사용
+        construct_dataset!(TestData, ContRectSystem, sys_arg, ContRectSystemArguments,
+                            [sys_max(max_half_length), f64, sys_volume[volume], f64, dim(dim), usize];
+                            ContBulkTarget, target_arg, ContBulkTargetArguments,
+                            [target_size, f64]);
+
+        let sys_arg = ContRectSystemArguments::new(BoundaryCond::Reflection, Position::new(vec![2f64, 0.5f64]),
+                            Position::new(vec![0f64, 0f64]), ReflectionType::Approximate);
+        let target_arg = ContBulkTargetArguments::new(Position::<f64>::new(vec![0f64,0f64]), 1f64);
+        let test = TestData::new(&sys_arg, &target_arg);
+
+        assert_eq!(test, TestData{
+            sys_max : 2f64,
+            sys_volume : 4f64,
+            dim : 2usize,
+            target_size : 1f64,
+        });
+        assert_eq!(test.export_file("RTS"), "RTS_sys_max_2_sys_volume_4_dim_2_target_size_1.dat");
+    }
+
     // #[test]
     // fn test_from_file(){
 
@@ -1500,6 +1547,28 @@ mod tests{
         assert_eq!(hashmap.get(&key6), None);
     }
 
+    #[test]
+    fn test_dataset_with_sim_key(){
+        construct_dataset!(TestData, ContCircSystem, sys_arg, ContCircSystemArguments,
+                            [sys_size, f64, dim, usize];
+                            {ResetSimulation, sim_arg, ResetSimulationArguments, [idx_set, usize], key [reset_rate, f64]});
+
+        let sys_arg = ContCircSystemArguments::new(10f64, 2);
+        let sim1 = ResetSimulationArguments::new(10, ResetProtocol::Poissonian, 0.5, 1, 1234, String::from("."));
+        let sim2 = ResetSimulationArguments::new(10, ResetProtocol::Poissonian, 0.5, 2, 1234, String::from("."));
+        let sim3 = ResetSimulationArguments::new(10, ResetProtocol::Poissonian, 1.0, 1, 1234, String::from("."));
+
+        let key1 = TestData::new(&sys_arg, &sim1);
+        let key2 = TestData::new(&sys_arg, &sim2);
+        let key3 = TestData::new(&sys_arg, &sim3);
+
+        assert_eq!(key1.export_file("Test"), "Test_sys_size_10_dim_2_reset_rate_0.5_idx_set_1.dat");
+        assert_eq!(key1.export_file_removed_idx("Test"), "Test_sys_size_10_dim_2_reset_rate_0.5.dat");
+        assert_eq!(key1, key2);             // idx_set은 dataset을 구분하지 않는다
+        assert_ne!(key1, key3);             // reset_rate는 dataset을 구분한다
+        assert_eq!(TestData::export_form(10), "sys_size  dim       reset_rate");
+    }
+
     #[test]
     fn test_mfpt_analysis(){
         use crate::random_mod::get_gaussian;
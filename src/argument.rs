@@ -39,6 +39,24 @@ pub trait Argument<T>{
     fn read_args_from_lines(reader : &mut Lines<BufReader<File>>) -> Result<T, Error>;
 }
 
+// convert_from의 결과를 Result로 통일해주는 Trait
+// 대부분의 convert_from은 structure를 그대로 반환하지만, 파일을 읽거나 parameter를 확인하는 경우는 Result를 반환한다.
+// setup macro에서는 둘 모두 into_result()? 로 처리한다.
+pub trait ConvertResult{
+    type Output;
+
+    fn into_result(self) -> Result<Self::Output, Error>;
+}
+
+impl<T> ConvertResult for Result<T, Error>{
+    type Output = T;
+
+    fn into_result(self) -> Result<T, Error>{
+        self
+    }
+}
+
+
 #[macro_export]
 #[allow(unused_macros)]
 macro_rules! define_num_args{
@@ -136,23 +154,38 @@ macro_rules! impl_fn_print_configuration{
 #[macro_export]
 #[allow(unused_macros)]
 macro_rules! impl_fn_read_args_from_lines{
+    (@line $reader:ident) => {
+        {
+            let list : String = match $reader.next(){
+                Some(x) => x.map_err(Error::make_error_io)?,
+                None => {return Err(Error::make_error_syntax(ErrorCode::InvalidFile));}
+            };
+            let split : Vec<String> = list.split(":")
+                                          .map(|s| s.to_string())
+                                          .collect();
+            if split.len() != 2{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+            }
+            let val_string : &str = &split[1].trim();
+            val_string.parse().expect("Failed to parse\n")
+        }
+    };
+    // Argument structure는 모든 field가 header에 있다.
+    (@all $name:ident, $($var:ident), *) => {
+        fn read_args_from_lines(reader : &mut Lines<BufReader<File>>) -> Result<$name, Error>{
+            Ok($name{
+                $($var : impl_fn_read_args_from_lines!(@line reader),
+                    )*
+            })
+        }
+    };
+    // header에 없는 field (예전 버전의 output 파일에는 없던 field)는 Default 값을 사용한다.
     ($name:ident, $($var:ident), *) => {
         fn read_args_from_lines(reader : &mut Lines<BufReader<File>>) -> Result<$name, Error>{
             Ok($name{
-                $($var : {
-                            let list : String = match reader.next(){
-                                Some(x) => x.map_err(Error::make_error_io)?,
-                                None => {return Err(Error::make_error_syntax(ErrorCode::InvalidFile));}
-                            };
-                            let split : Vec<String> = list.split(":")
-                                                          .map(|s| s.to_string())
-                                                          .collect();
-                            if split.len() != 2{
-                                return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
-                            }
-                            let val_string : &str = &split[1].trim();
-                            val_string.parse().expect("Failed to parse\n")},
+                $($var : impl_fn_read_args_from_lines!(@line reader),
                     )*
+                ..Default::default()
             })
         }
     }
@@ -160,6 +193,28 @@ macro_rules! impl_fn_read_args_from_lines{
 
 
 
+#[macro_export]
+#[allow(unused_macros)]
+macro_rules! impl_convert_result{
+    ($struct_name:ident) => {
+        impl ConvertResult for $struct_name{
+            type Output = $struct_name;
+
+            fn into_result(self) -> Result<$struct_name, Error>{
+                Ok(self)
+            }
+        }
+
+        impl ConvertResult for Vec<$struct_name>{
+            type Output = Vec<$struct_name>;
+
+            fn into_result(self) -> Result<Vec<$struct_name>, Error>{
+                Ok(self)
+            }
+        }
+    }
+}
+
 #[macro_export]
 #[allow(unused_macros)]
 macro_rules! impl_argument_trait{
@@ -181,9 +236,11 @@ macro_rules! impl_argument_trait{
 
             impl_fn_read_args_from_vec!($arg_name $(,$type_name, $type_default)*; $($var), *);
 
-            impl_fn_read_args_from_lines!($arg_name $(, $type_name)* $(,$var)*);
+            impl_fn_read_args_from_lines!(@all $arg_name $(, $type_name)* $(,$var)*);
         }
 
+        impl_convert_result!($struct_name);
+
         // impl Display for $struct_name{
         //     fn fmt(&self, f : &mut Formatter) -> fmt::Result{
         //         write!(f, "{}", self.print_configuration(10))
@@ -0,0 +1,167 @@
+// Module for Brownian bridge crossing correction
+//
+// 유한한 dt에서는 한 step 동안 target이나 흡수 경계를 지났다가 되돌아오는 경로를 놓치게 되어 FPT가 커진다.
+// 시작점과 끝점이 주어진 Brownian bridge가 그 사이에 경계를 지났을 확률을 구해,
+// 끝점이 경계 밖에 있더라도 이 확률로 target을 찾은 것으로 판정한다.
+// 거리 d0, d1 떨어진 평면을 지날 확률은 정확히
+//      P = exp(- d0 d1 / (D dt))
+// 이고, 구면은 접평면으로 근사한다. 이 근사의 오차는 sqrt(D dt) / (곡률 반경)의 order이다.
+// dt를 step마다 넘겨주므로 ConstStep, ExponentialStep 모두에 사용할 수 있다.
+
+use crate::prelude::*;
+use crate::random_mod::{get_uniform};
+
+
+// 평면에서 d0, d1 떨어진 두 점을 잇는 Brownian bridge가 평면을 지났을 확률
+pub fn crossing_probability(d0 : f64, d1 : f64, coeff_diff : f64, dt : f64) -> f64{
+    // d0, d1 : step 전후의 경계까지의 거리. 둘 중 하나라도 0 이하이면 이미 경계에 닿았다.
+    // coeff_diff : diffusion coefficient
+    // dt : step 크기
+    if d0 <= 0f64 || d1 <= 0f64{
+        return 1f64;
+    }
+    if coeff_diff <= 0f64 || dt <= 0f64{
+        return 0f64;
+    }
+    (- d0 * d1 / (coeff_diff * dt)).exp()
+}
+
+// 법선 normal, 원점으로부터 offset인 평면 (normal . x = offset)을 지났을 확률
+pub fn plane_crossing_probability(normal : &Position<f64>, offset : f64, prev : &Position<f64>, pos : &Position<f64>,
+                                  coeff_diff : f64, dt : f64) -> Result<f64, Error>{
+    // normal : 평면의 unit normal vector
+    // prev, pos : step 전후의 위치
+    if normal.dim() != prev.dim() || normal.dim() != pos.dim(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+
+    let h0 : f64 = normal.inner_product(prev)? - offset;
+    let h1 : f64 = normal.inner_product(pos)? - offset;
+    if h0 * h1 <= 0f64{                                         // 서로 다른 쪽에 있다.
+        return Ok(1f64);
+    }
+    Ok(crossing_probability(h0.abs(), h1.abs(), coeff_diff, dt))
+}
+
+// 중심 center, 반지름 radius인 구면을 지났을 확률. 구 안쪽과 바깥쪽 모두 사용할 수 있다.
+pub fn sphere_crossing_probability(center : &Position<f64>, radius : f64, prev : &Position<f64>, pos : &Position<f64>,
+                                   coeff_diff : f64, dt : f64) -> Result<f64, Error>{
+    // center, radius : 구의 중심과 반지름
+    // prev, pos : step 전후의 위치
+    let h0 : f64 = center.distance(prev)? - radius;
+    let h1 : f64 = center.distance(pos)? - radius;
+    if h0 * h1 <= 0f64{
+        return Ok(1f64);
+    }
+    Ok(crossing_probability(h0.abs(), h1.abs(), coeff_diff, dt))
+}
+
+// 주어진 확률로 경계를 지났는지 판정
+pub fn check_crossing(rng : &mut Pcg64, probability : f64) -> bool{
+    probability >= 1f64 || get_uniform(rng) < probability
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::random_mod::{get_gaussian_to_vec_nonstandard};
+
+    #[test]
+    fn test_crossing_probability(){
+        assert_eq!(crossing_probability(0.0, 1.0, 1.0, 1.0), 1f64);
+        assert_eq!(crossing_probability(1.0, -1.0, 1.0, 1.0), 1f64);
+        assert_eq!(crossing_probability(1.0, 1.0, 0.0, 1.0), 0f64);
+        assert!((crossing_probability(1.0, 2.0, 0.5, 4.0) - (-1f64).exp()).abs() < 1e-15);
+
+        let normal = Position::new(vec![1.0, 0.0]);
+        let prev = Position::new(vec![1.5, 3.0]);
+        let pos = Position::new(vec![2.0, -1.0]);
+        assert!((plane_crossing_probability(&normal, 1.0, &prev, &pos, 1.0, 0.5).unwrap() - (-1f64).exp()).abs() < 1e-15);
+        assert_eq!(plane_crossing_probability(&normal, 1.8, &prev, &pos, 1.0, 0.5), Ok(1f64));
+        assert_eq!(plane_crossing_probability(&normal, 1.0, &Position::new(vec![0.0]), &pos, 1.0, 0.5),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+
+        // 구 안쪽과 바깥쪽
+        let center = Position::new(vec![0.0, 0.0]);
+        let p1 = sphere_crossing_probability(&center, 2.0, &Position::new(vec![3.0, 0.0]), &Position::new(vec![0.0, 4.0]), 1.0, 2.0).unwrap();
+        let p2 = sphere_crossing_probability(&center, 2.0, &Position::new(vec![1.0, 0.0]), &Position::new(vec![0.0, 1.5]), 1.0, 0.25).unwrap();
+        assert!((p1 - (-1f64).exp()).abs() < 1e-15);
+        assert!((p2 - (-2f64).exp()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_bridge_statistics(){
+        // 1D에서 끝점이 같은 쪽에 있는 path 중 중간에 0을 지난 비율을 잘게 나눈 path로 직접 구한다.
+        let mut rng = rng_seed(12341234);
+        let (coeff_diff, dt, num_sub) : (f64, f64, usize) = (1.0, 0.1, 1000);
+        let stddev : f64 = (2f64 * coeff_diff * dt / num_sub as f64).sqrt();
+        let x0 : f64 = 0.2;
+
+        let (mut num_cross, mut sum_prob, mut num_total) : (usize, f64, usize) = (0, 0f64, 0);
+        let mut x = Position::new(vec![0f64]);
+        for _i in 0..4000{
+            x[0] = x0;
+            let mut crossed : bool = false;
+            for _j in 0..num_sub{
+                get_gaussian_to_vec_nonstandard(&mut rng, &mut x, 0f64, stddev);
+                crossed = crossed || x[0] <= 0f64;
+            }
+            if x[0] > 0f64{
+                num_total += 1;
+                num_cross += crossed as usize;
+                sum_prob += crossing_probability(x0, x[0], coeff_diff, dt);
+            }
+        }
+        let measured : f64 = num_cross as f64 / num_total as f64;
+        let expected : f64 = sum_prob / num_total as f64;
+        // 이산화된 path는 경계 근처의 짧은 crossing을 놓치므로 조금 작게 나온다.
+        assert!((measured - expected).abs() < 0.1 * expected, "{} {}", measured, expected);
+    }
+
+    fn mfpt_annulus<T>(timeiter : T, n : usize, bridge : bool) -> Result<f64, Error>
+        where T : TimeIterator + Iterator<Item = f64>{
+        // 반지름 3인 원의 중심에 반지름 1인 target, D = 1
+        let mut rng = rng_seed(12341234);
+        let sys = ContCircSystem::new(3.0, 2);
+        let target = ContBulkTarget::new(Position::new(vec![0.0, 0.0]), 1.0);
+        let mut agent = ContPassiveIndepAgent::new_uniform(&sys, &target, &mut rng, MoveType::Brownian(1.0))?;
+        let mut prev = Position::new(vec![0.0, 0.0]);
+        let mut single_move = Position::new(vec![0.0, 0.0]);
+
+        let mut mfpt : f64 = 0f64;
+        for _i in 0..n{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+            for (time, dt) in timeiter.into_diff().skip(1){
+                prev.clone_from(&agent.pos);
+                single_move.clear();
+                agent.random_move_to_vec(&mut rng, dt, &mut single_move)?;
+                sys.check_bc(&mut agent.pos, &mut single_move)?;
+                let found : bool = match bridge{
+                    true => target.check_find_bridge(&mut rng, &prev, &agent.pos, 1.0, dt)?,
+                    false => target.check_find(&agent.pos)?,
+                };
+                if found{
+                    mfpt += time / n as f64;
+                    break;
+                }
+            }
+        }
+        Ok(mfpt)
+    }
+
+    #[test]
+    fn test_mfpt_bridge() -> Result<(), Error>{
+        // T = [R^4 ln(R/a) / (R^2 - a^2) - (3R^2 - a^2) / 4] / 2D
+        let (r, a) : (f64, f64) = (3f64, 1f64);
+        let answer : f64 = (r.powi(4) * (r / a).ln() / (r * r - a * a) - (3f64 * r * r - a * a) / 4f64) / 2f64;
+
+        let biased : f64 = mfpt_annulus(ConstStep::new(1e-2)?, 3000, false)?;
+        let corrected : f64 = mfpt_annulus(ConstStep::new(1e-2)?, 3000, true)?;
+        let exponential : f64 = mfpt_annulus(ExponentialStep::new(1e-3, 1e-2, 10)?, 3000, true)?;
+        assert!(biased > 1.08 * answer, "{} {}", biased, answer);
+        assert!((corrected - answer).abs() < 0.05 * answer, "{} {}", corrected, answer);
+        assert!((exponential - answer).abs() < 0.05 * answer, "{} {}", exponential, answer);
+        Ok(())
+    }
+}
@@ -46,6 +46,9 @@ impl Error{
             | ErrorCode::InvalidFormat
             | ErrorCode::InvalidFile
             | ErrorCode::TooLargeTimeStep
+            | ErrorCode::NonConstantTimeStep
+            | ErrorCode::TooLongHorizon
+            | ErrorCode::AbsorbingBoundary
             | ErrorCode::FeatureNotProvided
             | ErrorCode::UnexpectedEnd => Category::Syntax,
         }
@@ -113,6 +116,15 @@ pub enum ErrorCode{
     // Time step is too large
     TooLargeTimeStep,
 
+    // Time step is not constant
+    NonConstantTimeStep,
+
+    // Number of time steps exceeds the horizon generated in advance
+    TooLongHorizon,
+
+    // Agent hits an absorbing boundary where only reflection is handled
+    AbsorbingBoundary,
+
     // Functionality is not developed yet
     FeatureNotProvided,
 
@@ -133,6 +145,9 @@ impl Display for ErrorCode{
             ErrorCode::InvalidFormat => f.write_str("Invalid Format"),
             ErrorCode::InvalidFile => f.write_str("Invalid File"),
             ErrorCode::TooLargeTimeStep => f.write_str("Time step is too large"),
+            ErrorCode::NonConstantTimeStep => f.write_str("Time step is not constant"),
+            ErrorCode::TooLongHorizon => f.write_str("Number of time steps exceeds the maximal horizon"),
+            ErrorCode::AbsorbingBoundary => f.write_str("Agent hits an absorbing boundary. Use check_bc_absorb"),
             ErrorCode::FeatureNotProvided => f.write_str("Functionality is not provided yet"),
             ErrorCode::UnexpectedEnd => f.write_str("Function ends unexpectedly"),
         }
@@ -174,6 +189,12 @@ mod tests{
             "Functionality is not provided yet");
         assert_eq!(format!("{}", Error::make_error_syntax(ErrorCode::TooLargeTimeStep)).as_str(),
             "Time step is too large");
+        assert_eq!(format!("{}", Error::make_error_syntax(ErrorCode::NonConstantTimeStep)).as_str(),
+            "Time step is not constant");
+        assert_eq!(format!("{}", Error::make_error_syntax(ErrorCode::TooLongHorizon)).as_str(),
+            "Number of time steps exceeds the maximal horizon");
+        assert_eq!(format!("{}", Error::make_error_syntax(ErrorCode::AbsorbingBoundary)).as_str(),
+            "Agent hits an absorbing boundary. Use check_bc_absorb");
     }
 
     #[test]
@@ -189,6 +210,9 @@ mod tests{
         assert_eq!(Error::make_error_syntax(ErrorCode::InvalidConfiguration).classify(), Category::Syntax);
         assert_eq!(Error::make_error_syntax(ErrorCode::InvalidNumberOfArguments).classify(), Category::Syntax);
         assert_eq!(Error::make_error_syntax(ErrorCode::TooLargeTimeStep).classify(), Category::Syntax);
+        assert_eq!(Error::make_error_syntax(ErrorCode::NonConstantTimeStep).classify(), Category::Syntax);
+        assert_eq!(Error::make_error_syntax(ErrorCode::TooLongHorizon).classify(), Category::Syntax);
+        assert_eq!(Error::make_error_syntax(ErrorCode::AbsorbingBoundary).classify(), Category::Syntax);
         assert_eq!(Error::make_error_syntax(ErrorCode::FeatureNotProvided).classify(), Category::Syntax);
     }
 }
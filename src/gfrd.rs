@@ -0,0 +1,515 @@
+// Module for Green's-function reaction dynamics (GFRD)
+//
+// 다른 것들과 충분히 떨어진 agent 주위에 protective domain (반지름 R의 구)을 만들고,
+// 자유 확산의 Green's function으로 domain을 벗어나는 시간과 위치를 정확히 뽑아 한번에 이동시킨다.
+// 중심에서 출발한 자유 확산이 domain 안에 남아있을 확률은 s = D t / R^2 에 대해
+//      1D : S(s) = 4/pi sum_k (-1)^k / (2k+1) exp(-(2k+1)^2 pi^2 s / 4)
+//      2D : S(s) = sum_n 2 / (j_n J1(j_n)) exp(-j_n^2 s),          j_n : J0의 n번째 zero
+//      3D : S(s) = 2 sum_n (-1)^{n+1} exp(-n^2 pi^2 s)
+// 이고, 벗어나는 위치는 구면 위에서 uniform하다.
+// Target, 벽, 다른 agent에 가까운 agent는 domain을 만들지 않고 보통의 Brownian step으로 움직인다.
+// Domain을 벗어나기 전에 agent를 꺼내야 할 때 (burst)는 그때까지 살아남았다는 조건 아래의 분포에서 위치를 뽑는다.
+// Resetting과 같이 time loop 안에서 agent마다 step_to_vec을 호출하고, check_bc와 check_find는 그대로 사용한다.
+//
+// van Zon, J. S., & ten Wolde, P. R. (2005). Green's-function reaction dynamics: a particle-based approach for
+// simulating biochemical networks in time and space. The Journal of chemical physics, 123(23), 234910.
+// Opplestrup, T., Bulatov, V. V., Gilmer, G. H., Kalos, M. H., & Sadigh, B. (2006).
+// First-passage Monte Carlo algorithm: diffusion without all the hops. Physical review letters, 97(23), 230602.
+
+use crate::prelude::*;
+use crate::random_mod::{get_uniform, get_gaussian_to_vec_nonstandard, get_direction_to_vec};
+
+const NUM_MODES : usize = 64;               // survival probability의 series에서 사용하는 항의 수
+const NUM_GRID : usize = 256;               // burst에서 radial 분포를 적분하는 grid의 수
+const S_MIN : f64 = 1e-3;                   // 이보다 짧은 exit time (D t / R^2)은 사실상 나오지 않는다
+const S_FREE : f64 = 0.02;                  // 이보다 짧은 시간의 burst는 경계를 무시한 gaussian으로 근사
+
+
+fn bessel_j0(x : f64) -> f64{
+    // Bessel function of the first kind J0. 오차 1e-8 정도의 rational approximation
+    let ax : f64 = x.abs();
+    if ax < 8f64{
+        let y : f64 = x * x;
+        let p : f64 = 57568490574.0 + y * (-13362590354.0 + y * (651619640.7
+                        + y * (-11214424.18 + y * (77392.33017 + y * (-184.9052456)))));
+        let q : f64 = 57568490411.0 + y * (1029532985.0 + y * (9494680.718
+                        + y * (59272.64853 + y * (267.8532712 + y))));
+        p / q
+    }
+    else{
+        let z : f64 = 8f64 / ax;
+        let y : f64 = z * z;
+        let xx : f64 = ax - 0.785398164;
+        let p : f64 = 1f64 + y * (-0.1098628627e-2 + y * (0.2734510407e-4
+                        + y * (-0.2073370639e-5 + y * 0.2093887211e-6)));
+        let q : f64 = -0.1562499995e-1 + y * (0.1430488765e-3
+                        + y * (-0.6911147651e-5 + y * (0.7621095161e-6 - y * 0.934935152e-7)));
+        (std::f64::consts::FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q)
+    }
+}
+
+fn bessel_j1(x : f64) -> f64{
+    // Bessel function of the first kind J1. 오차 1e-8 정도의 rational approximation
+    let ax : f64 = x.abs();
+    if ax < 8f64{
+        let y : f64 = x * x;
+        let p : f64 = x * (72362614232.0 + y * (-7895059235.0 + y * (242396853.1
+                        + y * (-2972611.439 + y * (15704.48260 + y * (-30.16036606))))));
+        let q : f64 = 144725228442.0 + y * (2300535178.0 + y * (18583304.74
+                        + y * (99447.43394 + y * (376.9991397 + y))));
+        p / q
+    }
+    else{
+        let z : f64 = 8f64 / ax;
+        let y : f64 = z * z;
+        let xx : f64 = ax - 2.356194491;
+        let p : f64 = 1f64 + y * (0.183105e-2 + y * (-0.3516396496e-4
+                        + y * (0.2457520174e-5 + y * (-0.240337019e-6))));
+        let q : f64 = 0.04687499995 + y * (-0.2002690873e-3
+                        + y * (0.8449199096e-5 + y * (-0.88228987e-6 + y * 0.105787412e-6)));
+        let ans : f64 = (std::f64::consts::FRAC_2_PI / ax).sqrt() * (xx.cos() * p - z * xx.sin() * q);
+        if x < 0f64 { -ans } else { ans }
+    }
+}
+
+fn bessel_j0_zero(n : usize) -> f64{
+    // J0의 n번째 zero (n >= 1). McMahon expansion에서 시작해 Newton method로 보정
+    let beta : f64 = (n as f64 - 0.25) * PI;
+    let mut x : f64 = beta + 1f64 / (8f64 * beta) - 124f64 / (3f64 * (8f64 * beta).powi(3));
+    for _i in 0..10{
+        let dx : f64 = bessel_j0(x) / bessel_j1(x);             // J0' = -J1
+        x += dx;
+        if dx.abs() < 1e-14 * x{
+            break;
+        }
+    }
+    x
+}
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Domain{                          // Protective domain of an agent
+    pub center : Position<f64>,             // agent의 위치이자 domain의 중심
+    pub radius : f64,                       // radius of domain
+    pub t_start : f64,                      // time when domain is constructed
+    pub t_exit : f64,                       // time when agent escapes from domain
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct GFRD{
+    pub dim : usize,                        // dimension of space
+    pub coeff_diff : f64,                   // diffusion coefficient of agents
+    pub ratio : f64,                        // domain의 최소 반지름과 한 step의 diffusion length sqrt(2 d D dt)의 비
+    modes : Vec<(f64, f64)>,                // S(s) = sum_n c_n exp(- mu_n^2 s) 의 (mu_n, c_n)
+    domains : Vec<Option<Domain>>,          // domain of each agent. Brownian step으로 움직이는 중이면 None
+    num_domain : usize,                     // number of domains constructed
+    num_step : usize,                       // number of Brownian steps
+}
+
+impl GFRD{
+    pub fn new(dim : usize, coeff_diff : f64, ratio : f64) -> Result<Self, Error>{
+        // dim : dimension of space. 1, 2, 3 차원만 지원한다.
+        // coeff_diff : diffusion coefficient
+        // ratio : domain을 만드는 최소 반지름 / sqrt(2 d D dt). 클수록 정확하지만 domain이 덜 만들어진다.
+        if coeff_diff <= 0f64 || ratio <= 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let modes : Vec<(f64, f64)> = match dim{
+            1 => (0..NUM_MODES).map(|k| {
+                    let m : f64 = (2 * k + 1) as f64;
+                    let sign : f64 = if k % 2 == 0 { 1f64 } else { -1f64 };
+                    (0.5 * m * PI, 4f64 * sign / (PI * m))
+                }).collect(),
+            2 => (1..=NUM_MODES).map(|n| {
+                    let j : f64 = bessel_j0_zero(n);
+                    (j, 2f64 / (j * bessel_j1(j)))
+                }).collect(),
+            3 => (1..=NUM_MODES).map(|n| {
+                    let sign : f64 = if n % 2 == 1 { 1f64 } else { -1f64 };
+                    (n as f64 * PI, 2f64 * sign)
+                }).collect(),
+            _ => {
+                return Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided));
+            }
+        };
+
+        Ok(GFRD{
+            dim,
+            coeff_diff,
+            ratio,
+            modes,
+            domains : Vec::new(),
+            num_domain : 0,
+            num_step : 0,
+        })
+    }
+
+    pub fn renew(&mut self, num_agent : usize){
+        // 새 ensemble을 시작할 때 호출. 모든 agent는 domain 없이 시작한다.
+        self.domains.clear();
+        self.domains.resize(num_agent, None);
+        self.num_domain = 0;
+        self.num_step = 0;
+    }
+
+    pub fn survival(&self, s : f64) -> f64{
+        // 중심에서 출발해 dimensionless time s = D t / R^2 동안 domain 안에 남아있을 확률
+        if s <= 0f64{
+            return 1f64;
+        }
+        let mut sum : f64 = 0f64;
+        for &(mu, c) in &self.modes{
+            let term : f64 = c * (- mu * mu * s).exp();
+            sum += term;
+            if term.abs() < 1e-17{
+                break;
+            }
+        }
+        sum.clamp(0f64, 1f64)
+    }
+
+    pub fn sample_exit_time(&self, rng : &mut Pcg64, radius : f64) -> f64{
+        // 반지름 radius의 domain 중심에서 출발한 agent가 domain을 벗어나는 시간
+        // S(s) = u 를 bisection으로 푼다.
+        let u : f64 = 1f64 - get_uniform(rng);                          // (0, 1]
+        let (mu, c) = self.modes[0];
+        let mut hi : f64 = ((c / u).ln() / (mu * mu)).max(0f64) + 1f64;
+        while self.survival(hi) > u{
+            hi *= 2f64;
+        }
+        let mut lo : f64 = S_MIN;
+        if self.survival(lo) <= u{
+            return lo * radius * radius / self.coeff_diff;
+        }
+        for _i in 0..60{
+            let mid : f64 = 0.5 * (lo + hi);
+            if self.survival(mid) > u{
+                lo = mid;
+            }
+            else{
+                hi = mid;
+            }
+        }
+        0.5 * (lo + hi) * radius * radius / self.coeff_diff
+    }
+
+    fn radial_density(&self, rho : f64, s : f64) -> f64{
+        // 살아남은 agent의 중심으로부터의 거리 rho = r / R 의 (정규화되지 않은) 분포
+        let mut sum : f64 = 0f64;
+        for &(mu, c) in &self.modes{
+            let decay : f64 = (- mu * mu * s).exp();
+            if decay < 1e-17{
+                break;
+            }
+            sum += match self.dim{
+                1 => (mu * rho).cos() * decay,
+                2 => {
+                    let j1 : f64 = 2f64 / (mu * c);                     // c = 2 / (j J1(j))
+                    rho * bessel_j0(mu * rho) / (j1 * j1) * decay
+                },
+                _ => rho * mu * (mu * rho).sin() * decay,
+            };
+        }
+        sum.max(0f64)
+    }
+
+    fn sample_survived_to_vec(&self, rng : &mut Pcg64, s : f64, vec : &mut Position<f64>){
+        // 중심에서 출발해 s 동안 살아남았다는 조건 아래에서 중심으로부터의 displacement (R 단위)를 vec에 기록
+        if s < S_FREE{
+            // 경계까지 가기에 너무 짧은 시간. 자유 확산에서 경계 밖의 결과만 버린다.
+            loop{
+                vec.clear();
+                get_gaussian_to_vec_nonstandard(rng, vec, 0f64, (2f64 * s).sqrt());
+                if vec.norm() < 1f64{
+                    return;
+                }
+            }
+        }
+
+        let mut cdf : Vec<f64> = vec![0f64; NUM_GRID + 1];
+        let h : f64 = 1f64 / NUM_GRID as f64;
+        let mut prev : f64 = self.radial_density(0f64, s);
+        for i in 1..=NUM_GRID{
+            let next : f64 = self.radial_density(i as f64 * h, s);
+            cdf[i] = cdf[i - 1] + 0.5 * h * (prev + next);
+            prev = next;
+        }
+        let u : f64 = get_uniform(rng) * cdf[NUM_GRID];
+        let i : usize = cdf.iter().position(|&c| c >= u).unwrap_or(NUM_GRID).max(1);
+        let width : f64 = cdf[i] - cdf[i - 1];
+        let frac : f64 = if width > 0f64 { (u - cdf[i - 1]) / width } else { 0.5 };
+        let rho : f64 = (i as f64 - 1f64 + frac) * h;
+
+        get_direction_to_vec(rng, vec);
+        vec.mut_scalar_mul(rho);
+    }
+
+    pub fn min_radius(&self, dt : f64) -> f64{
+        // domain을 만들 수 있는 최소 반지름
+        self.ratio * (2f64 * self.dim as f64 * self.coeff_diff * dt).sqrt()
+    }
+
+    pub fn domain(&self, idx : usize) -> Option<&Domain>{
+        // idx번째 agent의 domain
+        self.domains.get(idx).and_then(|d| d.as_ref())
+    }
+
+    pub fn next_event_time(&self) -> Option<f64>{
+        // 모든 agent가 domain 안에 있으면 가장 먼저 domain을 벗어나는 시간. 그 전까지는 아무 일도 일어나지 않는다.
+        let mut t_next : f64 = f64::INFINITY;
+        for d in &self.domains{
+            match d{
+                Some(domain) => t_next = t_next.min(domain.t_exit),
+                None => return None,
+            }
+        }
+        if self.domains.is_empty() { None } else { Some(t_next) }
+    }
+
+    pub fn num_domain(&self) -> usize{
+        self.num_domain
+    }
+
+    pub fn num_step(&self) -> usize{
+        self.num_step
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_to_vec(&mut self, rng : &mut Pcg64, idx : usize, time : f64, dt : f64, free_radius : f64,
+                       pos : &Position<f64>, vec : &mut Position<f64>) -> Result<(), Error>{
+        // idx번째 agent를 time에서 time + dt까지 움직이고, displacement를 vec에 더해준다.
+        // rng : random number generator
+        // idx : agent의 index
+        // time, dt : 현재 시간과 step 크기
+        // free_radius : target, 벽, 다른 agent까지의 거리. domain을 만들 때만 사용된다.
+        // pos : agent의 현재 위치. domain 안에 있는 동안은 domain의 중심이다.
+        // vec : 값을 저장할 벡터
+        if idx >= self.domains.len(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        if pos.dim() != self.dim || vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        if self.domains[idx].is_none() && free_radius >= self.min_radius(dt){
+            let t_exit : f64 = time + self.sample_exit_time(rng, free_radius);
+            self.domains[idx] = Some(Domain{
+                center : pos.clone(),
+                radius : free_radius,
+                t_start : time,
+                t_exit,
+            });
+            self.num_domain += 1;
+        }
+
+        let t_end : f64 = time + dt;
+        let rest : f64 = match self.domains[idx].take(){
+            Some(domain) if domain.t_exit > t_end => {
+                // 아직 domain 안에 있다.
+                self.domains[idx] = Some(domain);
+                return Ok(());
+            },
+            Some(domain) => {
+                // domain 표면의 uniform한 위치로 벗어난 뒤 남은 시간 동안 Brownian motion
+                let mut dir : Position<f64> = Position::new(vec![0f64; self.dim]);
+                get_direction_to_vec(rng, &mut dir);
+                for i in 0..self.dim{
+                    vec[i] += domain.center[i] + domain.radius * dir[i] - pos[i];
+                }
+                t_end - domain.t_exit
+            },
+            None => {
+                self.num_step += 1;
+                dt
+            },
+        };
+
+        if rest > 0f64{
+            get_gaussian_to_vec_nonstandard(rng, vec, 0f64, (2f64 * self.coeff_diff * rest).sqrt());
+        }
+        Ok(())
+    }
+
+    pub fn burst(&mut self, rng : &mut Pcg64, idx : usize, time : f64, pos : &mut Position<f64>) -> Result<bool, Error>{
+        // idx번째 agent의 domain을 없애고 time에서의 위치를 pos에 기록한다. domain이 없었으면 false
+        // 다른 agent가 domain에 다가오거나, 중간에 위치를 기록해야 할 때 사용한다.
+        if idx >= self.domains.len(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        if pos.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        match self.domains[idx].take(){
+            Some(domain) => {
+                let s : f64 = self.coeff_diff * (time - domain.t_start).max(0f64) / domain.radius.powi(2);
+                self.sample_survived_to_vec(rng, s, pos);
+                pos.mut_scalar_mul(domain.radius);
+                pos.mut_add(&domain.center);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_bessel(){
+        assert!((bessel_j0(1.0) - 0.7651976866).abs() < 1e-8);
+        assert!((bessel_j1(10.0) - 0.0434727462).abs() < 1e-8);
+        assert!((bessel_j0_zero(1) - 2.404825557695773).abs() < 1e-8);
+        assert!((bessel_j0_zero(2) - 5.520078110286311).abs() < 1e-8);
+        assert!((bessel_j0_zero(20) - 62.04846919022717).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_survival() -> Result<(), Error>{
+        // 평균 exit time은 R^2 / (2 d D), 즉 int_0^inf S(s) ds = 1 / 2d
+        for dim in 1..4{
+            let gfrd = GFRD::new(dim, 1.0, 1.0)?;
+            assert!((gfrd.survival(S_MIN) - 1f64).abs() < 1e-10);
+            let h : f64 = 1e-4;
+            let integral : f64 = (0..50000).map(|i| gfrd.survival((i as f64 + 0.5) * h) * h).sum();
+            assert!((integral - 0.5 / dim as f64).abs() < 1e-4, "{} {}", dim, integral);
+        }
+        assert_eq!(GFRD::new(4, 1.0, 1.0), Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_time() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let n : usize = 20000;
+        let (coeff_diff, radius) : (f64, f64) = (0.5, 2.0);
+        for dim in 1..4{
+            let gfrd = GFRD::new(dim, coeff_diff, 1.0)?;
+            let mean : f64 = (0..n).map(|_| gfrd.sample_exit_time(&mut rng, radius)).sum::<f64>() / n as f64;
+            let answer : f64 = radius * radius / (2f64 * dim as f64 * coeff_diff);
+            assert!((mean - answer).abs() < 0.03 * answer, "{} {} {}", dim, mean, answer);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_burst() -> Result<(), Error>{
+        // 직접 Brownian motion으로 구한, 살아남은 agent의 <r^2>와 비교
+        let mut rng = rng_seed(12341234);
+        let (n, ds) : (usize, f64) = (4000, 2e-4);
+        for dim in 1..4{
+            for &s in [0.01, 0.1].iter(){
+                let mut gfrd = GFRD::new(dim, 1.0, 1.0)?;
+                let mut pos = Position::new(vec![0f64; dim]);
+                let mut sample : f64 = 0f64;
+                for _i in 0..n{
+                    gfrd.renew(1);
+                    gfrd.domains[0] = Some(Domain{center : Position::new(vec![0f64; dim]), radius : 1.0,
+                                            t_start : 0.0, t_exit : 1.0});
+                    assert!(gfrd.burst(&mut rng, 0, s, &mut pos)?);
+                    assert!(pos.norm() < 1f64);
+                    sample += pos.norm().powi(2) / n as f64;
+                }
+                assert_eq!(gfrd.burst(&mut rng, 0, s, &mut pos)?, false);
+
+                let (mut direct, mut count) : (f64, usize) = (0f64, 0);
+                let steps : usize = (s / ds).round() as usize;
+                'walk : for _i in 0..4 * n{
+                    let mut x = Position::new(vec![0f64; dim]);
+                    for _j in 0..steps{
+                        get_gaussian_to_vec_nonstandard(&mut rng, &mut x, 0f64, (2f64 * ds).sqrt());
+                        if x.norm() >= 1f64{
+                            continue 'walk;
+                        }
+                    }
+                    direct += x.norm().powi(2);
+                    count += 1;
+                }
+                direct /= count as f64;
+                assert!((sample - direct).abs() < 0.05 * direct + 0.005, "{} {} {} {}", dim, s, sample, direct);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_step_to_vec() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let mut gfrd = GFRD::new(2, 1.0, 3.0)?;
+        gfrd.renew(2);
+        let dt : f64 = 0.01;
+        let pos = Position::new(vec![0.0, 0.0]);
+        let mut vec = Position::new(vec![0.0, 0.0]);
+
+        // 너무 가까우면 Brownian step, 충분히 멀면 domain
+        gfrd.step_to_vec(&mut rng, 0, 0.0, dt, 0.1, &pos, &mut vec)?;
+        assert!(gfrd.domain(0).is_none() && vec.norm() > 0f64);
+        assert_eq!(gfrd.next_event_time(), None);
+
+        vec.clear();
+        gfrd.step_to_vec(&mut rng, 1, 0.0, dt, 2.0, &pos, &mut vec)?;
+        let t_exit : f64 = gfrd.domain(1).unwrap().t_exit;
+        assert!(t_exit > dt);
+        assert_eq!(vec, Position::new(vec![0.0, 0.0]));
+        assert_eq!((gfrd.num_domain(), gfrd.num_step()), (1, 1));
+
+        // exit time이 지나면 domain 표면에서 남은 시간만큼 움직인 위치
+        let time : f64 = t_exit - 0.5 * dt;
+        gfrd.step_to_vec(&mut rng, 1, time, dt, 2.0, &pos, &mut vec)?;
+        assert!(gfrd.domain(1).is_none());
+        assert!((vec.norm() - 2.0).abs() < 5f64 * (2f64 * 0.5 * dt).sqrt());
+
+        assert_eq!(gfrd.step_to_vec(&mut rng, 2, 0.0, dt, 2.0, &pos, &mut vec),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mfpt() -> Result<(), Error>{
+        // 반지름 R인 원의 중심에 반지름 a인 target, uniform한 초기 위치에서의 MFPT
+        //      T = [R^4 ln(R/a) / (R^2 - a^2) - (3R^2 - a^2) / 4] / 2D
+        // step 끝에서만 target을 확인하므로 a를 0.5826 sqrt(2 D dt)만큼 줄여 비교한다.
+        let mut rng = rng_seed(12341234);
+        let (sys_size, target_size, dt) : (f64, f64, f64) = (3.0, 1.0, 1e-3);
+        let sys = ContCircSystem::new(sys_size, 2);
+        let target = ContBulkTarget::new(Position::new(vec![0.0, 0.0]), target_size);
+        let mut agent = ContPassiveIndepAgent::new_uniform(&sys, &target, &mut rng, MoveType::Brownian(1.0))?;
+        let mut gfrd = GFRD::new(2, 1.0, 3.0)?;
+
+        let n : usize = 1000;
+        let mut mfpt : f64 = 0f64;
+        let mut vec = Position::new(vec![0.0, 0.0]);
+        let mut num_step : usize = 0;
+        for _i in 0..n{
+            agent.renew_uniform(&sys, &target, &mut rng)?;
+            gfrd.renew(1);
+            let mut time : f64 = 0f64;
+            loop{
+                if let Some(t_next) = gfrd.next_event_time(){
+                    time = time.max(t_next - dt);
+                }
+                let free_radius : f64 = (target.distance(&agent.pos)? - target_size).min(sys.signed_distance(&agent.pos)?);
+                vec.clear();
+                gfrd.step_to_vec(&mut rng, 0, time, dt, free_radius, &agent.pos, &mut vec)?;
+                sys.check_bc(&mut agent.pos, &mut vec)?;
+                time += dt;
+                if target.check_find(&agent.pos)?{
+                    break;
+                }
+            }
+            mfpt += time / n as f64;
+            num_step += gfrd.num_step();
+        }
+
+        let a : f64 = target_size - 0.5826 * (2f64 * dt).sqrt();
+        let r2 : f64 = sys_size * sys_size;
+        let answer : f64 = (r2 * r2 * (sys_size / a).ln() / (r2 - a * a) - (3f64 * r2 - a * a) / 4f64) / 2f64;
+        assert!((mfpt - answer).abs() < 0.08 * answer, "{} {}", mfpt, answer);
+        assert!((num_step as f64) < 0.5 * mfpt * n as f64 / dt);
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+// Module for numerical integration of overdamped Langevin equation
+//
+//      dx = (a(x) + f) dt + sqrt(2D) dW
+// a(x)는 agent가 Drift trait으로 제공하는 drift, f는 한 step 동안 고정된 drift (interaction force 등)이다.
+// Integrator는 scheme과 agent마다 필요한 상태를 관리하므로, time loop 안에서 random_move_to_vec 대신
+// step_to_vec을 사용하면 loop를 고치지 않고 simulation argument만으로 scheme을 바꿀 수 있다.
+//
+// Leimkuhler, B., Matthews, C., & Stoltz, G. (2016). The computation of averages from equilibrium and
+// nonequilibrium Langevin molecular dynamics. IMA Journal of Numerical Analysis, 36(1), 13-79.
+// Kloeden, P. E., & Platen, E. (1992). Numerical solution of stochastic differential equations. Springer. (Sec 11.2)
+
+use crate::prelude::*;
+use crate::agent_mod::{Drift};
+use crate::random_mod::{get_gaussian_to_vec};
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Integrator{                      // Overdamped Langevin equation의 한 step을 적분
+    pub scheme : IntegratorType,            // Numerical scheme
+    memory : Vec<Option<(f64, Position<f64>)>>, // agent마다 다음 step에서 다시 사용할 standard noise와 그 때의 dt (Leimkuhler-Matthews)
+}
+
+impl Integrator{
+    pub fn new(scheme : IntegratorType, num_agent : usize) -> Self{
+        // scheme : numerical scheme
+        // num_agent : 적분할 agent의 수
+        Integrator{
+            scheme,
+            memory : vec![None; num_agent],
+        }
+    }
+
+    pub fn renew(&mut self){
+        // 새 ensemble을 시작할 때 agent마다 기억하던 noise를 지운다.
+        for m in self.memory.iter_mut(){
+            *m = None;
+        }
+    }
+
+    pub fn step_to_vec<A>(&mut self, rng : &mut Pcg64, idx : usize, agent : &A, force : &Position<f64>,
+                          dt : f64, vec : &mut Position<f64>) -> Result<(), Error>
+        where A : AgentCore<f64> + Drift<f64, f64>{
+        // idx번째 agent의 한 step displacement를 vec에 더해준다.
+        // rng : random number generator
+        // idx : agent의 index. Leimkuhler-Matthews에서 agent마다 noise를 기억하는 데 사용
+        // agent : 적분할 agent
+        // force : step 동안 고정된 drift f. 없으면 0 vector
+        // dt : time step size
+        // vec : 값을 저장할 벡터
+
+        let pos : &Position<f64> = agent.pos();
+        let dim : usize = pos.dim();
+        if force.dim() != dim || vec.dim() != dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if idx >= self.memory.len(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let coeff_diff : f64 = agent.coeff_diff()?;
+        let length : f64 = (2f64 * coeff_diff * dt).sqrt();                  // variance가 sqrt(2 D dt)
+
+        let mut xi : Position<f64> = Position::new(vec![0f64; dim]);
+        get_gaussian_to_vec(rng, &mut xi);
+
+        let mut drift : Position<f64> = force.clone();                       // a(x) + f
+        agent.drift_to_vec(pos, &mut drift)?;
+
+        match self.scheme{
+            IntegratorType::EulerMaruyama => {
+                // x' = x + (a(x) + f) dt + sqrt(2 D dt) xi
+                for i in 0..dim{
+                    vec[i] += drift[i] * dt + length * xi[i];
+                }
+            },
+            IntegratorType::Heun => {
+                // predictor : y = x + (a(x) + f) dt + sqrt(2 D dt) xi
+                // corrector : x' = x + (a(x) + a(y)) dt / 2 + f dt + sqrt(2 D dt) xi
+                let mut predictor : Position<f64> = pos.clone();
+                for i in 0..dim{
+                    predictor[i] += drift[i] * dt + length * xi[i];
+                }
+                let mut drift_pred : Position<f64> = force.clone();
+                agent.drift_to_vec(&predictor, &mut drift_pred)?;
+                for i in 0..dim{
+                    vec[i] += 0.5 * (drift[i] + drift_pred[i]) * dt + length * xi[i];
+                }
+            },
+            IntegratorType::LeimkuhlerMatthews => {
+                // x' = x + (a(x) + f) dt + sqrt(2 D dt) (xi_n + xi_{n+1}) / 2
+                // xi_{n+1}은 다음 step의 xi_n으로 다시 사용된다.
+                // 일정한 dt를 가정한 scheme이므로, dt가 바뀌면 기억하던 noise를 버리고 새로 시작한다.
+                let prev : Position<f64> = match self.memory[idx].take(){
+                    Some((prev_dt, p)) if prev_dt == dt => p,
+                    _ => {
+                        let mut p : Position<f64> = Position::new(vec![0f64; dim]);
+                        get_gaussian_to_vec(rng, &mut p);
+                        p
+                    },
+                };
+                if prev.dim() != dim{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                for i in 0..dim{
+                    vec[i] += drift[i] * dt + 0.5 * length * (prev[i] + xi[i]);
+                }
+                self.memory[idx] = Some((dt, xi));
+            },
+            IntegratorType::StrongOrder15 => {
+                // Y+-_j = x + a dt +- b sqrt(dt) e_j,  b = sqrt(2D)
+                // x' = x + b dW + sum_j (a(Y+_j) - a(Y-_j)) / (2 sqrt(dt)) dZ_j
+                //        + dt / 4 [2 a(x) + sum_j (a(Y+_j) + a(Y-_j)) - 2 (d - 1) a(x + a dt)]
+                // dW = sqrt(dt) xi, dZ = dt^{3/2} (xi + eta / sqrt(3)) / 2
+                let mut eta : Position<f64> = Position::new(vec![0f64; dim]);
+                get_gaussian_to_vec(rng, &mut eta);
+
+                let sqrt_dt : f64 = dt.sqrt();
+                let shift : f64 = (2f64 * coeff_diff).sqrt() * sqrt_dt;
+
+                let mut base : Position<f64> = pos.clone();
+                for i in 0..dim{
+                    base[i] += drift[i] * dt;
+                }
+                let mut drift_base : Position<f64> = force.clone();
+                agent.drift_to_vec(&base, &mut drift_base)?;
+
+                let mut det : Position<f64> = Position::new(vec![0f64; dim]);
+                for i in 0..dim{
+                    det[i] = 2f64 * drift[i] - 2f64 * (dim - 1) as f64 * drift_base[i];
+                }
+
+                let mut shifted : Position<f64> = base.clone();
+                let mut plus : Position<f64> = Position::new(vec![0f64; dim]);
+                let mut minus : Position<f64> = Position::new(vec![0f64; dim]);
+                for j in 0..dim{
+                    shifted[j] = base[j] + shift;
+                    plus.clone_from(force);
+                    agent.drift_to_vec(&shifted, &mut plus)?;
+                    shifted[j] = base[j] - shift;
+                    minus.clone_from(force);
+                    agent.drift_to_vec(&shifted, &mut minus)?;
+                    shifted[j] = base[j];
+
+                    let dz : f64 = 0.5 * dt * sqrt_dt * (xi[j] + eta[j] / 3f64.sqrt());
+                    for i in 0..dim{
+                        det[i] += plus[i] + minus[i];
+                        vec[i] += (plus[i] - minus[i]) / (2f64 * sqrt_dt) * dz;
+                    }
+                }
+
+                for i in 0..dim{
+                    vec[i] += 0.25 * dt * det[i] + length * xi[i];
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::random_mod::get_gaussian_vec;
+
+    #[test]
+    fn test_single_step() -> Result<(), Error>{
+        // a(x) = - lambda x 일 때 같은 random number로 계산한 값과 비교
+        let (dt, lambda) : (f64, f64) = (0.1, 2.0);
+        let agent = ContPassiveFieldAgent::new(MoveType::Brownian(1.0), FieldType::Harmonic(Position::new(vec![0.0]), lambda),
+                            Position::new(vec![1.0]));
+        let zero = Position::new(vec![0.0]);
+        let b : f64 = 2f64.sqrt();
+        let g = get_gaussian_vec(&mut rng_seed(1234), 5);
+
+        // Strong order 1.5
+        let mut integrator = Integrator::new(IntegratorType::StrongOrder15, 1);
+        let mut vec = Position::new(vec![0.0]);
+        integrator.step_to_vec(&mut rng_seed(1234), 0, &agent, &zero, dt, &mut vec)?;
+        let dz : f64 = 0.5 * dt.powf(1.5) * (g[0] + g[1] / 3f64.sqrt());
+        let answer : f64 = - lambda * dt + 0.5 * (lambda * dt).powi(2) - lambda * b * dz + b * dt.sqrt() * g[0];
+        assert!((vec[0] - answer).abs() < 1e-10, "{} {}", vec[0], answer);
+
+        // Leimkuhler-Matthews : 두번째 step은 첫 step의 noise를 다시 사용
+        let mut integrator = Integrator::new(IntegratorType::LeimkuhlerMatthews, 1);
+        let mut rng = rng_seed(1234);
+        let mut agent = agent.clone();
+        vec.clear();
+        integrator.step_to_vec(&mut rng, 0, &agent, &zero, dt, &mut vec)?;
+        assert!((vec[0] - (- lambda * dt + 0.5 * b * dt.sqrt() * (g[0] + g[1]))).abs() < 1e-10);
+
+        agent.pos.mut_add(&vec);
+        let x : f64 = agent.pos[0];
+        vec.clear();
+        integrator.step_to_vec(&mut rng, 0, &agent, &zero, dt, &mut vec)?;
+        assert!((vec[0] - (- lambda * x * dt + 0.5 * b * dt.sqrt() * (g[0] + g[2]))).abs() < 1e-10);
+
+        // dt가 바뀌면 기억하던 noise를 다시 사용하지 않는다
+        agent.pos.mut_add(&vec);
+        let x : f64 = agent.pos[0];
+        vec.clear();
+        integrator.step_to_vec(&mut rng, 0, &agent, &zero, 2f64 * dt, &mut vec)?;
+        assert!((vec[0] - (- lambda * x * 2f64 * dt + 0.5 * b * (2f64 * dt).sqrt() * (g[3] + g[4]))).abs() < 1e-10);
+
+        assert_eq!(integrator.step_to_vec(&mut rng, 1, &agent, &zero, dt, &mut vec),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_constant_force() -> Result<(), Error>{
+        // 고정된 drift f만 있으면 평균 displacement는 f dt
+        let mut rng = rng_seed(12341234);
+        let n : usize = 20000;
+        let dt : f64 = 0.1;
+        let agent = ContPassiveIndepAgent::new(MoveType::Brownian(0.5), Position::new(vec![0.0, 0.0]));
+        let force = Position::new(vec![1.0, -2.0]);
+
+        for scheme in vec![IntegratorType::EulerMaruyama, IntegratorType::Heun,
+                           IntegratorType::LeimkuhlerMatthews, IntegratorType::StrongOrder15]{
+            let mut integrator = Integrator::new(scheme, 1);
+            let mut mean = Position::new(vec![0.0, 0.0]);
+            for _i in 0..n{
+                integrator.step_to_vec(&mut rng, 0, &agent, &force, dt, &mut mean)?;
+            }
+            mean.mut_scalar_mul(1f64 / n as f64);
+            assert!((mean[0] - 0.1).abs() < 0.01 && (mean[1] + 0.2).abs() < 0.01, "{} {}", scheme, mean);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stationary_variance() -> Result<(), Error>{
+        // Harmonic trap에서 정상상태 분산. 정확한 값은 1 / k
+        // lambda dt = 0.2 에서 EM : 1 / (1 - lambda dt / 2), LM : 정확, Heun과 SO1.5 : 1% 이내
+        let mut rng = rng_seed(12341234);
+        let n : usize = 200000;
+        let dt : f64 = 0.2;
+        let zero = Position::new(vec![0.0]);
+
+        for (scheme, answer) in vec![(IntegratorType::EulerMaruyama, 1f64 / 0.9),
+                                     (IntegratorType::Heun, 0.989),
+                                     (IntegratorType::LeimkuhlerMatthews, 1f64),
+                                     (IntegratorType::StrongOrder15, 0.993)]{
+            let mut agent = ContPassiveFieldAgent::new(MoveType::Brownian(1.0), FieldType::Harmonic(Position::new(vec![0.0]), 1.0),
+                                Position::new(vec![0.0]));
+            let mut integrator = Integrator::new(scheme, 1);
+            let mut vec = Position::new(vec![0.0]);
+            let mut var : f64 = 0f64;
+            for _i in 0..n{
+                vec.clear();
+                integrator.step_to_vec(&mut rng, 0, &agent, &zero, dt, &mut vec)?;
+                agent.pos.mut_add(&vec);
+                var += agent.pos[0].powi(2) / n as f64;
+            }
+            assert!((var - answer).abs() < 0.03, "{} {} {}", scheme, var, answer);
+        }
+        Ok(())
+    }
+}
@@ -17,6 +17,11 @@ pub mod random_mod;
 pub mod position;
 pub mod iterator;
 pub mod setup;
+pub mod trajectory;
+pub mod resetting;
+pub mod integrator;
+pub mod gfrd;
+pub mod bridge;
 
 pub mod prelude;
 
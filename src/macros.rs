@@ -96,6 +96,8 @@ macro_rules! impl_structure{
         impl $name{
             define_pub_num_args!($num_args);
 
+            // argument 개수만큼 인자를 받는 생성자
+            #[allow(clippy::too_many_arguments)]
             pub fn new($($var : $t),*) -> Self{
                 Self{
                     $($type_name : $type_default,
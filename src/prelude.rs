@@ -4,7 +4,7 @@ pub use crate::{
     {define_num_args, define_pub_num_args, define_num_args_of_structure,
         define_structure, define_structure_wo_eq, impl_structure, construct_structure,
         impl_fn_info, impl_fn_brief_info, impl_fn_print_configuration,
-        impl_fn_read_args_from_vec, impl_fn_read_args_from_lines, impl_argument_trait,
+        impl_fn_read_args_from_vec, impl_fn_read_args_from_lines, impl_convert_result, impl_argument_trait,
         impl_fmt_for_type, impl_fmt_test, impl_fromstr_for_type, impl_fromstr_test,
         construct_dataset, derive_hash, export_form, pub_export_form, export_data, pub_export_data,
         export_simulation_item, define_total_num_args, read_arguments, export_simulation_info,
@@ -12,30 +12,53 @@ pub use crate::{
     },
     error::{Error, ErrorCode},
     position::{Position, Numerics},
-    argument::{Argument},
+    argument::{Argument, ConvertResult},
     analysis::{Bin, Var1, Analysis, TimeAnalysis, TimeVecAnalysis, ProcessAnalysis, DataSet},
     random_mod::{rng_seed},
-    system_mod::{SystemCore, SystemType, BoundaryCond,
+    system_mod::{SystemCore, SystemType, BoundaryCond, FaceBoundary, FaceCond, ReflectionType,
             cont_circ::{ContCircSystem, ContCircSystemArguments},
             cont_cubic::{ContCubicSystem, ContCubicSystemArguments},
-            cont_cyl::{ContCylindricalSystem, ContCylindricalSystemArguments}
+            cont_cyl::{ContCylindricalSystem, ContCylindricalSystemArguments},
+            cont_rect::{ContRectSystem, ContRectSystemArguments},
+            cont_shell::{ContShellSystem, ContShellSystemArguments},
+            cont_ellip::{ContEllipsoidSystem, ContEllipsoidSystemArguments},
+            cont_polygon::{ContPolygonSystem, ContPolygonSystemArguments},
+            cont_mesh::{ContMeshSystem, ContMeshSystemArguments},
+            cont_surface::{ContSphereSurfaceSystem, ContSphereSurfaceSystemArguments},
+            crowded::{CrowdedSystem, Obstacle}
     },
     target_mod::{TargetCore, TargetType,
             // cont_boundary::{ContBoundaryTarget, ContBoundaryTargetArguments},
             cont_bulk::{ContBulkTarget, ContBulkTargetArguments},
+            cont_surface::{ContSurfaceTarget, ContSurfaceTargetArguments},
     },
-    agent_mod::{AgentCore, Passive, Active, Interaction, Merge,
-            types::{AgentType, MoveType, InitType, InteractType},
+    agent_mod::{AgentCore, Passive, Stateful, Drift, Active, Interaction, Merge,
+            types::{AgentType, MoveType, InitType, InteractType, FieldType, ForceFn, IntegratorType,
+                DiffusionType, NoiseConvention, WaitingType, JumpType},
             cont_passive_indep::{ContPassiveIndepAgent, ContPassiveIndepAgentArguments},
             cont_passive_merge::{ContPassiveMergeAgent, ContPassiveMergeAgentArguments},
             cont_passive_exp::{ContPassiveExpAgent, ContPassiveExpAgentArguments},
             cont_passive_lj::{ContPassiveLJAgent, ContPassiveLJAgentArguments},
+            cont_passive_field::{ContPassiveFieldAgent, ContPassiveFieldAgentArguments},
+            cont_passive_hetero::{ContPassiveHeteroAgent, ContPassiveHeteroAgentArguments},
+            cont_passive_ou::{ContPassiveOUAgent, ContPassiveOUAgentArguments},
+            cont_passive_fbm::{ContPassiveFBMAgent, ContPassiveFBMAgentArguments},
+            cont_passive_ctrw::{ContPassiveCTRWAgent, ContPassiveCTRWAgentArguments},
+            cont_passive_langevin::{ContPassiveLangevinAgent, ContPassiveLangevinAgentArguments},
+            cont_active_intermittent::{SearchPhase, ContActiveIntermittentAgent, ContActiveIntermittentAgentArguments},
     },
-    time_mod::{TimeType, TimeIterator,
+    time_mod::{TimeType, TimeIterator, TimeGrid,
         ConstStep, ConstStepArguments,
-        ExponentialStep, ExponentialStepArguments},
-    setup::{VariableSimulation, VariableSimulationArguments, ParVariableSimulation, ParVariableSimulationArguments, ProcessSimulation, ProcessSimulationArguments},
+        ExponentialStep, ExponentialStepArguments,
+        AdaptiveStep, AdaptiveStepArguments},
+    setup::{VariableSimulation, VariableSimulationArguments, ParVariableSimulation, ParVariableSimulationArguments, ProcessSimulation, ProcessSimulationArguments,
+        ResetSimulation, ResetSimulationArguments, IntegratorSimulation, IntegratorSimulationArguments},
     iterator::{Node, LinkedList},
+    trajectory::{TrajFormat, RecordType, TrajectoryAgent, TrajectoryWriter},
+    resetting::{ResetType, ResetProtocol, ResetDest, ResetEvent, Resetting},
+    integrator::{Integrator},
+    gfrd::{Domain, GFRD},
+    bridge::{crossing_probability, plane_crossing_probability, sphere_crossing_probability, check_crossing},
     macros::TypeName,
 };
 
@@ -0,0 +1,192 @@
+// Module for fractional Gaussian noise
+//
+// Hurst exponent H를 갖는 fractional Gaussian noise (fractional Brownian motion의 unit step increment)를
+// Davies-Harte method (circulant embedding)로 생성한다.
+// 길이 n의 autocovariance
+//      gamma(k) = ( |k+1|^{2H} - 2|k|^{2H} + |k-1|^{2H} ) / 2
+// 를 크기 m = 2^p >= 2n 인 circulant matrix에 embedding 하면, 그 eigenvalue는 FFT 한 번으로 구해지고
+// fGn에 대해서는 모든 0 < H < 1에서 음이 아님이 알려져 있다.
+// 복소 gaussian을 사용하면 FFT 한 번에 독립인 sample path 두 개(실수부, 허수부)가 나온다.
+//
+// Davies, R. B., & Harte, D. S. (1987). Tests for Hurst effect. Biometrika, 74(1), 95-101.
+
+use crate::prelude::*;
+use crate::random_mod::get_gaussian;
+
+
+pub fn fgn_autocovariance(hurst : f64, k : usize) -> f64{
+    // lag k에서 unit step fGn의 autocovariance
+    // hurst : Hurst exponent
+    // k : lag
+
+    let h2 : f64 = 2f64 * hurst;
+    let k : f64 = k as f64;
+    0.5 * ((k + 1f64).powf(h2) - 2f64 * k.powf(h2) + (k - 1f64).abs().powf(h2))
+}
+
+fn fft(re : &mut [f64], im : &mut [f64]){
+    // 길이가 2의 거듭제곱인 in-place radix-2 FFT.  X_k = sum_j x_j exp(-2 pi i jk / m)
+
+    let m : usize = re.len();
+    if m < 2{
+        return;
+    }
+
+    // bit reversal permutation
+    let mut j : usize = 0;
+    for i in 1..m{
+        let mut bit = m >> 1;
+        while j & bit != 0{
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j{
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len : usize = 2;
+    while len <= m{
+        let theta : f64 = -2f64 * PI / len as f64;
+        for start in (0..m).step_by(len){
+            for k in 0..len / 2{
+                let (ws, wc) = (theta * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tr : f64 = wc * re[b] - ws * im[b];
+                let ti : f64 = wc * im[b] + ws * re[b];
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct FGNGenerator{                // 고정된 길이의 unit step fGn sample path 생성기
+    pub hurst : f64,                    // Hurst exponent
+    pub length : usize,                 // length of sample path
+    sqrt_eigen : Vec<f64>,              // sqrt(lambda_j / m)
+    spare : Option<Vec<f64>>,           // 앞선 FFT에서 남은 독립 sample path
+    buffer : (Vec<f64>, Vec<f64>),
+}
+
+impl FGNGenerator{
+    pub fn new(hurst : f64, length : usize) -> Result<Self, Error>{
+        // hurst : Hurst exponent, 0 < H < 1
+        // length : 한번에 생성할 increment의 수
+
+        if hurst <= 0f64 || hurst >= 1f64 || length == 0{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let m : usize = (2 * length).next_power_of_two();
+        let mut re : Vec<f64> = (0..m).map(|j| fgn_autocovariance(hurst, j.min(m - j))).collect();
+        let mut im : Vec<f64> = vec![0f64; m];
+        fft(&mut re, &mut im);
+
+        let max : f64 = re.iter().cloned().fold(0f64, f64::max);
+        let mut sqrt_eigen : Vec<f64> = Vec::with_capacity(m);
+        for lambda in re{
+            if lambda < - 1e-10 * max{
+                // circulant embedding이 positive semidefinite하지 않음
+                return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+            }
+            sqrt_eigen.push((lambda.max(0f64) / m as f64).sqrt());
+        }
+
+        Ok(FGNGenerator{
+            hurst,
+            length,
+            sqrt_eigen,
+            spare : None,
+            buffer : (vec![0f64; m], vec![0f64; m]),
+        })
+    }
+
+    pub fn generate_to_vec(&mut self, rng : &mut Pcg64, vec : &mut Vec<f64>){
+        // unit step fGn sample path를 vec에 기록한다. 분산은 1, 합의 분산은 n^{2H}.
+        // rng : random number generator
+        // vec : 결과를 저장할 vector. 길이는 length로 맞춰진다.
+
+        if let Some(spare) = self.spare.take(){
+            *vec = spare;
+            return;
+        }
+
+        let (re, im) = &mut self.buffer;
+        for (j, s) in self.sqrt_eigen.iter().enumerate(){
+            re[j] = s * get_gaussian(rng);
+            im[j] = s * get_gaussian(rng);
+        }
+        fft(re, im);
+
+        vec.clear();
+        vec.extend_from_slice(&re[..self.length]);
+        self.spare = Some(im[..self.length].to_vec());
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_fft(){
+        // 직접 계산한 DFT와 비교
+        let m : usize = 16;
+        let x : Vec<f64> = (0..m).map(|j| ((j * j) % 7) as f64 - 2.5).collect();
+        let (mut re, mut im) = (x.clone(), vec![0f64; m]);
+        fft(&mut re, &mut im);
+
+        for k in 0..m{
+            let (mut sr, mut si) = (0f64, 0f64);
+            for j in 0..m{
+                let theta = -2f64 * PI * (j * k) as f64 / m as f64;
+                sr += x[j] * theta.cos();
+                si += x[j] * theta.sin();
+            }
+            assert!((re[k] - sr).abs() < 1e-10 && (im[k] - si).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_autocovariance(){
+        assert_eq!(fgn_autocovariance(0.5, 0), 1f64);
+        assert!(fgn_autocovariance(0.5, 3).abs() < 1e-15);
+        assert!(fgn_autocovariance(0.7, 1) > 0f64);
+        assert!(fgn_autocovariance(0.3, 1) < 0f64);
+    }
+
+    #[test]
+    fn test_fgn_statistics() -> Result<(), Error>{
+        // 합의 분산 n^{2H}와 lag 1 covariance 확인
+        let mut rng = rng_seed(12341234);
+        let (n, ensemble) : (usize, usize) = (64, 20000);
+
+        for hurst in vec![0.25, 0.5, 0.8]{
+            let mut generator = FGNGenerator::new(hurst, n)?;
+            let mut path : Vec<f64> = vec![];
+            let (mut var_sum, mut cov1) = (0f64, 0f64);
+            for _i in 0..ensemble{
+                generator.generate_to_vec(&mut rng, &mut path);
+                assert_eq!(path.len(), n);
+                let s : f64 = path.iter().sum();
+                var_sum += s * s / ensemble as f64;
+                cov1 += path[10] * path[11] / ensemble as f64;
+            }
+            let answer : f64 = (n as f64).powf(2f64 * hurst);
+            assert!((var_sum - answer).abs() < 0.05 * answer, "{} {} {}", hurst, var_sum, answer);
+            assert!((cov1 - fgn_autocovariance(hurst, 1)).abs() < 0.05, "{} {}", hurst, cov1);
+        }
+
+        assert_eq!(FGNGenerator::new(1.0, n), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
+}
@@ -10,10 +10,12 @@
 
 
 use crate::prelude::*;
-use rand_distr::StandardNormal;
+use rand_distr::{StandardNormal, Gamma};
 use rand::distributions::Open01;
 use rand::Rng;
 
+pub mod fgn;            // Davies-Harte method로 fractional Gaussian noise를 생성
+
 
 pub fn rng_seed(seed : u128) -> Pcg64{
     // PCG family algorithm을 기반해서 random number를 만드는 generator를 만들어주는 함수.
@@ -120,10 +122,75 @@ pub fn get_gaussian_to_vec_nonstandard(rng: &mut Pcg64, vec: &mut Position<f64>,
     }
 }
 
+pub fn get_direction_to_vec(rng: &mut Pcg64, vec: &mut Position<f64>){
+    // 모든 방향이 같은 확률을 갖는 unit vector를 vec에 기입
+    // standard normal vector는 isotropic하므로, 크기로 나눠주면 된다.
+    // rng : random number generator
+    // vec : 결과값을 저장할 vector의 reference
+
+    loop{
+        get_gaussian_to_vec(rng, vec);
+        let norm : f64 = vec.norm();
+        if norm > 0f64{
+            vec.mut_scalar_mul(1f64 / norm);
+            return;
+        }
+    }
+}
+
+pub fn get_ball_to_vec(rng: &mut Pcg64, vec: &mut [f64], radius: f64){
+    // 반지름 radius인 vec.len() 차원 ball 안에서 uniform한 점을 vec에 기입
+    // 방향은 gaussian vector로, 반지름은 r^d가 uniform하도록 u^(1/d)로 뽑는다. rejection이 없어 모든 차원에서 빠르다.
+    // rng : random number generator
+    // vec : 결과값을 저장할 slice
+    // radius : ball의 반지름
+
+    if vec.is_empty(){
+        return;
+    }
+    let r : f64 = radius * get_uniform(rng).powf(1f64 / vec.len() as f64);
+    let mut norm : f64 = 0f64;
+    while norm == 0f64{
+        for x in vec.iter_mut(){
+            *x = rng.sample(StandardNormal);
+        }
+        norm = vec.iter().map(|x| x * x).sum::<f64>().sqrt();
+    }
+    for x in vec.iter_mut(){
+        *x *= r / norm;
+    }
+}
+
+pub fn get_gamma(rng : &mut Pcg64, shape : f64, scale : f64) -> Result<f64, Error>{
+    // Gamma distribution x^(shape - 1) exp(-x / scale)을 따르는 random number
+    // rng : random number generator
+    // shape : shape parameter
+    // scale : scale parameter
+
+    let gamma = Gamma::new(shape, scale).map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+    Ok(rng.sample(gamma))
+}
+
 #[cfg(test)]
 mod tests{
     use super::*;
 
+    #[test]
+    fn test_ball(){
+        // 10차원 단위 ball에서 |x|^10은 (0, 1)에서 uniform, 즉 평균 1/2이어야 한다.
+        let mut rng = rng_seed(3123412314);
+        let n : usize = 10000;
+        let mut vec = [0f64; 10];
+        let mut mean : f64 = 0f64;
+        for _i in 0..n{
+            get_ball_to_vec(&mut rng, &mut vec, 2.0);
+            let r : f64 = vec.iter().map(|x| x * x).sum::<f64>().sqrt() / 2.0;
+            assert!(r < 1.0);
+            mean += r.powi(10) / n as f64;
+        }
+        assert!((mean - 0.5).abs() < 0.02, "{}", mean);
+    }
+
     #[test]
     fn test_uniform(){
         // uniform distribution 범위 안에 잘 들어오는가 확인하는 테스트
@@ -188,4 +255,17 @@ mod tests{
         assert_eq!(pos4, Position::<f64>::new(vec![-0.1368207211245114, -1.7150386023894229, 1.1485182266457186]));
 
     }
+
+    #[test]
+    fn test_direction(){
+        let mut rng = rng_seed(3123412314);
+        let mut vec = Position::<f64>::new(vec![0f64; 3]);
+
+        for _i in 0..10{
+            get_direction_to_vec(&mut rng, &mut vec);
+            assert!((vec.norm() - 1f64).abs() < 1e-10);
+        }
+        assert!(get_gamma(&mut rng, 2.0, 1.0).unwrap() > 0f64);
+        assert_eq!(get_gamma(&mut rng, -1.0, 1.0), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+    }
 }
@@ -0,0 +1,393 @@
+// Module for stochastic resetting of searchers
+//
+// Resetting protocol은 time loop 안에서 random_move_to_vec, check_bc와 함께 사용된다.
+// 각 agent마다 다음 reset 시간을 관리하고, 그 시간이 지나면 agent를 초기 위치 혹은
+// 주어진 resetting distribution에서 뽑은 위치로 되돌린다.
+
+use crate::prelude::*;
+use crate::random_mod::{get_uniform};
+
+
+// =====================================================================================
+// ===  Implement ResetType ============================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug)]
+pub enum ResetType{                         // Waiting time distribution between resets
+    Poissonian(f64),                        // Poissonian resetting with rate r
+    Deterministic(f64),                     // Resetting at every given period
+    Custom(fn(&mut Pcg64) -> f64),          // Custom waiting time distribution
+}
+
+impl ResetType{
+    // Rate를 이용해 reset type을 정의. Deterministic의 경우 period = 1 / rate
+    pub fn from_rate(protocol : ResetProtocol, rate : f64) -> Self{
+        match protocol{
+            ResetProtocol::Poissonian => ResetType::Poissonian(rate),
+            ResetProtocol::Deterministic => ResetType::Deterministic(1f64 / rate),
+        }
+    }
+
+    // Check whether parameters are valid
+    pub fn check_parameters(&self) -> Result<(), Error>{
+        match self{
+            ResetType::Poissonian(rate) if *rate < 0f64 || rate.is_nan() => {
+                Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput))
+            },
+            ResetType::Deterministic(period) if *period <= 0f64 || period.is_nan() => {
+                Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // 다음 reset까지의 waiting time
+    pub fn waiting_time(&self, rng : &mut Pcg64) -> f64{
+        match self{
+            ResetType::Poissonian(rate) => {
+                if *rate <= 0f64{                                   // rate 0 : resetting 없음
+                    return f64::INFINITY;
+                }
+                -(1f64 - get_uniform(rng)).ln() / rate              // exponential distribution
+            },
+            ResetType::Deterministic(period) => *period,
+            ResetType::Custom(f) => f(rng),
+        }
+    }
+}
+
+impl PartialEq for ResetType{
+    fn eq(&self, other : &Self) -> bool{
+        match (self, other){
+            (ResetType::Poissonian(x), ResetType::Poissonian(y)) => x == y,
+            (ResetType::Deterministic(x), ResetType::Deterministic(y)) => x == y,
+            (ResetType::Custom(f), ResetType::Custom(g)) => *f as usize == *g as usize,
+            _ => false,
+        }
+    }
+}
+
+impl Display for ResetType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            ResetType::Poissonian(rate) => write!(f, "Poissonian resetting with rate {}", rate),
+            ResetType::Deterministic(period) => write!(f, "Deterministic resetting with period {}", period),
+            ResetType::Custom(_) => write!(f, "Resetting with custom waiting time distribution"),
+        }
+    }
+}
+
+impl FromStr for ResetType{
+    type Err = Error;
+
+    // Custom waiting time distribution은 argument로 줄 수 없다.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        let parse = |x : &str| x.parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+
+        if split.len() == 5 && split[1] == "resetting" {
+            match split[0]{
+                "Poissonian"    => return Ok(ResetType::Poissonian(parse(split[4])?)),
+                "Deterministic" => return Ok(ResetType::Deterministic(parse(split[4])?)),
+                _ => return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+
+        let s = s.trim();
+        if let (Some(start), true) = (s.find('('), s.ends_with(')')){
+            let value = parse(&s[start + 1..s.len() - 1])?;
+            return match &s[..start]{
+                "Poisson" | "Poissonian"    => Ok(ResetType::Poissonian(value)),
+                "Deterministic" | "Sharp"   => Ok(ResetType::Deterministic(value)),
+                _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            };
+        }
+        Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput))
+    }
+}
+
+
+// =====================================================================================
+// ===  Implement ResetProtocol ========================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Default)]
+pub enum ResetProtocol{                     // Simulation argument로 주어지는 resetting 방식
+    #[default]
+    Poissonian,                             // exponential waiting time with mean 1 / r
+    Deterministic,                          // constant waiting time 1 / r
+}
+
+impl_fmt_for_type!(ResetProtocol,
+    ResetProtocol::Poissonian => "Poissonian resetting",
+    ResetProtocol::Deterministic => "Deterministic resetting");
+
+impl FromStr for ResetProtocol{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim(){
+            "Poissonian resetting" | "Poissonian" | "Poisson" => Ok(ResetProtocol::Poissonian),
+            "Deterministic resetting" | "Deterministic" | "Sharp" => Ok(ResetProtocol::Deterministic),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+
+// =====================================================================================
+// ===  Implement Resetting ============================================================
+// =====================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ResetDest{                         // Resetting 후의 위치
+    Initial,                                // 각 agent의 초기 위치
+    Distribution(InitType<f64>),            // 주어진 분포에서 매번 새로 뽑은 위치
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ResetEvent{                      // Record of single reset event
+    pub time : f64,                         // time of resetting
+    pub idx : usize,                        // index of agent
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resetting{
+    pub rtype : ResetType,                  // waiting time distribution
+    pub dest : ResetDest,                   // destination of resetting
+    pub with_log : bool,                    // whether reset events are recorded
+    initial : Vec<Position<f64>>,           // initial positions of agents
+    next_time : Vec<f64>,                   // next resetting time of each agent
+    num_reset : usize,                      // total number of resets
+    log : Vec<ResetEvent>,                  // reset events
+}
+
+impl Resetting{
+    pub fn new(rtype : ResetType, dest : ResetDest) -> Self{
+        Resetting{
+            rtype,
+            dest,
+            with_log : true,
+            initial : Vec::new(),
+            next_time : Vec::new(),
+            num_reset : 0,
+            log : Vec::new(),
+        }
+    }
+
+    pub fn set_log(&mut self, with_log : bool){
+        self.with_log = with_log;
+    }
+
+    // 새 ensemble을 시작할 때 호출. 초기 위치를 저장하고 다음 reset 시간을 새로 뽑는다.
+    // agent들의 renew_uniform 이후에 호출해야 한다.
+    pub fn renew<'a, I>(&mut self, positions : I, rng : &mut Pcg64) -> Result<(), Error>
+        where I : IntoIterator<Item = &'a Position<f64>>{
+        self.rtype.check_parameters()?;
+
+        let mut n : usize = 0;
+        for pos in positions{
+            if n < self.initial.len(){
+                self.initial[n].clone_from(pos);
+                self.next_time[n] = self.rtype.waiting_time(rng);
+            }
+            else{
+                self.initial.push(pos.clone());
+                self.next_time.push(self.rtype.waiting_time(rng));
+            }
+            n += 1;
+        }
+        self.initial.truncate(n);
+        self.next_time.truncate(n);
+
+        self.num_reset = 0;
+        self.log.clear();
+        Ok(())
+    }
+
+    // Number of agents managed by the protocol
+    pub fn num_agent(&self) -> usize{
+        self.initial.len()
+    }
+
+    // Next resetting time of idx-th agent
+    pub fn next_time(&self, idx : usize) -> Option<f64>{
+        self.next_time.get(idx).copied()
+    }
+
+    // idx-th agent의 reset 시간이 지났다면 위치를 reset하고 true를 반환한다.
+    // check_bc 이후, target.check_find와 같은 step 안에서 호출한다.
+    pub fn check_reset(&mut self, idx : usize, time : f64, pos : &mut Position<f64>,
+                       sys : &dyn SystemCore<f64>, target : &dyn TargetCore<f64>,
+                       rng : &mut Pcg64) -> Result<bool, Error>{
+        if idx >= self.next_time.len(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        if time < self.next_time[idx]{
+            return Ok(false);
+        }
+
+        match &self.dest{
+            ResetDest::Initial => {
+                if pos.dim() != self.initial[idx].dim(){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+                }
+                pos.clone_from(&self.initial[idx]);
+            },
+            ResetDest::Distribution(itype) => {
+                itype.init_to_vec(sys, target, rng, pos)?;
+            },
+        }
+
+        // Deterministic resetting은 time step에 상관없이 주기가 밀리지 않도록 한다.
+        match self.rtype{
+            ResetType::Deterministic(period) => {
+                while self.next_time[idx] <= time{
+                    self.next_time[idx] += period;
+                }
+            },
+            _ => {
+                self.next_time[idx] = time + self.rtype.waiting_time(rng);
+            },
+        }
+
+        self.num_reset += 1;
+        if self.with_log{
+            self.log.push(ResetEvent{
+                time,
+                idx,
+            });
+        }
+        Ok(true)
+    }
+
+    // Total number of resets in current ensemble
+    pub fn num_reset(&self) -> usize{
+        self.num_reset
+    }
+
+    // Recorded reset events in current ensemble
+    pub fn log(&self) -> &[ResetEvent]{
+        &self.log
+    }
+
+    // Export reset events. 한 줄에 하나의 event : (time) (index of agent)
+    pub fn export_log<W : Write>(&self, writer : &mut W) -> Result<(), Error>{
+        for event in &self.log{
+            writeln!(writer, "{0:.5e}\t{1}", event.time, event.idx).map_err(Error::make_error_io)?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::{impl_fmt_test, impl_fromstr_test};
+
+    impl_fmt_test!(test_fmt_resettype,
+        ResetType::Poissonian(0.5) => "Poissonian resetting with rate 0.5",
+        ResetType::Deterministic(2.0) => "Deterministic resetting with period 2");
+
+    impl_fromstr_test!(test_fromstr_resettype,
+        ResetType,
+        ResetType::Poissonian(0.5) => "Poissonian resetting with rate 0.5",
+        ResetType::Deterministic(2.0) => "Deterministic resetting with period 2",
+        ResetType::Poissonian(0.1) => "Poisson(0.1)",
+        ResetType::Deterministic(3.0) => "Sharp(3)");
+
+    impl_fromstr_test!(test_fromstr_resetprotocol,
+        ResetProtocol,
+        ResetProtocol::Poissonian => "Poissonian resetting",
+        ResetProtocol::Deterministic => "Deterministic resetting",
+        ResetProtocol::Poissonian => "Poisson",
+        ResetProtocol::Deterministic => "Sharp");
+
+    #[test]
+    fn test_waiting_time(){
+        let mut rng = rng_seed(12341234);
+        let n : usize = 100000;
+
+        let rtype = ResetType::Poissonian(2.0);
+        let mean : f64 = (0..n).map(|_| rtype.waiting_time(&mut rng)).sum::<f64>() / n as f64;
+        assert!((mean - 0.5).abs() < 0.01);
+
+        assert_eq!(ResetType::Poissonian(0.0).waiting_time(&mut rng), f64::INFINITY);
+        assert_eq!(ResetType::from_rate(ResetProtocol::Deterministic, 4.0).waiting_time(&mut rng), 0.25);
+
+        fn constant(_rng : &mut Pcg64) -> f64 { 1.5 }
+        assert_eq!(ResetType::Custom(constant).waiting_time(&mut rng), 1.5);
+
+        assert_eq!(ResetType::Poissonian(-1.0).check_parameters(),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(ResetType::Deterministic(0.0).check_parameters(),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+    }
+
+    #[test]
+    fn test_deterministic_reset() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let sys = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::new(vec![0.0, 0.0]), 1.0);
+
+        let init = vec![Position::new(vec![3.0, 0.0]), Position::new(vec![0.0, 4.0])];
+        let mut resetting = Resetting::new(ResetType::Deterministic(1.0), ResetDest::Initial);
+        resetting.renew(init.iter(), &mut rng)?;
+        assert_eq!(resetting.num_agent(), 2);
+
+        let mut pos = Position::new(vec![5.0, 5.0]);
+        assert_eq!(resetting.check_reset(0, 0.5, &mut pos, &sys, &target, &mut rng), Ok(false));
+        assert_eq!(pos, Position::new(vec![5.0, 5.0]));
+
+        assert_eq!(resetting.check_reset(0, 1.05, &mut pos, &sys, &target, &mut rng), Ok(true));
+        assert_eq!(pos, Position::new(vec![3.0, 0.0]));
+        assert_eq!(resetting.next_time(0), Some(2.0));
+
+        // 여러 주기가 한 번에 지나가도 다음 reset 시간은 주기 위에 있다
+        assert_eq!(resetting.check_reset(0, 3.2, &mut pos, &sys, &target, &mut rng), Ok(true));
+        assert_eq!(resetting.next_time(0), Some(4.0));
+
+        assert_eq!(resetting.num_reset(), 2);
+        assert_eq!(resetting.log(), &[ResetEvent{time : 1.05, idx : 0}, ResetEvent{time : 3.2, idx : 0}]);
+
+        let mut output : Vec<u8> = Vec::new();
+        resetting.export_log(&mut output)?;
+        assert_eq!(String::from_utf8(output).unwrap(), "1.05000e0\t0\n3.20000e0\t0\n");
+
+        assert_eq!(resetting.check_reset(2, 3.2, &mut pos, &sys, &target, &mut rng),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+
+        resetting.renew(init.iter().take(1), &mut rng)?;
+        assert_eq!(resetting.num_agent(), 1);
+        assert_eq!(resetting.num_reset(), 0);
+        assert!(resetting.log().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset_to_distribution() -> Result<(), Error>{
+        let mut rng = rng_seed(12341234);
+        let sys = ContCircSystem::new(10.0, 2);
+        let target = ContBulkTarget::new(Position::new(vec![0.0, 0.0]), 1.0);
+
+        let init = vec![Position::new(vec![3.0, 0.0])];
+        let mut resetting = Resetting::new(ResetType::Poissonian(10.0),
+                                ResetDest::Distribution(InitType::Shell(2.0)));
+        resetting.set_log(false);
+        resetting.renew(init.iter(), &mut rng)?;
+
+        let mut pos = Position::new(vec![5.0, 5.0]);
+        let mut count : usize = 0;
+        for i in 1..1000{
+            if resetting.check_reset(0, i as f64 * 0.01, &mut pos, &sys, &target, &mut rng)?{
+                assert!((pos.norm() - 2.0).abs() < 1e-10);
+                count += 1;
+            }
+        }
+        assert_eq!(resetting.num_reset(), count);
+        assert!(count > 50);
+        assert!(resetting.log().is_empty());
+        Ok(())
+    }
+}
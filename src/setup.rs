@@ -50,7 +50,7 @@ macro_rules! export_simulation_info {
     ($dataset:ident, $dir:ident, $writer:ident, $width:ident, $prefix:expr  $(, $struct_type:ty, $struct_name:ident, $argument_name:ident)*) => {
 
         $(
-            let $struct_name = <$struct_type>::convert_from(&$argument_name);
+            let $struct_name = <$struct_type>::convert_from(&$argument_name).into_result()?;
             )*
 
         fs::create_dir_all(&$dir).map_err(Error::make_error_io)?;
@@ -192,6 +192,75 @@ impl ProcessSimulation{
     }
 }
 
+pub struct ResetSimulation{
+    pub num_ensemble : usize,
+    pub protocol : ResetProtocol,
+    pub reset_rate : f64,
+    pub idx_set : usize,
+    pub seed : u128,
+    pub output_dir : String,
+}
+
+impl_argument_trait!(ResetSimulation, "Reset Simulation", ResetSimulationArguments, 6;
+    num_ensemble, usize, "Number of Ensemble",
+    protocol, ResetProtocol, "Resetting protocol. ex) Poissonian : exponential waiting time / Deterministic : period 1/r",
+    reset_rate, f64, "Resetting rate r",
+    idx_set, usize, "Index of Ensemble Set",
+    seed, u128, "Initial Seed for Random Number Generator",
+    output_dir, String, "Directory containing output file");
+
+impl ResetSimulation{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ResetSimulationArguments) -> Self{
+        Self{
+            num_ensemble    : argument.num_ensemble,
+            protocol        : argument.protocol,
+            reset_rate      : argument.reset_rate,
+            idx_set         : argument.idx_set,
+            seed            : argument.seed,
+            output_dir      : argument.output_dir.clone(),
+        }
+    }
+
+    // Waiting time distribution corresponding to given protocol and rate
+    pub fn reset_type(&self) -> ResetType{
+        ResetType::from_rate(self.protocol, self.reset_rate)
+    }
+}
+
+
+pub struct IntegratorSimulation{
+    pub num_ensemble : usize,
+    pub scheme : IntegratorType,
+    pub idx_set : usize,
+    pub seed : u128,
+    pub output_dir : String,
+}
+
+impl_argument_trait!(IntegratorSimulation, "Integrator Simulation", IntegratorSimulationArguments, 5;
+    num_ensemble, usize, "Number of Ensemble",
+    scheme, IntegratorType, "Numerical scheme. ex) EM : Euler-Maruyama / Heun : Stochastic Heun / BAOAB : Leimkuhler-Matthews / SO15 : Strong order 1.5",
+    idx_set, usize, "Index of Ensemble Set",
+    seed, u128, "Initial Seed for Random Number Generator",
+    output_dir, String, "Directory containing output file");
+
+impl IntegratorSimulation{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &IntegratorSimulationArguments) -> Self{
+        Self{
+            num_ensemble    : argument.num_ensemble,
+            scheme          : argument.scheme,
+            idx_set         : argument.idx_set,
+            seed            : argument.seed,
+            output_dir      : argument.output_dir.clone(),
+        }
+    }
+
+    // Integrator for given number of agents
+    pub fn integrator(&self, num_agent : usize) -> Integrator{
+        Integrator::new(self.scheme, num_agent)
+    }
+}
 
 
 #[cfg(test)]
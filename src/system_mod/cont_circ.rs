@@ -2,13 +2,15 @@
 // 연속된 원형, 혹은 구형 시스템
 
 use crate::prelude::*;
+use crate::system_mod::{reflect_ball_exact, absorb_or_reflect, unit_ball_volume};
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct ContCircSystem{              // 연속 원형(구형) 시스템
     pub sys_type : SystemType,             // System type
     pub bctype : BoundaryCond,          // Boundary condition : here, only reflective bc is available
     pub sys_size : f64,                   // radius of system
     pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
 }
 
 impl ContCircSystem{
@@ -25,10 +27,33 @@ impl ContCircSystem{
             bctype : BoundaryCond::Reflection,
             sys_size : r,
             dim : dim,
+            reflection : ReflectionType::Approximate,
         }
     }
+
+    pub fn new_with_bc(bctype : BoundaryCond, r : f64, dim : usize) -> Self{
+        // 경계가 흡수하는 원형(구형) 시스템
+        // bctype : Reflection, Absorbing, PartiallyAbsorbing 중 하나
+        match bctype{
+            BoundaryCond::Reflection | BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) => (),
+            _ => panic!("{:?}", ErrorCode::InvalidArgumentInput),
+        }
+        ContCircSystem{
+            bctype,
+            ..Self::new(r, dim)
+        }
+    }
+}
+
+impl Default for ContCircSystem{
+    // 반지름 1인 원. header에서 읽지 않은 field를 채울 때 사용된다.
+    fn default() -> Self{
+        Self::new(1f64, 2)
+    }
 }
 
+// reflection은 argument로 받지 않는다. 지금까지의 output 파일 header와 같은 형식을 유지하기 위함이고,
+// Exact reflection은 set_reflection으로 정한다.
 impl_argument_trait!(ContCircSystem, "System", ContCircSystemArguments, 2,
     sys_type, SystemType, SystemType::ContinuousCircular,
     bctype, BoundaryCond, BoundaryCond::Reflection;
@@ -45,9 +70,10 @@ impl ContCircSystem{
 
         Self{
             sys_type    : argument.sys_type,
-            bctype      : argument.bctype,
+            bctype      : argument.bctype.clone(),
             sys_size    : argument.sys_size,
             dim         : argument.dim,
+            reflection  : ReflectionType::Approximate,
         }
     }
 }
@@ -80,6 +106,23 @@ impl SystemCore<f64> for ContCircSystem{
         // pos : initial position of ptl
         // dp : displacement of ptl
 
+        if self.bctype.is_absorbing(){
+            // 흡수 경계에서는 agent를 지워야 하므로 check_bc_absorb를 사용해야 한다.
+            pos.mut_add(dp);
+            if self.check_inclusion(pos)?{
+                return Ok(());
+            }
+            pos.mut_sub(dp);
+            return Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary));
+        }
+
+        if self.reflection == ReflectionType::Exact{
+            if self.dim != pos.dim() || self.dim != dp.dim(){
+                return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+            }
+            return reflect_ball_exact(&mut pos.coordinate, &mut dp.coordinate, None, self.sys_size);
+        }
+
         pos.mut_add(dp);                           // add dp to pos
         if self.check_inclusion(pos)?{              // if ptl is still in the system after movement
             return Ok(());                          // return
@@ -97,46 +140,80 @@ impl SystemCore<f64> for ContCircSystem{
 
 
 
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 반사시키고,
+        // 경계의 법선 방향 (반지름 방향) velocity 성분을 뒤집는다.
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        // vel : velocity of ptl
+
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if self.bctype.is_absorbing(){
+            return self.check_bc(pos, dp);
+        }
+        if self.reflection == ReflectionType::Exact{
+            if self.dim != pos.dim() || self.dim != dp.dim(){
+                return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+            }
+            return reflect_ball_exact(&mut pos.coordinate, &mut dp.coordinate, Some(&mut vel.coordinate), self.sys_size);
+        }
+        pos.mut_add(dp);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+
+        let r0 : f64 = self.sys_size;
+        let s : f64 = pos.norm();
+        let vn : f64 = pos.inner_product(vel)? / s;
+        for i in 0..self.dim{
+            vel[i] -= 2f64 * vn * pos[i] / s;
+        }
+        pos.mut_scalar_mul((2f64 * r0 - s) / s);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+        Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+    }
+
+    fn check_bc_absorb(&self, rng: &mut Pcg64, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                       coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+        // 흡수 경계에 닿으면 true. 흡수되지 않으면 반사 경계처럼 check_bc
+        let reflective = Self{ bctype : BoundaryCond::Reflection, ..*self };
+        absorb_or_reflect(&reflective, &self.bctype, rng, pos, dp, coeff_diff, dt)
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
     fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
         // System 내부의 임의의 위치를 uniform하게 뽑아 반환
         // rng : random number generator
 
-        use crate::random_mod::get_uniform_vec;
-
-        let r : f64 = self.sys_size;
-        let dim : usize = self.dim;
-        let pos0 : Position<f64> = Position::<f64>::new(vec![-0.5f64; self.dim]);
-        let mut pos1;
-        loop{
-            pos1 = &get_uniform_vec(rng, dim) + &pos0;          // 일단 (0, 1)^dim 에서 뽑은 후에, (-0.5, 0.5)^dim이 되도록
-                                                                // 평행이동
-            pos1.mut_scalar_mul(2f64 * r);                      // 그러고 2r을 곱해서 (-r, r)^dim로 변환
-            if self.check_inclusion(&pos1)?{                    // 그래도 system 밖에 있을 수 있으니 check
-                break;
-            }
-        }
-        return Ok(pos1);
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
     }
 
     fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
         // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // 외접하는 cube에서 rejection하면 고차원에서 acceptance가 급격히 줄어드므로 ball에서 바로 뽑는다.
         // rng : random number generator
         // vec : 결과 적을 mutable reference
 
-        use crate::random_mod::get_uniform_to_vec_nonstandard;
+        use crate::random_mod::get_ball_to_vec;
 
-        let r : f64 = self.sys_size;
-        let dim : usize = self.dim;
-        if vec.dim() != dim{
+        if vec.dim() != self.dim{
             return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
         }
-        loop{
-            vec.clear();
-            get_uniform_to_vec_nonstandard(rng, vec, -r, r);        // (-r, r)^dim 에서 uniform하게 뽑음
-            if self.check_inclusion(vec)?{                          // 그리고 그게 system 내부에 있는지 확인
-                break;
-            }
-        }
+        get_ball_to_vec(rng, &mut vec.coordinate, self.sys_size);
         return Ok(());
     }
 
@@ -162,9 +239,48 @@ impl SystemCore<f64> for ContCircSystem{
         }
         Ok(())
     }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 구면까지의 거리. 안쪽이 양수
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok(self.sys_size - pos.norm())
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        // 가장 가까운 구면 위의 점의 normal은 pos 방향. 중심에서는 모든 방향이 같으므로 첫번째 축으로 둔다.
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let r : f64 = pos.norm();
+        if r == 0f64{
+            normal.clear();
+            normal[0] = 1f64;
+            return Ok(());
+        }
+        for (n, x) in normal.coordinate.iter_mut().zip(&pos.coordinate){
+            *n = x / r;
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        Ok(unit_ball_volume(self.dim) * self.sys_size.powi(self.dim as i32))
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // d 차원 구의 겉넓이는 d V_d R^(d-1)
+        Ok(self.dim as f64 * unit_ball_volume(self.dim) * self.sys_size.powi(self.dim as i32 - 1))
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let r : f64 = self.sys_size;
+        Ok((Position::new(vec![-r; self.dim]), Position::new(vec![r; self.dim])))
+    }
 }
 
-pub fn check_bc_exact(sys: ContCircSystem, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+pub fn check_bc_exact(sys: &ContCircSystem, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
     // Most exact way
     // 가장 정확한 방법으로 reflection을 계산하는 함수
     // pos 에서 pos + dp 를 잇는 선분에서 시스템의 경계점을 찾고
@@ -174,31 +290,15 @@ pub fn check_bc_exact(sys: ContCircSystem, pos: &mut Position<f64>, dp: &mut Pos
     // sys : system configuration
     // pos : initial position of ptl
     // dp : displacement of ptl
+    // 한 step에 여러번 부딪히는 경우도 경계점마다 반사를 반복한다.
 
-    pos.mut_add(dp);
-    if sys.check_inclusion(pos)?{
-        return Ok(());
+    if sys.dim != pos.dim() || sys.dim != dp.dim(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
     }
-
-    pos.mut_sub(dp);
-    let r0 = sys.sys_size;
-    let r = pos.norm();
-    let dr = dp.norm();
-    let rdr = pos.inner_product(dp)?;
-    let t =  (- rdr + (rdr * rdr + dr * dr * (r0 * r0 - r * r)).sqrt()) / (dr * dr);
-    let k = (1f64 - t) * (rdr + t * dr * dr) / (r0 * r0);
-
-    dp.mut_scalar_mul(1f64 - 2f64 * k * t);
-    pos.mut_scalar_mul(1f64 - 2f64 * k);
-    pos.mut_add(dp);
-
-    if sys.check_inclusion(pos)?{
-        return Ok(());
-    }
-    return Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep));
+    reflect_ball_exact(&mut pos.coordinate, &mut dp.coordinate, None, sys.sys_size)
 }
 
-pub fn check_bc_first_order(sys: ContCircSystem, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+pub fn check_bc_first_order(sys: &ContCircSystem, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
     // first order expansion
     // 위의 정확한 계산에서 dp가 작고, pos이 경계에 가깝단 조건에서 first order만 남긴 결과
     // 비례로 줄여들어오는 것보다 오히려 더 안좋다.
@@ -270,6 +370,17 @@ mod tests{
             assert_eq!(sys.check_inclusion(&pos), Ok(true));
         }
 
+        // 고차원에서도 rejection 없이 뽑힌다. (r/R)^dim의 평균은 1/2
+        let sys : ContCircSystem = ContCircSystem::new(3.0, 20);
+        let mut pos : Position<f64> = Position::new(vec![0.0; 20]);
+        let mut mean : f64 = 0f64;
+        for _i in 0..10000{
+            sys.random_pos_to_vec(&mut rng, &mut pos)?;
+            assert_eq!(sys.check_inclusion(&pos), Ok(true));
+            mean += (pos.norm() / 3.0).powi(20) / 10000f64;
+        }
+        assert!((mean - 0.5).abs() < 0.02, "{}", mean);
+
         Ok(())
     }
 
@@ -292,7 +403,7 @@ mod tests{
 
                     let mut pos1 = pos.clone();
                     let mut dp1 = dp.clone();
-                    check_bc_exact(sys, &mut pos1, &mut dp1)?;
+                    check_bc_exact(&sys, &mut pos1, &mut dp1)?;
 
                     let mut pos2 = pos.clone();
                     let mut dp2 = dp.clone();
@@ -313,8 +424,109 @@ mod tests{
         let mut dp : Position<f64> = Position::new(vec![0.96, 0.0]);
         let res : Position<f64> = Position::new(vec![4.99, 0.0]);
 
-        check_bc_exact(sys, &mut pos, &mut dp)?;
+        check_bc_exact(&sys, &mut pos, &mut dp)?;
         assert!((&pos - &res).norm() < 1e-10);
         return Ok(());
     }
+
+    #[test]
+    fn test_check_bc_with_velocity() -> Result<(), Error>{
+        // 반지름 방향 velocity 성분만 뒤집힌다
+        let sys : ContCircSystem = ContCircSystem::new(5.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![4.9, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.2, 0.0]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 2.0]);
+
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![4.9, 0.0])).norm() < 1e-10);
+        assert!((&vel - &Position::new(vec![-1.0, 2.0])).norm() < 1e-10);
+
+        let mut dp : Position<f64> = Position::new(vec![-0.1, 0.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&vel - &Position::new(vec![-1.0, 2.0])).norm() < 1e-10);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_reflection_exact() -> Result<(), Error>{
+        // 한 step에 여러번 부딪혀도 시스템 안에 남고, 결과는 check_bc_exact와 같다.
+        let mut sys : ContCircSystem = ContCircSystem::new(1.0, 2);
+        assert_eq!(sys.reflection(), ReflectionType::Approximate);
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![3.5, 0.0]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep)));
+
+        sys.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![2.5, 0.0]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 0.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![-0.5, 0.0])).norm() < 1e-12);
+        assert!((&vel - &Position::new(vec![-1.0, 0.0])).norm() < 1e-12);
+
+        let mut pos1 : Position<f64> = Position::new(vec![0.9, 0.1]);
+        let mut dp1 : Position<f64> = Position::new(vec![0.2, 0.05]);
+        let (mut pos2, mut dp2) = (pos1.clone(), dp1.clone());
+        sys.check_bc(&mut pos1, &mut dp1)?;
+        check_bc_exact(&sys, &mut pos2, &mut dp2)?;
+        assert!((&pos1 - &pos2).norm() < 1e-12);
+
+        // check_bc_exact는 system의 reflection 방식과 상관없이 exact reflection을 계산한다.
+        sys.set_reflection(ReflectionType::Approximate)?;
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![2.5, 0.0]);
+        check_bc_exact(&sys, &mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![-0.5, 0.0])).norm() < 1e-12);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_absorb() -> Result<(), Error>{
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContCircSystem = ContCircSystem::new_with_bc(BoundaryCond::Absorbing, 1.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.6, 0.0]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(true));
+        assert!((&pos - &Position::new(vec![0.5, 0.0])).norm() < 1e-12);
+
+        let mut dp : Position<f64> = Position::new(vec![0.2, 0.0]);
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(false));
+        assert!((&pos - &Position::new(vec![0.7, 0.0])).norm() < 1e-12);
+
+        // 흡수되지 않으면 반사된다.
+        let sys : ContCircSystem = ContCircSystem::new_with_bc(BoundaryCond::PartiallyAbsorbing(1e-6), 1.0, 2);
+        let mut dp : Position<f64> = Position::new(vec![0.4, 0.0]);
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(false));
+        assert!((&pos - &Position::new(vec![0.9, 0.0])).norm() < 1e-12);
+
+        // 반사 경계는 사라지지 않는다.
+        let sys : ContCircSystem = ContCircSystem::new(1.0, 2);
+        let mut dp : Position<f64> = Position::new(vec![0.2, 0.0]);
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(false));
+        assert!((&pos - &Position::new(vec![0.9, 0.0])).norm() < 1e-12);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        use std::f64::consts::PI;
+        let sys : ContCircSystem = ContCircSystem::new(2.0, 3);
+        let pos : Position<f64> = Position::new(vec![0.0, 1.5, 0.0]);
+        let mut normal : Position<f64> = Position::new(vec![0.0; 3]);
+
+        assert_eq!(sys.signed_distance(&pos)?, 0.5);
+        assert_eq!(sys.signed_distance(&Position::new(vec![0.0, 0.0, 3.0]))?, -1.0);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 1.0, 0.0]));
+        assert!((sys.volume()? - 4.0 * PI * 8.0 / 3.0).abs() < 1e-12);
+        assert!((sys.surface_area()? - 4.0 * PI * 4.0).abs() < 1e-12);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-2.0; 3]), Position::new(vec![2.0; 3])));
+
+        let sys : ContCircSystem = ContCircSystem::new(2.0, 2);
+        assert!((sys.volume()? - 4.0 * PI).abs() < 1e-12);
+        assert!((sys.surface_area()? - 4.0 * PI).abs() < 1e-12);
+        assert_eq!(sys.signed_distance(&pos), Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        return Ok(());
+    }
 }
@@ -2,13 +2,16 @@
 // 연속된 원형, 혹은 구형 시스템
 
 use crate::prelude::*;
+use crate::system_mod::{fold_reflect_exact, fold_periodic_exact, absorb_or_reflect, fold_box,
+                        signed_distance_from_excess, normal_weights_from_excess};
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct ContCubicSystem{              // 연속 cubic 시스템
     pub sys_type : SystemType,             // System type
     pub bctype : BoundaryCond,          // Boundary condition : reflective or periodic
     pub sys_size : f64,                   // width,length,height of system. ex. 1D domain = (-sys_size, sys_size)
     pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
 }
 
 impl ContCubicSystem{
@@ -20,10 +23,10 @@ impl ContCubicSystem{
             panic!("{:?}", ErrorCode::InvalidArgumentInput);
         }
 
-        if let BoundaryCond::Mixed(d) = bctype{
-            if d > dim{
-                panic!("{}", ErrorCode::InvalidArgumentInput);
-            }
+        match &bctype{
+            BoundaryCond::Mixed(d) if *d > dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            BoundaryCond::PerFace(faces) if faces.dim() != dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            _ => (),
         }
 
         ContCubicSystem{
@@ -31,15 +34,17 @@ impl ContCubicSystem{
             bctype : bctype,
             sys_size : length,
             dim : dim,
+            reflection : ReflectionType::Approximate,
         }
     }
 }
 
-impl_argument_trait!(ContCubicSystem, "System", ContCubicSystemArguments, 3,
+impl_argument_trait!(ContCubicSystem, "System", ContCubicSystemArguments, 4,
     sys_type, SystemType, SystemType::ContinuousRectangular;
-    bctype, BoundaryCond, "Boundary condition. ex) Reflective, Periodic, integer indicates Mixed",
+    bctype, BoundaryCond, "Boundary condition. ex) Reflective, Periodic, integer indicates Mixed, P:R/A indicates per-face",
     sys_size, f64, "Size of System",
-    dim, usize, "Dimension of System");
+    dim, usize, "Dimension of System",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
 
 impl ContCubicSystem{
     #[allow(dead_code)]
@@ -49,17 +54,18 @@ impl ContCubicSystem{
             panic!("{:?}", ErrorCode::InvalidArgumentInput);
         }
 
-        if let BoundaryCond::Mixed(d) = argument.bctype{
-            if d > argument.dim{
-                panic!("{}", ErrorCode::InvalidArgumentInput);
-            }
+        match &argument.bctype{
+            BoundaryCond::Mixed(d) if *d > argument.dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            BoundaryCond::PerFace(faces) if faces.dim() != argument.dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            _ => (),
         }
 
         Self{
             sys_type    : argument.sys_type,
-            bctype      : argument.bctype,
+            bctype      : argument.bctype.clone(),
             sys_size    : argument.sys_size,
             dim         : argument.dim,
+            reflection  : argument.reflection,
         }
     }
 }
@@ -91,6 +97,24 @@ impl SystemCore<f64> for ContCubicSystem{
         // pos : initial position of ptl
         // dp : displacement of ptl
 
+        if let BoundaryCond::PerFace(faces) = &self.bctype{
+            return self.check_bc_per_face(faces, pos, dp, None, None).map(|_| ());
+        }
+
+        if self.bctype.is_absorbing(){
+            // 흡수 경계에서는 agent를 지워야 하므로 check_bc_absorb를 사용해야 한다.
+            pos.mut_add(dp);
+            if self.check_inclusion(pos)?{
+                return Ok(());
+            }
+            pos.mut_sub(dp);
+            return Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary));
+        }
+
+        if self.reflection == ReflectionType::Exact{
+            return self.check_bc_exact(pos, dp, None);
+        }
+
         pos.mut_add(dp);                           // add dp to pos
         if self.check_inclusion(pos)?{              // if ptl is still in the system after movement
             return Ok(());                          // return
@@ -171,7 +195,10 @@ impl SystemCore<f64> for ContCubicSystem{
                 if self.check_inclusion(pos)?{              // 지금은 안에 있는가?
                     return Ok(());
                 }
-            }
+            },
+            BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) | BoundaryCond::PerFace(_) => {
+                return Err(Error::make_error_syntax(ErrorCode::InvalidType));
+            },
         }
 
         return Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep));
@@ -180,6 +207,59 @@ impl SystemCore<f64> for ContCubicSystem{
 
 
 
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 옮기고,
+        // 반사된 성분의 velocity를 뒤집는다. periodic 성분은 그대로 둔다.
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        // vel : velocity of ptl
+
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if let BoundaryCond::PerFace(faces) = &self.bctype{
+            return self.check_bc_per_face(faces, pos, dp, None, Some(vel)).map(|_| ());
+        }
+        if self.reflection == ReflectionType::Exact{
+            return self.check_bc_exact(pos, dp, Some(vel));
+        }
+        let num_reflect : usize = match self.bctype{
+            BoundaryCond::Reflection => self.dim,
+            BoundaryCond::Periodic => 0,
+            BoundaryCond::Mixed(d) => d,
+            BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) | BoundaryCond::PerFace(_) => {
+                return self.check_bc(pos, dp);
+            },
+        };
+
+        let length : f64 = self.sys_size;
+        for i in 0..num_reflect{
+            if (pos[i] + dp[i]).abs() > length{
+                vel[i] = - vel[i];
+            }
+        }
+        self.check_bc(pos, dp)
+    }
+
+    fn check_bc_absorb(&self, rng: &mut Pcg64, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                       coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+        // 흡수 경계에 닿으면 true. 흡수되지 않으면 반사 경계처럼 check_bc
+        if let BoundaryCond::PerFace(faces) = &self.bctype{
+            return self.check_bc_per_face(faces, pos, dp, Some((rng, coeff_diff, dt)), None);
+        }
+        let reflective = Self{ bctype : BoundaryCond::Reflection, ..*self };
+        absorb_or_reflect(&reflective, &self.bctype, rng, pos, dp, coeff_diff, dt)
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
     fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
         // System 내부의 임의의 위치를 uniform하게 뽑아 반환
         // rng : random number generator
@@ -233,8 +313,110 @@ impl SystemCore<f64> for ContCubicSystem{
         }
         Ok(())
     }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // periodic하지 않은 축의 벽들만 경계로 본다.
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let excess : Vec<f64> = self.wall_axes().map(|i| pos[i].abs() - self.sys_size).collect();
+        Ok(signed_distance_from_excess(&excess))
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let axes : Vec<usize> = self.wall_axes().collect();
+        let excess : Vec<f64> = axes.iter().map(|&i| pos[i].abs() - self.sys_size).collect();
+        let weights : Vec<f64> = normal_weights_from_excess(&excess)?;
+        normal.clear();
+        for (&i, w) in axes.iter().zip(&weights){
+            // 원점 위에서는 양의 방향 벽을 택한다.
+            normal[i] = if pos[i] < 0f64 { -w } else { *w };
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        Ok((2f64 * self.sys_size).powi(self.dim as i32))
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 벽이 있는 축마다 넓이 (2L)^(d-1)인 면이 두 개
+        let num_wall : usize = self.wall_axes().count();
+        Ok(2f64 * num_wall as f64 * (2f64 * self.sys_size).powi(self.dim as i32 - 1))
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let length : f64 = self.sys_size;
+        Ok((Position::new(vec![-length; self.dim]), Position::new(vec![length; self.dim])))
+    }
+
+    fn is_periodic(&self, axis: usize) -> bool{
+        axis < self.dim && self.wall_axes().all(|i| i != axis)
+    }
 }
 
+impl ContCubicSystem{
+    fn wall_axes(&self) -> impl Iterator<Item = usize> + '_{
+        // periodic하지 않아서 벽이 있는 축들
+        (0..self.dim).filter(move |&i| match &self.bctype{
+            BoundaryCond::Periodic => false,
+            BoundaryCond::Mixed(d) => i < *d,
+            BoundaryCond::PerFace(faces) => !faces.is_periodic(i),
+            BoundaryCond::Reflection | BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) => true,
+        })
+    }
+}
+
+impl ContCubicSystem{
+    fn check_bc_exact(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        // 벽이 모두 좌표축에 수직이므로 straight path의 specular reflection은 각 성분마다 따로 계산된다.
+        // 모서리에 부딪히는 경우도 두 성분이 동시에 뒤집히는 것과 같다.
+        // 한 step에 여러번 부딪히거나 여러 period를 지나가도 된다.
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let num_reflect : usize = match self.bctype{
+            BoundaryCond::Reflection => self.dim,
+            BoundaryCond::Periodic => 0,
+            BoundaryCond::Mixed(d) => d,
+            BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) | BoundaryCond::PerFace(_) => {
+                return Err(Error::make_error_syntax(ErrorCode::InvalidType));
+            },
+        };
+
+        let length : f64 = self.sys_size;
+        pos.mut_add(dp);
+        let mut vel = vel;
+        for i in 0..self.dim{
+            if i < num_reflect{
+                if fold_reflect_exact(&mut pos[i], length){
+                    if let Some(v) = vel.as_mut(){
+                        v[i] = - v[i];
+                    }
+                }
+            }
+            else{
+                fold_periodic_exact(&mut pos[i], length);
+            }
+        }
+        Ok(())
+    }
+
+    fn check_bc_per_face(&self, faces: &FaceBoundary, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                         absorb: Option<(&mut Pcg64, f64, f64)>, vel: Option<&mut Position<f64>>) -> Result<bool, Error>{
+        // 각 면마다 boundary condition이 다른 경우. 흡수되면 true를 반환하고 pos는 움직이기 전 그대로 둔다.
+        // absorb : (rng, coeff_diff, dt). None이면 흡수하는 면에 닿았을 때 AbsorbingBoundary error
+        // vel : 주어지면 반사될 때마다 해당 성분을 뒤집는다.
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let length : f64 = self.sys_size;
+        fold_box(|i| [(-length, faces.lower(i)), (length, faces.upper(i))], self.reflection, pos, dp, absorb, vel)
+    }
+}
 
 #[cfg(test)]
 mod tests{
@@ -350,4 +532,237 @@ mod tests{
         }
         return Ok(());
     }
+
+    #[test]
+    fn test_check_bc_with_velocity() -> Result<(), Error>{
+        // 반사된 성분만 velocity가 뒤집힌다
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Mixed(1), 5.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![4.9, 4.9]);
+        let mut dp : Position<f64> = Position::new(vec![0.2, 0.2]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![4.9, -4.9])).norm() < 1e-10);
+        assert_eq!(vel, Position::new(vec![-1.0, 1.0]));
+
+        let mut dp : Position<f64> = Position::new(vec![0.01, 0.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert_eq!(vel, Position::new(vec![-1.0, 1.0]));
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_reflection_exact() -> Result<(), Error>{
+        // 모서리 근처에서 긴 step으로 여러번 부딪히는 경우
+        let mut sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Mixed(1), 1.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![2.0, 4.2]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep)));
+
+        sys.set_reflection(ReflectionType::Exact)?;
+        assert_eq!(sys.reflection(), ReflectionType::Exact);
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![2.0, 4.2]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![-0.5, 0.7])).norm() < 1e-12);
+        assert_eq!(vel, Position::new(vec![-1.0, 1.0]));
+
+        // 모서리에 정확히 부딪히면 두 성분이 모두 뒤집힌다.
+        let sys : ContCubicSystem = ContCubicSystem{ reflection : ReflectionType::Exact,
+                                    ..ContCubicSystem::new(BoundaryCond::Reflection, 1.0, 2) };
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![1.0, 1.0]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![0.5, 0.5])).norm() < 1e-12);
+        assert_eq!(vel, Position::new(vec![-1.0, -1.0]));
+
+        // 한번 부딪히는 경우는 근사와 같다.
+        let sys2 : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Reflection, 1.0, 2);
+        let mut pos1 : Position<f64> = Position::new(vec![0.9, -0.95]);
+        let mut dp1 : Position<f64> = Position::new(vec![0.3, -0.1]);
+        let (mut pos2, mut dp2) = (pos1.clone(), dp1.clone());
+        sys.check_bc(&mut pos1, &mut dp1)?;
+        sys2.check_bc(&mut pos2, &mut dp2)?;
+        assert!((&pos1 - &pos2).norm() < 1e-12);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_absorb() -> Result<(), Error>{
+        // 1D (-1, 1)에서 0에서 출발한 agent의 평균 탈출 시간
+        //      Absorbing : L^2 / 2D,   PartiallyAbsorbing(kappa) : L^2 / 2D + L / kappa
+        // Absorbing은 step 끝에서만 확인하므로 L을 0.5826 sqrt(2 D dt)만큼 늘려 비교한다.
+        use crate::random_mod::get_gaussian_to_vec_nonstandard;
+
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let (dt, n) : (f64, usize) = (1e-3, 2000);
+        let l : f64 = 1f64 + 0.5826 * (2f64 * dt).sqrt();
+        for (bctype, answer) in [(BoundaryCond::Absorbing, 0.5 * l * l), (BoundaryCond::PartiallyAbsorbing(0.5), 2.5)]{
+            let sys : ContCubicSystem = ContCubicSystem::new(bctype.clone(), 1.0, 1);
+            let mut pos : Position<f64> = Position::new(vec![0.0]);
+            let mut dp : Position<f64> = Position::new(vec![0.0]);
+            let mut mean : f64 = 0f64;
+            for _i in 0..n{
+                pos[0] = 0f64;
+                let mut time : f64 = 0f64;
+                loop{
+                    dp.clear();
+                    get_gaussian_to_vec_nonstandard(&mut rng, &mut dp, 0f64, (2f64 * dt).sqrt());
+                    time += dt;
+                    if sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, dt)?{
+                        break;
+                    }
+                    assert!(sys.check_inclusion(&pos)?);
+                }
+                mean += time / n as f64;
+            }
+            assert!((mean - answer).abs() < 0.05 * answer, "{} {} {}", bctype, mean, answer);
+        }
+
+        let mut pos : Position<f64> = Position::new(vec![0.9]);
+        let mut dp : Position<f64> = Position::new(vec![0.2]);
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Absorbing, 1.0, 1);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        assert!((pos[0] - 0.9).abs() < 1e-12);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_per_face() -> Result<(), Error>{
+        // x : periodic, y : -1에서 반사, +1에서 흡수
+        let faces : FaceBoundary = "P:R/A".parse()?;
+        let mut sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::PerFace(faces), 1.0, 2);
+
+        let mut pos : Position<f64> = Position::new(vec![0.9, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.2, -1.5]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![-0.9, -0.5])).norm() < 1e-12);
+
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.5, -1.2]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![0.5, -0.8])).norm() < 1e-12);
+        assert_eq!(vel, Position::new(vec![1.0, -1.0]));
+
+        // 흡수하는 면에 닿으면 check_bc는 error, check_bc_absorb는 true. pos는 그대로 둔다.
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let mut pos : Position<f64> = Position::new(vec![0.9, 0.9]);
+        let mut dp : Position<f64> = Position::new(vec![0.3, 0.2]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        assert_eq!(pos, Position::new(vec![0.9, 0.9]));
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(true));
+        assert_eq!(pos, Position::new(vec![0.9, 0.9]));
+
+        // Approximate reflection은 한 성분을 한번만 접는다.
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![4.5, 0.0]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep)));
+        assert_eq!(pos, Position::new(vec![0.0, 0.0]));
+        sys.set_reflection(ReflectionType::Exact)?;
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.5, 0.0])).norm() < 1e-12);
+
+        // -1에서 반사된 후 +1의 흡수 경계에 닿는다.
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.0, -3.5]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(true));
+        assert_eq!(pos, Position::new(vec![0.0, 0.0]));
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_per_face_high_dim() -> Result<(), Error>{
+        // 20차원에서 마지막 축만 +1에서 흡수하고, 나머지 면은 모두 반사한다.
+        let dim : usize = 20;
+        let mut sides : Vec<(FaceCond, FaceCond)> = vec![(FaceCond::Reflection, FaceCond::Reflection); dim];
+        sides[dim - 1] = (FaceCond::Reflection, FaceCond::Absorbing);
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::PerFace(FaceBoundary::new(&sides)?), 1.0, dim);
+
+        let mut pos : Position<f64> = Position::new(vec![0.9; dim]);
+        let mut dp : Position<f64> = Position::new(vec![0.0; dim]);
+        dp[0] = 0.2;
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((pos[0] - 0.9).abs() < 1e-12);
+
+        let mut pos : Position<f64> = Position::new(vec![0.9; dim]);
+        let mut dp : Position<f64> = Position::new(vec![0.0; dim]);
+        dp[dim - 1] = 0.2;
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        return Ok(());
+    }
+
+    #[test]
+    fn test_mfpt_per_face()-> Result<(), Error>{
+        // x는 periodic, y는 -1에서 반사하고 +1에서 흡수하는 slab. y = 0에서 출발하면 평균 탈출 시간은
+        //      ((2L)^2 - L^2) / 2D
+        // step 끝에서만 확인하므로 흡수 경계를 0.5826 sqrt(2 D dt)만큼 밀어 비교한다.
+        use crate::random_mod::get_gaussian_to_vec_nonstandard;
+
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let (dt, n) : (f64, usize) = (1e-3, 2000);
+        let l : f64 = 2f64 + 0.5826 * (2f64 * dt).sqrt();
+        let answer : f64 = 0.5 * (l * l - 1f64);
+
+        let sys : ContCubicSystem = ContCubicSystem::new("P:R/A".parse()?, 1.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut mean : f64 = 0f64;
+        for _i in 0..n{
+            pos.clear();
+            let mut time : f64 = 0f64;
+            loop{
+                dp.clear();
+                get_gaussian_to_vec_nonstandard(&mut rng, &mut dp, 0f64, (2f64 * dt).sqrt());
+                time += dt;
+                if sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, dt)?{
+                    break;
+                }
+                assert!(sys.check_inclusion(&pos)?);
+            }
+            mean += time / n as f64;
+        }
+        assert!((mean - answer).abs() < 0.05 * answer, "{} {}", mean, answer);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Reflection, 1.0, 2);
+        let mut normal : Position<f64> = Position::new(vec![0.0; 2]);
+
+        let pos : Position<f64> = Position::new(vec![0.2, -0.7]);
+        assert!((sys.signed_distance(&pos)? - 0.3).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, -1.0]));
+
+        // 모서리 밖에서는 모서리까지의 거리
+        let pos : Position<f64> = Position::new(vec![4.0, 5.0]);
+        assert!((sys.signed_distance(&pos)? + 5.0).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert!((&normal - &Position::new(vec![0.6, 0.8])).norm() < 1e-12);
+
+        assert_eq!(sys.volume()?, 4.0);
+        assert_eq!(sys.surface_area()?, 8.0);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-1.0; 2]), Position::new(vec![1.0; 2])));
+
+        // periodic한 축에는 벽이 없다.
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Mixed(1), 1.0, 2);
+        assert!((sys.signed_distance(&Position::new(vec![0.2, -0.9]))? - 0.8).abs() < 1e-12);
+        assert_eq!(sys.surface_area()?, 4.0);
+        let sys : ContCubicSystem = ContCubicSystem::new("P:R/A".parse()?, 1.0, 2);
+        assert!((sys.signed_distance(&Position::new(vec![0.9, 0.5]))? - 0.5).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![0.9, 0.5]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 1.0]));
+
+        let sys : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Periodic, 1.0, 2);
+        assert_eq!(sys.signed_distance(&Position::new(vec![0.9, 0.5]))?, f64::INFINITY);
+        assert_eq!(sys.surface_area()?, 0.0);
+        assert_eq!(sys.boundary_normal_to_vec(&Position::new(vec![0.9, 0.5]), &mut normal),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+        return Ok(());
+    }
 }
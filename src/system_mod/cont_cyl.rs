@@ -2,14 +2,18 @@
 // 연속된 원형, 혹은 구형 시스템
 
 use crate::prelude::*;
+use crate::system_mod::{reflect_ball_exact, fold_reflect_exact, fold_periodic_exact, unit_ball_volume,
+                        signed_distance_from_excess, normal_weights_from_excess};
 
-#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct ContCylindricalSystem{              // 연속 cubic 시스템
     pub sys_type : SystemType,          // System type
     pub bctype : BoundaryCond,          // Boundary condition : mixed. reflection for circular domain, periodic for rectangular domain
+                                        // reflection이면 rectangular domain의 양 끝 (cap)에서도 반사된다.
     pub sys_radius : f64,                   // radius of circular domain
     pub sys_length : f64,                   // length of rectangular domain (-length, length)
     pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
 }
 
 impl ContCylindricalSystem{
@@ -27,16 +31,25 @@ impl ContCylindricalSystem{
             sys_radius : sys_radius,
             sys_length : sys_length,
             dim : dim,
+            reflection : ReflectionType::Approximate,
         }
     }
+
+    pub fn new_closed(cyl_dim : usize, sys_radius : f64, sys_length : f64, dim : usize) -> Self{
+        // 양 끝이 막혀있는 cylinder. rectangular domain에서도 reflection
+        let mut sys = Self::new(cyl_dim, sys_radius, sys_length, dim);
+        sys.bctype = BoundaryCond::Reflection;
+        sys
+    }
 }
 
-impl_argument_trait!(ContCylindricalSystem, "System", ContCylindricalSystemArguments, 5;
+impl_argument_trait!(ContCylindricalSystem, "System", ContCylindricalSystemArguments, 6;
     sys_type, SystemType, "Dimension of circular domain.",
     bctype, BoundaryCond, "Boundary condition. ex) Reflective, Periodic, integer indicates Mixed",
     sys_radius, f64, "Radius of Circular Domain",
     sys_length, f64, "Length of Rectangular Domain (-length, length)",
-    dim, usize, "Dimension of System");
+    dim, usize, "Dimension of System",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
 
 impl ContCylindricalSystem{
     #[allow(dead_code)]
@@ -49,6 +62,8 @@ impl ContCylindricalSystem{
         };
         let cyl_dim2 = match argument.bctype{
             BoundaryCond::Mixed(d) => d,
+            BoundaryCond::Reflection => cyl_dim1,
+            // 흡수 경계는 지원하지 않는다.
             _ => {
                 panic!("{:?}", ErrorCode::InvalidArgumentInput);
             },
@@ -60,10 +75,11 @@ impl ContCylindricalSystem{
 
         Self{
             sys_type    : argument.sys_type,
-            bctype      : argument.bctype,
+            bctype      : argument.bctype.clone(),
             sys_radius      : argument.sys_radius,
             sys_length      : argument.sys_length,
             dim         : argument.dim,
+            reflection  : argument.reflection,
         }
     }
 }
@@ -112,13 +128,19 @@ impl SystemCore<f64> for ContCylindricalSystem{
         // pos : initial position of ptl
         // dp : displacement of ptl
 
+        if self.reflection == ReflectionType::Exact{
+            return self.check_bc_exact(pos, dp, None);
+        }
+
         pos.mut_add(dp);                           // add dp to pos
         if self.check_inclusion(pos)?{              // if ptl is still in the system after movement
             return Ok(());                          // return
         }
 
+        let closed : bool = self.bctype == BoundaryCond::Reflection;
         match self.bctype{
-            BoundaryCond::Mixed(d) =>{
+            BoundaryCond::Mixed(_) | BoundaryCond::Reflection =>{
+                let d : usize = self.cyl_dim()?;
 
                 // Reflection
                 let sys_radius = self.sys_radius;
@@ -135,7 +157,7 @@ impl SystemCore<f64> for ContCylindricalSystem{
                 }
 
 
-                // Periodic
+                // Periodic (closed이면 reflection)
                 let sys_length : f64 = self.sys_length;
                 for i in d..self.dim{
                     let x = &mut pos.coordinate[i];
@@ -143,7 +165,10 @@ impl SystemCore<f64> for ContCylindricalSystem{
                         continue;
                     }
 
-                    if *x < 0f64{
+                    if closed{
+                        *x = if *x < 0f64 { - 2f64 * sys_length - *x } else { 2f64 * sys_length - *x };
+                    }
+                    else if *x < 0f64{
                         *x = 2f64 * sys_length + *x;
                     }
                     else{
@@ -164,83 +189,80 @@ impl SystemCore<f64> for ContCylindricalSystem{
 
 
 
-    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
-        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
-        // rng : random number generator
-
-        use crate::random_mod::get_uniform_vec;
-
-        let d = match self.sys_type{
-            SystemType::ContinuousCylindrical(dim) => dim,
-            _ => {
-                return Err(Error::make_error_syntax(ErrorCode::InvalidType));
-            },
-        };
-        let sys_radius : f64 = self.sys_radius;
-        let sys_length : f64 = self.sys_length;
-        let dim : usize = self.dim;
-        let pos0 : Position<f64> = Position::<f64>::new(vec![-0.5f64; self.dim]);
-        let mut pos1;
-        loop{
-            pos1 = &get_uniform_vec(rng, dim) + &pos0;          // 일단 (0, 1)^dim 에서 뽑은 후에, (-0.5, 0.5)^dim이 되도록
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 옮기고,
+        // 원형 domain의 경계에서 반사되면 반지름 방향 velocity 성분을 뒤집는다.
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        // vel : velocity of ptl
 
-                                                                // 평행이동
-            let mut r : f64 = 0f64;
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if self.reflection == ReflectionType::Exact{
+            return self.check_bc_exact(pos, dp, Some(vel));
+        }
+        let d : usize = self.cyl_dim()?;
+
+        let mut r : f64 = 0f64;
+        let mut vn : f64 = 0f64;
+        for i in 0..d{
+            let x : f64 = pos[i] + dp[i];
+            r += x * x;
+            vn += x * vel[i];
+        }
+        r = r.sqrt();
+        if r > self.sys_radius{
             for i in 0..d{
-                pos1[i] *= 2f64 * sys_radius;
-                r += pos1[i] * pos1[i];
-            }
-            for i in d..dim{
-                pos1[i] *= 2f64 * sys_length;
+                vel[i] -= 2f64 * vn * (pos[i] + dp[i]) / (r * r);
             }
-
-            r = r.sqrt();
-            if r < sys_radius{
-                break;
+        }
+        if self.bctype == BoundaryCond::Reflection{
+            for i in d..self.dim{
+                if (pos[i] + dp[i]).abs() > self.sys_length{
+                    vel[i] = - vel[i];
+                }
             }
         }
+        self.check_bc(pos, dp)
+    }
 
-        return Ok(pos1);
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
     }
 
     fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
         // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // 원형 domain은 ball에서 바로 뽑고, rectangular domain은 (-length, length)에서 uniform하게 뽑는다.
         // rng : random number generator
         // vec : 결과 적을 mutable reference
 
-        use crate::random_mod::get_uniform_to_vec_nonstandard;
+        use crate::random_mod::{get_ball_to_vec, get_uniform};
 
-        let d = match self.sys_type{
-            SystemType::ContinuousCylindrical(dim) => dim,
-            _ => {
-                return Err(Error::make_error_syntax(ErrorCode::InvalidType));
-            },
-        };
-        let sys_length : f64 = self.sys_length;
-        let sys_radius : f64 = self.sys_radius;
-        let dim : usize = self.dim;
-        if vec.dim() != dim{
+        let d : usize = self.cyl_dim()?;
+        if vec.dim() != self.dim{
             return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
         }
-        loop{
-            vec.clear();
-            get_uniform_to_vec_nonstandard(rng, vec, -1f64, 1f64);
-
-            let mut r : f64 = 0f64;
-            for i in 0..d{
-                vec[i] *= 2f64 * sys_radius;
-                r += vec[i] * vec[i];
-            }
-            for i in d..dim{
-                vec[i] *= 2f64 * sys_length;
-            }
+        get_ball_to_vec(rng, &mut vec.coordinate[..d], self.sys_radius);
 
-            r = r.sqrt();
-            if r < sys_radius{
-                break;
-            }
+        let sys_length : f64 = self.sys_length;
+        for x in &mut vec.coordinate[d..]{
+            *x = sys_length * (2f64 * get_uniform(rng) - 1f64);
         }
-
         return Ok(());
     }
 
@@ -266,6 +288,142 @@ impl SystemCore<f64> for ContCylindricalSystem{
         }
         Ok(())
     }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 원형 domain의 벽과, 닫혀있는 경우 cap까지 중 가까운 쪽. periodic한 방향은 경계가 아니다.
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok(signed_distance_from_excess(&self.boundary_excess(pos)?))
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let d : usize = self.cyl_dim()?;
+        let weights : Vec<f64> = normal_weights_from_excess(&self.boundary_excess(pos)?)?;
+        normal.clear();
+
+        // excess의 앞쪽은 원형 벽, 그 뒤로 cap들이 온다.
+        let mut caps = weights.iter();
+        if d > 0{
+            let w : f64 = *caps.next().unwrap();
+            let rho : f64 = pos[..d].iter().map(|x| x * x).sum::<f64>().sqrt();
+            if rho == 0f64{
+                normal[0] = w;
+            }
+            else{
+                for i in 0..d{
+                    normal[i] = w * pos[i] / rho;
+                }
+            }
+        }
+        for (i, w) in (d..self.dim).zip(caps){
+            normal[i] = if pos[i] < 0f64 { -w } else { *w };
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        // V_d r^d (2l)^(dim - d)
+        let d : usize = self.cyl_dim()?;
+        Ok(unit_ball_volume(d) * self.sys_radius.powi(d as i32)
+           * (2f64 * self.sys_length).powi((self.dim - d) as i32))
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 옆면 d V_d r^(d-1) (2l)^(dim - d)에, 닫혀있으면 cap 2(dim - d)개의 넓이 V_d r^d (2l)^(dim - d - 1)를 더함
+        let d : usize = self.cyl_dim()?;
+        let m : i32 = (self.dim - d) as i32;
+        let ball : f64 = unit_ball_volume(d);
+        let width : f64 = 2f64 * self.sys_length;
+        let mut area : f64 = d as f64 * ball * self.sys_radius.powi(d as i32 - 1) * width.powi(m);
+        if self.bctype == BoundaryCond::Reflection && m > 0{
+            area += 2f64 * m as f64 * ball * self.sys_radius.powi(d as i32) * width.powi(m - 1);
+        }
+        Ok(area)
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let d : usize = self.cyl_dim()?;
+        let mut lower : Position<f64> = Position::new(vec![-self.sys_length; self.dim]);
+        let mut upper : Position<f64> = Position::new(vec![self.sys_length; self.dim]);
+        for i in 0..d{
+            lower[i] = -self.sys_radius;
+            upper[i] = self.sys_radius;
+        }
+        Ok((lower, upper))
+    }
+
+    fn is_periodic(&self, axis: usize) -> bool{
+        // 양 끝이 열려있으면 rectangular domain은 periodic
+        match &self.bctype{
+            BoundaryCond::Mixed(d) => *d <= axis && axis < self.dim,
+            _ => false,
+        }
+    }
+}
+
+impl ContCylindricalSystem{
+    fn cyl_dim(&self) -> Result<usize, Error>{
+        // Dimension of circular domain
+        match self.sys_type{
+            SystemType::ContinuousCylindrical(d) => Ok(d),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidType)),
+        }
+    }
+
+    fn boundary_excess(&self, pos: &Position<f64>) -> Result<Vec<f64>, Error>{
+        // 각 경계 밖으로 나간 거리. 원형 벽이 먼저, 닫혀있으면 각 축의 cap이 뒤따른다.
+        let d : usize = self.cyl_dim()?;
+        let mut excess : Vec<f64> = Vec::with_capacity(self.dim - d + 1);
+        if d > 0{
+            let rho : f64 = pos[..d].iter().map(|x| x * x).sum::<f64>().sqrt();
+            excess.push(rho - self.sys_radius);
+        }
+        if self.bctype == BoundaryCond::Reflection{
+            for x in &pos[d..]{
+                excess.push(x.abs() - self.sys_length);
+            }
+        }
+        Ok(excess)
+    }
+
+    fn check_bc_exact(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        // 원형 domain의 벽의 법선에는 rectangular 성분이 없고, cap의 법선에는 원형 성분이 없다.
+        // 따라서 straight path의 specular reflection은 원형 성분과 rectangular 성분으로 나누어 정확히 계산된다.
+        // 벽과 cap의 모서리에 부딪히는 경우도 마찬가지.
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let d : usize = self.cyl_dim()?;
+        let closed : bool = match self.bctype{
+            BoundaryCond::Mixed(_) => false,
+            BoundaryCond::Reflection => true,
+            _ => return Err(Error::make_error_syntax(ErrorCode::InvalidType)),
+        };
+
+        let mut vel = vel;
+        reflect_ball_exact(&mut pos.coordinate[..d], &mut dp.coordinate[..d],
+                           vel.as_mut().map(|v| &mut v.coordinate[..d]), self.sys_radius)?;
+
+        let sys_length : f64 = self.sys_length;
+        for i in d..self.dim{
+            pos[i] += dp[i];
+            if closed{
+                if fold_reflect_exact(&mut pos[i], sys_length){
+                    if let Some(v) = vel.as_mut(){
+                        v[i] = - v[i];
+                    }
+                }
+            }
+            else{
+                fold_periodic_exact(&mut pos[i], sys_length);
+            }
+        }
+        Ok(())
+    }
 }
 
 
@@ -284,6 +442,21 @@ mod tests{
         assert_eq!(sys.dim, 2);
     }
 
+    #[test]
+    #[should_panic]
+    fn test_absorbing_not_supported(){
+        // 흡수 경계는 지원하지 않으므로 argument에서 거른다.
+        let argument = ContCylindricalSystemArguments{
+            sys_type    : SystemType::ContinuousCylindrical(1),
+            bctype      : BoundaryCond::Absorbing,
+            sys_radius  : 3.0,
+            sys_length  : 10.0,
+            dim         : 2,
+            reflection  : ReflectionType::Approximate,
+        };
+        ContCylindricalSystem::convert_from(&argument);
+    }
+
     #[test]
     fn test_inclusion(){
         // System 안에 있는지 여부를 잘 확인하는지 테스트
@@ -312,6 +485,19 @@ mod tests{
             assert_eq!(sys.check_inclusion(&pos), Ok(true));
         }
 
+        // 고차원 원형 domain. rectangular 성분은 (-length, length)에서 uniform하므로 x^2의 평균은 length^2 / 3
+        let sys : ContCylindricalSystem = ContCylindricalSystem::new(12, 1.0, 0.5, 14);
+        let mut pos : Position<f64> = Position::new(vec![0.0; 14]);
+        let (mut mean_rho, mut mean_x2) : (f64, f64) = (0f64, 0f64);
+        for _i in 0..10000{
+            sys.random_pos_to_vec(&mut rng, &mut pos)?;
+            assert_eq!(sys.check_inclusion(&pos), Ok(true));
+            mean_rho += pos[..12].iter().map(|x| x * x).sum::<f64>().powi(6) / 10000f64;
+            mean_x2 += pos[13] * pos[13] / 10000f64;
+        }
+        assert!((mean_rho - 0.5).abs() < 0.02, "{}", mean_rho);
+        assert!((mean_x2 - 0.25 / 3.0).abs() < 0.005, "{}", mean_x2);
+
         Ok(())
     }
 
@@ -343,4 +529,73 @@ mod tests{
         }
         return Ok(());
     }
+
+    #[test]
+    fn test_check_bc_with_velocity() -> Result<(), Error>{
+        // 원형 domain에서 반사될 때만 velocity가 뒤집힌다
+        let sys : ContCylindricalSystem = ContCylindricalSystem::new(1, 5.0, 5.0, 2);
+        let mut pos : Position<f64> = Position::new(vec![4.9, 4.9]);
+        let mut dp : Position<f64> = Position::new(vec![0.2, 0.2]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![4.9, -4.9])).norm() < 1e-10);
+        assert!((&vel - &Position::new(vec![-1.0, 1.0])).norm() < 1e-10);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_check_bc_reflection_exact() -> Result<(), Error>{
+        // 원형 벽과 cap에 동시에 부딪히는 긴 step
+        let mut sys : ContCylindricalSystem = ContCylindricalSystem::new_closed(2, 1.0, 1.0, 3);
+        assert_eq!(sys.bctype, BoundaryCond::Reflection);
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![2.5, 0.0, 3.0]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep)));
+
+        sys.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![2.5, 0.0, 3.0]);
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0, 1.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![-0.5, 0.0, -0.5])).norm() < 1e-12);
+        assert!((&vel - &Position::new(vec![-1.0, 1.0, 1.0])).norm() < 1e-12);
+
+        // 열린 cylinder에서는 periodic
+        let mut sys : ContCylindricalSystem = ContCylindricalSystem::new(1, 1.0, 1.0, 2);
+        sys.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![0.6, 4.2]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.9, 0.7])).norm() < 1e-12);
+        return Ok(());
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        use std::f64::consts::PI;
+        let mut normal : Position<f64> = Position::new(vec![0.0; 3]);
+
+        // 열린 cylinder는 옆면만 경계
+        let sys : ContCylindricalSystem = ContCylindricalSystem::new(2, 1.0, 2.0, 3);
+        let pos : Position<f64> = Position::new(vec![0.0, 0.6, 1.9]);
+        assert!((sys.signed_distance(&pos)? - 0.4).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 1.0, 0.0]));
+        assert!((sys.volume()? - 4.0 * PI).abs() < 1e-12);
+        assert!((sys.surface_area()? - 8.0 * PI).abs() < 1e-12);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-1.0, -1.0, -2.0]), Position::new(vec![1.0, 1.0, 2.0])));
+
+        // 닫힌 cylinder는 cap까지 포함
+        let sys : ContCylindricalSystem = ContCylindricalSystem::new_closed(2, 1.0, 2.0, 3);
+        assert!((sys.signed_distance(&pos)? - 0.1).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 0.0, 1.0]));
+        assert!((sys.surface_area()? - 10.0 * PI).abs() < 1e-12);
+        let pos : Position<f64> = Position::new(vec![0.0, 4.0, -6.0]);
+        assert!((sys.signed_distance(&pos)? + 5.0).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert!((&normal - &Position::new(vec![0.0, 0.6, -0.8])).norm() < 1e-12);
+        return Ok(());
+    }
 }
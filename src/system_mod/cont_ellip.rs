@@ -0,0 +1,427 @@
+// Modules for continous ellipsoidal system.
+// 원점이 중심이고 각 축의 반지름이 다른 타원 (타원체) 시스템
+
+use crate::prelude::*;
+use crate::system_mod::{reflect_surface_exact, unit_ball_volume, carlson_rg};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContEllipsoidSystem{         // 연속 ellipsoidal 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition : here, only reflective bc is available
+    pub sys_size : Position<f64>,       // semi-axes. sum (x_i / sys_size[i])^2 <= 1
+    pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
+}
+
+impl ContEllipsoidSystem{
+    pub fn new(sys_size : Position<f64>) -> Self{
+        // sys_size : semi-axes of ellipsoid
+        if sys_size.dim() == 0 || sys_size.iter().any(|&x| x <= 0f64){
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+
+        ContEllipsoidSystem{
+            sys_type : SystemType::ContinuousEllipsoidal,
+            bctype : BoundaryCond::Reflection,
+            dim : sys_size.dim(),
+            sys_size,
+            reflection : ReflectionType::Exact,       // scaled 좌표에서 반지름 방향으로 접는 것은 specular reflection이 아니다.
+        }
+    }
+
+    // 각 축을 반지름으로 나눈 좌표에서의 norm. 1 이하이면 시스템 안에 있다.
+    fn scaled_norm(&self, pos : &[f64]) -> f64{
+        pos.iter().zip(self.sys_size.iter()).map(|(x, a)| (x / a) * (x / a)).sum::<f64>().sqrt()
+    }
+
+    fn exit_time(&self, pos : &[f64], dp : &[f64]) -> Option<f64>{
+        // pos부터 pos + dp까지 움직일 때 경계에 닿는 t in (0, 1]. 끝점이 안에 있으면 None
+        let (mut a, mut b, mut c, mut end) : (f64, f64, f64, f64) = (0f64, 0f64, 0f64, 0f64);
+        for ((x, dx), r) in pos.iter().zip(dp.iter()).zip(self.sys_size.iter()){
+            let (y, dy) : (f64, f64) = (x / r, dx / r);
+            a += dy * dy;
+            b += y * dy;
+            c += y * y;
+            end += (y + dy) * (y + dy);
+        }
+        if end <= 1f64{
+            return None;
+        }
+        Some(((- b + (b * b - a * (c - 1f64)).max(0f64).sqrt()) / a).clamp(0f64, 1f64))
+    }
+
+    fn surface_normal(&self, pos : &mut [f64], normal : &mut [f64]){
+        // 경계 근처의 점을 경계로 옮기고, 그 점의 unit normal (x_i / a_i^2 방향)을 normal에 기입
+        let s : f64 = self.scaled_norm(pos);
+        let mut norm : f64 = 0f64;
+        for ((x, n), r) in pos.iter_mut().zip(normal.iter_mut()).zip(self.sys_size.iter()){
+            *x /= s;
+            *n = *x / (r * r);
+            norm += *n * *n;
+        }
+        let norm : f64 = norm.sqrt();
+        for n in normal.iter_mut(){
+            *n /= norm;
+        }
+    }
+
+    fn nearest_boundary_point(&self, pos : &[f64]) -> Vec<f64>{
+        // 경계 위에서 pos와 가장 가까운 점 y. y_i = a_i^2 x_i / (a_i^2 + t)이고, t > - a_min^2는
+        //      f(t) = sum (a_i x_i / (a_i^2 + t))^2 - 1 = 0
+        // 의 유일한 근이다. f는 감소함수이므로 bisection으로 구한다.
+        // 가장 짧은 축 방향의 성분이 모두 0이면 t = - a_min^2에서도 f < 0일 수 있고,
+        // 이때 y는 그 축 방향으로 나머지 길이만큼 뻗는다.
+        let a2 : Vec<f64> = self.sys_size.iter().map(|a| a * a).collect();
+        let mut k : usize = 0;
+        for (i, b) in a2.iter().enumerate(){
+            if *b < a2[k]{
+                k = i;
+            }
+        }
+        let f = |t : f64| -> f64 {
+            pos.iter().zip(self.sys_size.iter()).zip(a2.iter()).filter(|((x, _), _)| **x != 0f64)
+                .map(|((x, a), b)| (a * x / (b + t)).powi(2)).sum::<f64>() - 1f64
+        };
+
+        if a2.iter().zip(pos.iter()).all(|(b, x)| *b != a2[k] || *x == 0f64){
+            let rest : f64 = f(- a2[k]);
+            if rest <= 0f64{
+                let mut y : Vec<f64> = pos.iter().zip(a2.iter())
+                    .map(|(x, b)| if *b == a2[k] { 0f64 } else { b * x / (b - a2[k]) }).collect();
+                y[k] = self.sys_size[k] * (- rest).sqrt();
+                return y;
+            }
+        }
+
+        let (mut lower, mut upper) : (f64, f64) = (- a2[k], pos.iter().zip(self.sys_size.iter())
+                                                    .map(|(x, a)| (a * x).powi(2)).sum::<f64>().sqrt());
+        loop{
+            let t : f64 = 0.5 * (lower + upper);
+            if t <= lower || t >= upper{
+                break;
+            }
+            if f(t) > 0f64{
+                lower = t;
+            }
+            else{
+                upper = t;
+            }
+        }
+        pos.iter().zip(a2.iter()).map(|(x, b)| b * x / (b + upper)).collect()
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        if self.reflection == ReflectionType::Exact{
+            return reflect_surface_exact(&mut pos.coordinate, &mut dp.coordinate, vel.map(|v| &mut v.coordinate[..]),
+                |x, dx| self.exit_time(x, dx),
+                |x, normal| self.surface_normal(x, normal));
+        }
+
+        pos.mut_add(dp);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+
+        // 각 축을 반지름으로 나눈 좌표에서 원과 같이 반지름 방향으로 접어 넣는다.
+        let s : f64 = self.scaled_norm(&pos.coordinate);
+        if let Some(v) = vel{
+            let mut surface : Position<f64> = pos.clone();
+            let mut normal : Position<f64> = Position::new(vec![0f64; self.dim]);
+            self.surface_normal(&mut surface.coordinate, &mut normal.coordinate);
+            let vn : f64 = normal.inner_product(v)?;
+            for i in 0..self.dim{
+                v[i] -= 2f64 * vn * normal[i];
+            }
+        }
+        pos.mut_scalar_mul((2f64 - s) / s);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+        Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+    }
+}
+
+impl_argument_trait!(ContEllipsoidSystem, "System", ContEllipsoidSystemArguments, 2,
+    sys_type, SystemType, SystemType::ContinuousEllipsoidal,
+    bctype, BoundaryCond, BoundaryCond::Reflection;
+    sys_size, Position::<f64>, "Semi-axes of System. ex) 2:1 = x^2/4 + y^2 <= 1",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
+
+impl ContEllipsoidSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContEllipsoidSystemArguments) -> Self{
+        Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            reflection  : argument.reflection,
+            ..Self::new(argument.sys_size.clone())
+        }
+    }
+}
+
+impl ContEllipsoidSystemArguments{
+    // sys_size가 Position이므로 dataset (파일 이름)에는 아래의 scalar 값들을 사용한다.
+    pub fn max_semi_axis(&self) -> f64{
+        self.sys_size.iter().fold(0f64, |acc, &a| acc.max(a))
+    }
+
+    pub fn dim(&self) -> usize{
+        self.sys_size.dim()
+    }
+}
+
+
+impl SystemCore<f64> for ContEllipsoidSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is in the system
+        // pos : vector to check
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok(self.scaled_norm(&pos.coordinate) <= 1f64)
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_inner(pos, dp, None)
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 반사시키고, 경계의 법선 방향 velocity 성분을 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel))
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // 단위 ball에서 uniform하게 뽑은 후 각 축을 반지름만큼 늘린다. 선형 변환이므로 uniform하다.
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        use crate::random_mod::get_ball_to_vec;
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        get_ball_to_vec(rng, &mut vec.coordinate, 1f64);
+        for (x, a) in vec.iter_mut().zip(self.sys_size.iter()){
+            *x *= a;
+        }
+        Ok(())
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        let mut pos : Position<f64> = self.sys_size.clone();
+        pos.mut_scalar_mul(2f64);
+        pos
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for (x, a) in vec.iter_mut().zip(self.sys_size.iter()){
+            *x = 2f64 * a;
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 경계 위의 가장 가까운 점까지의 거리. 안쪽이 양수
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let y : Vec<f64> = self.nearest_boundary_point(&pos.coordinate);
+        let distance : f64 = pos.iter().zip(y.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f64>().sqrt();
+        Ok(if self.check_inclusion(pos)? { distance } else { - distance })
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut y : Vec<f64> = self.nearest_boundary_point(&pos.coordinate);
+        self.surface_normal(&mut y, &mut normal.coordinate);
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        Ok(unit_ball_volume(self.dim) * self.sys_size.iter().product::<f64>())
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 타원의 둘레는 8 R_G(0, a^2, b^2), 타원체의 겉넓이는 4 pi abc R_G(1/a^2, 1/b^2, 1/c^2)
+        // 4차원 이상은 모든 축이 같은 경우 (구)만 지원한다.
+        let a : &Position<f64> = &self.sys_size;
+        if a.iter().all(|&x| x == a[0]){
+            return Ok(self.dim as f64 * unit_ball_volume(self.dim) * a[0].powi(self.dim as i32 - 1));
+        }
+        match self.dim{
+            2 => Ok(8f64 * carlson_rg(0f64, a[0] * a[0], a[1] * a[1])),
+            3 => Ok(4f64 * std::f64::consts::PI * a[0] * a[1] * a[2]
+                    * carlson_rg((a[0] * a[0]).recip(), (a[1] * a[1]).recip(), (a[2] * a[2]).recip())),
+            _ => Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)),
+        }
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let mut lower : Position<f64> = self.sys_size.clone();
+        lower.mut_scalar_mul(-1f64);
+        Ok((lower, self.sys_size.clone()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_new(){
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![2.0, 1.0]));
+        assert_eq!(sys.sys_type, SystemType::ContinuousEllipsoidal);
+        assert_eq!(sys.bctype, BoundaryCond::Reflection);
+        assert_eq!(sys.dim, 2);
+        assert_eq!(sys.reflection(), ReflectionType::Exact);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid(){
+        ContEllipsoidSystem::new(Position::new(vec![2.0, 0.0]));
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![2.0, 1.0]));
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.9, 0.0]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.0, 1.1]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.5, 0.7]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let mut sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![2.0, 1.0]));
+
+        // 근사적인 방법은 scaled 좌표에서 반지름 방향으로 접는다.
+        sys.set_reflection(ReflectionType::Approximate)?;
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![0.0, 1.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.0, 0.5])).norm() < 1e-12);
+
+        // (0, 0)에서 (2, 1) 방향으로 움직이면 (sqrt(2), 1/sqrt(2))에서 법선 (1, 2)/sqrt(5)에 대해 반사된다.
+        sys.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![2.0, 1.0]);
+        let mut vel : Position<f64> = Position::new(vec![2.0, 1.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        let hit : Position<f64> = Position::new(vec![2f64.sqrt(), 0.5f64.sqrt()]);
+        let t : f64 = 1f64 - 1f64 / 2f64.sqrt();
+        let reflected : Position<f64> = Position::new(vec![2.0 - 1.6, 1.0 - 3.2]);
+        let mut answer : Position<f64> = reflected.clone();
+        answer.mut_scalar_mul(t);
+        answer.mut_add(&hit);
+        assert!((&pos - &answer).norm() < 1e-12, "{:?} {:?}", pos, answer);
+        assert!((&vel - &reflected).norm() < 1e-12);
+        assert!(sys.check_inclusion(&pos)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        // |x| < 1일 확률은 2D에서 (2/pi) (asin(1/2) + sqrt(3)/4)
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![2.0, 1.0]));
+        let n : usize = 20000;
+        let mut count : usize = 0;
+        for _i in 0..n{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+            count += (pos[0].abs() < 1f64) as usize;
+        }
+        let answer : f64 = 2f64 / PI * (0.5f64.asin() + 3f64.sqrt() / 4f64);
+        assert!((count as f64 / n as f64 - answer).abs() < 0.02);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![2.0, 1.0]));
+        let mut normal : Position<f64> = Position::new(vec![0.0; 2]);
+
+        // 짧은 축 위의 점과 중심은 짧은 축 끝이 가장 가깝다.
+        assert!((sys.signed_distance(&Position::new(vec![0.0, 0.5]))? - 0.5).abs() < 1e-12);
+        assert!((sys.signed_distance(&Position::new(vec![0.0, 0.0]))? - 1.0).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![0.0, 0.0]), &mut normal)?;
+        assert!((&normal - &Position::new(vec![0.0, 1.0])).norm() < 1e-12);
+
+        // 긴 축 위의 점은 x > a - b^2 / a 이면 긴 축 끝이, 아니면 (4/3, sqrt(5)/3)이 가장 가깝다.
+        assert!((sys.signed_distance(&Position::new(vec![1.9, 0.0]))? - 0.1).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![1.9, 0.0]), &mut normal)?;
+        assert!((&normal - &Position::new(vec![1.0, 0.0])).norm() < 1e-12);
+        assert!((sys.signed_distance(&Position::new(vec![1.0, 0.0]))? - (2f64 / 3f64).sqrt()).abs() < 1e-12);
+        assert!((sys.signed_distance(&Position::new(vec![3.0, 0.0]))? + 1.0).abs() < 1e-12);
+
+        // 경계를 촘촘히 나눈 점들과의 최소 거리와 비교
+        use crate::random_mod::get_uniform;
+        let mut rng : Pcg64 = rng_seed(12341234);
+        for _i in 0..20{
+            let pos : Position<f64> = Position::new(vec![3.0 * (2.0 * get_uniform(&mut rng) - 1.0),
+                                                         2.0 * (2.0 * get_uniform(&mut rng) - 1.0)]);
+            let n : usize = 100000;
+            let answer : f64 = (0..n).map(|j| {
+                let theta : f64 = 2f64 * PI * j as f64 / n as f64;
+                (2.0 * theta.cos() - pos[0]).hypot(theta.sin() - pos[1])
+            }).fold(f64::INFINITY, f64::min);
+            let distance : f64 = sys.signed_distance(&pos)?;
+            assert!((distance.abs() - answer).abs() < 1e-6, "{} {} {}", pos, distance, answer);
+            assert_eq!(distance > 0f64, sys.check_inclusion(&pos)?);
+        }
+
+        assert!((sys.volume()? - 2.0 * PI).abs() < 1e-12);
+        assert!((sys.surface_area()? - 9.688448220547675).abs() < 1e-10);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-2.0, -1.0]), Position::new(vec![2.0, 1.0])));
+
+        // prolate spheroid의 겉넓이는 2 pi a^2 (1 + c asin(e) / (a e))
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![1.0, 2.0, 1.0]));
+        let e : f64 = 0.75f64.sqrt();
+        assert!((sys.surface_area()? - 2.0 * PI * (1.0 + 2.0 * e.asin() / e)).abs() < 1e-10);
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![1.0, 1.0, 1.0, 1.0]));
+        assert!((sys.surface_area()? - 2.0 * PI * PI).abs() < 1e-12);
+        let sys : ContEllipsoidSystem = ContEllipsoidSystem::new(Position::new(vec![1.0, 2.0, 1.0, 1.0]));
+        assert_eq!(sys.surface_area(), Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+        Ok(())
+    }
+}
@@ -0,0 +1,647 @@
+// Modules for continous mesh system.
+// 닫힌 triangle mesh (OBJ, ASCII STL)로 정의되는 3D 시스템. segmentation으로 얻은 세포 모양 등을 그대로 쓸 수 있다.
+// 모든 면이 바깥을 향하도록 정렬해 두고, 반사할 때는 바깥으로 나가는 방향의 면만 확인한다.
+
+use crate::prelude::*;
+use std::cell::Cell;
+use crate::system_mod::{reflect_surface_exact, random_pos_by_rejection_to_vec};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContMeshSystem{             // 연속 mesh 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition : here, only reflective bc is available
+    pub mesh_file : String,             // mesh를 읽은 파일. 직접 만든 경우 빈 문자열
+    pub vertices : Vec<[f64; 3]>,       // 꼭짓점 목록
+    pub faces : Vec<[usize; 3]>,        // 삼각형 면. 바깥에서 볼 때 반시계 방향으로 정렬되어 있다.
+    pub dim : usize,                    // dimension of system : always 3
+    pub reflection : ReflectionType,    // Accuracy of reflection
+    volume : f64,                       // 부피
+    bbox : ([f64; 3], [f64; 3]),        // bounding box
+}
+
+fn sub(a : &[f64], b : &[f64]) -> [f64; 3]{
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a : &[f64], b : &[f64]) -> f64{
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a : &[f64], b : &[f64]) -> [f64; 3]{
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn ray_triangle(pos : &[f64], dir : &[f64], a : &[f64; 3], b : &[f64; 3], c : &[f64; 3]) -> Option<f64>{
+    // Moller-Trumbore : pos + t * dir가 삼각형 abc와 만나는 t
+    let (e1, e2) : ([f64; 3], [f64; 3]) = (sub(b, a), sub(c, a));
+    let h : [f64; 3] = cross(dir, &e2);
+    let det : f64 = dot(&e1, &h);
+    if det == 0f64{
+        return None;
+    }
+    let s : [f64; 3] = sub(pos, a);
+    let u : f64 = dot(&s, &h) / det;
+    if !(0f64..=1f64).contains(&u){
+        return None;
+    }
+    let q : [f64; 3] = cross(&s, &e1);
+    let v : f64 = dot(dir, &q) / det;
+    if v < 0f64 || u + v > 1f64{
+        return None;
+    }
+    Some(dot(&e2, &q) / det)
+}
+
+fn closest_point_triangle(p : &[f64], a : &[f64; 3], b : &[f64; 3], c : &[f64; 3]) -> [f64; 3]{
+    // 삼각형 abc 위에서 p와 가장 가까운 점 (Ericson, Real-Time Collision Detection 5.1.5)
+    let point = |u : f64, v : f64| -> [f64; 3]{
+        let mut x : [f64; 3] = *a;
+        for i in 0..3{
+            x[i] += u * (b[i] - a[i]) + v * (c[i] - a[i]);
+        }
+        x
+    };
+    let (ab, ac, ap) : ([f64; 3], [f64; 3], [f64; 3]) = (sub(b, a), sub(c, a), sub(p, a));
+    let (d1, d2) : (f64, f64) = (dot(&ab, &ap), dot(&ac, &ap));
+    if d1 <= 0f64 && d2 <= 0f64{
+        return *a;
+    }
+    let bp : [f64; 3] = sub(p, b);
+    let (d3, d4) : (f64, f64) = (dot(&ab, &bp), dot(&ac, &bp));
+    if d3 >= 0f64 && d4 <= d3{
+        return *b;
+    }
+    let vc : f64 = d1 * d4 - d3 * d2;
+    if vc <= 0f64 && d1 >= 0f64 && d3 <= 0f64{
+        return point(d1 / (d1 - d3), 0f64);
+    }
+    let cp : [f64; 3] = sub(p, c);
+    let (d5, d6) : (f64, f64) = (dot(&ab, &cp), dot(&ac, &cp));
+    if d6 >= 0f64 && d5 <= d6{
+        return *c;
+    }
+    let vb : f64 = d5 * d2 - d1 * d6;
+    if vb <= 0f64 && d2 >= 0f64 && d6 <= 0f64{
+        return point(0f64, d2 / (d2 - d6));
+    }
+    let va : f64 = d3 * d6 - d5 * d4;
+    if va <= 0f64 && (d4 - d3) >= 0f64 && (d5 - d6) >= 0f64{
+        let w : f64 = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return point(1f64 - w, w);
+    }
+    let denom : f64 = va + vb + vc;
+    point(vb / denom, vc / denom)
+}
+
+fn parse_coord(words : &[&str]) -> Result<[f64; 3], Error>{
+    if words.len() < 3{
+        return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+    }
+    let mut x : [f64; 3] = [0f64; 3];
+    for i in 0..3{
+        x[i] = words[i].parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidFormat))?;
+    }
+    Ok(x)
+}
+
+impl ContMeshSystem{
+    pub fn new(vertices : Vec<[f64; 3]>, faces : Vec<[usize; 3]>) -> Result<Self, Error>{
+        // vertices : 꼭짓점 목록
+        // faces : 삼각형 면. 닫혀 있고 방향이 일관되어야 하며, 모두 안쪽을 향하면 뒤집는다.
+        if faces.len() < 4 || faces.iter().any(|f| f.iter().any(|&i| i >= vertices.len()))
+            || vertices.iter().any(|v| v.iter().any(|x| !x.is_finite())){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        // 닫힌 mesh에서 모든 방향 있는 변은 한번씩, 반대 방향으로 한번씩 나타난다.
+        let mut edges : HashMap<(usize, usize), usize> = HashMap::new();
+        for f in &faces{
+            for k in 0..3{
+                *edges.entry((f[k], f[(k + 1) % 3])).or_insert(0) += 1;
+            }
+        }
+        if edges.iter().any(|(&(a, b), &n)| n != 1 || edges.get(&(b, a)) != Some(&1)){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+        }
+
+        let mut faces : Vec<[usize; 3]> = faces;
+        let mut volume : f64 = faces.iter().map(|f| {
+            dot(&vertices[f[0]], &cross(&vertices[f[1]], &vertices[f[2]]))
+        }).sum::<f64>() / 6f64;
+        if volume == 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+        }
+        else if volume < 0f64{
+            for f in faces.iter_mut(){
+                f.swap(1, 2);
+            }
+            volume = - volume;
+        }
+
+        let mut bbox : ([f64; 3], [f64; 3]) = (vertices[faces[0][0]], vertices[faces[0][0]]);
+        for v in &vertices{
+            for (i, x) in v.iter().enumerate(){
+                bbox.0[i] = bbox.0[i].min(*x);
+                bbox.1[i] = bbox.1[i].max(*x);
+            }
+        }
+
+        Ok(ContMeshSystem{
+            sys_type : SystemType::ContinuousMesh,
+            bctype : BoundaryCond::Reflection,
+            mesh_file : String::new(),
+            vertices,
+            faces,
+            dim : 3,
+            reflection : ReflectionType::Approximate,
+            volume,
+            bbox,
+        })
+    }
+
+    pub fn from_file<P>(path : P) -> Result<Self, Error>
+        where P : AsRef<Path>{
+        // Wavefront OBJ, 혹은 ASCII STL 파일을 읽는다. 'solid'로 시작하면 STL로 본다.
+        // OBJ : 'v x y z', 'f i j k ...' 만 읽고, 사각형 이상의 면은 부채꼴로 나눈다. index는 1부터 시작하고 음수는 뒤에서부터 센다.
+        // STL : 'vertex x y z' 세 줄이 하나의 면이고, 같은 좌표의 꼭짓점은 하나로 합친다.
+        let f = File::open(path.as_ref()).map_err(Error::make_error_io)?;
+        let mut lines : Vec<String> = Vec::new();
+        for line in BufReader::new(f).lines(){
+            lines.push(line.map_err(Error::make_error_io)?);
+        }
+        let is_stl : bool = lines.iter().map(|x| x.trim()).find(|x| !x.is_empty())
+                                 .map_or(false, |x| x.starts_with("solid"));
+
+        let mut vertices : Vec<[f64; 3]> = Vec::new();
+        let mut faces : Vec<[usize; 3]> = Vec::new();
+        if is_stl{
+            let mut index : HashMap<[u64; 3], usize> = HashMap::new();
+            let mut facet : Vec<usize> = Vec::with_capacity(3);
+            for line in &lines{
+                let words : Vec<&str> = line.split_whitespace().collect();
+                if words.first() != Some(&"vertex"){
+                    continue;
+                }
+                let x : [f64; 3] = parse_coord(&words[1..])?;
+                let key : [u64; 3] = [x[0].to_bits(), x[1].to_bits(), x[2].to_bits()];
+                let n : usize = vertices.len();
+                let idx : usize = *index.entry(key).or_insert(n);
+                if idx == n{
+                    vertices.push(x);
+                }
+                facet.push(idx);
+                if facet.len() == 3{
+                    faces.push([facet[0], facet[1], facet[2]]);
+                    facet.clear();
+                }
+            }
+            if !facet.is_empty(){
+                return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+            }
+        }
+        else{
+            for line in &lines{
+                let words : Vec<&str> = line.split_whitespace().collect();
+                match words.first(){
+                    Some(&"v") => vertices.push(parse_coord(&words[1..])?),
+                    Some(&"f") => {
+                        let n : usize = vertices.len();
+                        let polygon : Vec<usize> = words[1..].iter().map(|w| {
+                            let i : i64 = w.split('/').next().unwrap().parse::<i64>()
+                                           .map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidFormat))?;
+                            match i{
+                                i if i > 0 => Ok(i as usize - 1),
+                                i if i < 0 && (- i) as usize <= n => Ok(n - (- i) as usize),
+                                _ => Err(Error::make_error_syntax(ErrorCode::InvalidFormat)),
+                            }
+                        }).collect::<Result<Vec<usize>, Error>>()?;
+                        if polygon.len() < 3{
+                            return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+                        }
+                        for k in 1..polygon.len() - 1{
+                            faces.push([polygon[0], polygon[k], polygon[k + 1]]);
+                        }
+                    },
+                    _ => continue,
+                }
+            }
+        }
+
+        let mut sys : Self = Self::new(vertices, faces)?;
+        sys.mesh_file = path.as_ref().display().to_string();
+        Ok(sys)
+    }
+
+    fn face(&self, i : usize) -> (&[f64; 3], &[f64; 3], &[f64; 3]){
+        let f : &[usize; 3] = &self.faces[i];
+        (&self.vertices[f[0]], &self.vertices[f[1]], &self.vertices[f[2]])
+    }
+
+    fn outward_normal(&self, i : usize) -> [f64; 3]{
+        let (a, b, c) = self.face(i);
+        let n : [f64; 3] = cross(&sub(b, a), &sub(c, a));
+        let norm : f64 = dot(&n, &n).sqrt();
+        [n[0] / norm, n[1] / norm, n[2] / norm]
+    }
+
+    fn nearest_face(&self, pos : &[f64]) -> (usize, [f64; 3], f64){
+        // pos에서 가장 가까운 면의 index, 그 면 위의 가장 가까운 점, 거리의 제곱
+        let mut result : (usize, [f64; 3], f64) = (0, [0f64; 3], f64::INFINITY);
+        for i in 0..self.faces.len(){
+            let (a, b, c) = self.face(i);
+            let proj : [f64; 3] = closest_point_triangle(pos, a, b, c);
+            let d : [f64; 3] = sub(pos, &proj);
+            let d2 : f64 = dot(&d, &d);
+            if d2 < result.2{
+                result = (i, proj, d2);
+            }
+        }
+        result
+    }
+
+    fn exit_time(&self, pos : &[f64], dp : &[f64], exclude : Option<usize>) -> Option<(usize, f64)>{
+        // pos부터 pos + dp까지 움직일 때 바깥으로 나가는 방향으로 처음 닿는 면의 index와 t in [0, 1]. 닿지 않으면 None
+        // exclude : 직전에 반사된 면. 반올림 오차로 같은 면에 t = 0으로 다시 닿지 않도록 제외한다.
+        let mut result : Option<(usize, f64)> = None;
+        for i in 0..self.faces.len(){
+            if exclude == Some(i){
+                continue;
+            }
+            let (a, b, c) = self.face(i);
+            if dot(&cross(&sub(b, a), &sub(c, a)), dp) <= 0f64{
+                continue;
+            }
+            if let Some(t) = ray_triangle(pos, dp, a, b, c){
+                if !(-1e-12..=1f64).contains(&t){
+                    continue;
+                }
+                let t : f64 = t.max(0f64);
+                if result.map_or(true, |(_j, x)| t < x){
+                    result = Some((i, t));
+                }
+            }
+        }
+        result
+    }
+
+    fn surface_normal(&self, i : usize, pos : &mut [f64], normal : &mut [f64]){
+        // 경계 위의 점을 i번째 면 위로 옮기고, 그 면의 unit normal을 normal에 기입
+        let (a, b, c) = self.face(i);
+        let proj : [f64; 3] = closest_point_triangle(pos, a, b, c);
+        let n : [f64; 3] = self.outward_normal(i);
+        pos[..3].copy_from_slice(&proj);
+        normal[..3].copy_from_slice(&n);
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        if self.reflection == ReflectionType::Exact{
+            // 마지막으로 닿은 면
+            let last : Cell<Option<usize>> = Cell::new(None);
+            return reflect_surface_exact(&mut pos.coordinate, &mut dp.coordinate, vel.map(|v| &mut v.coordinate[..]),
+                |x, dx| {
+                    let hit = self.exit_time(x, dx, last.get());
+                    last.set(hit.map(|(i, _t)| i));
+                    hit.map(|(_i, t)| t)
+                },
+                |x, normal| {
+                    let i : usize = last.get().unwrap_or_else(|| self.nearest_face(x).0);
+                    self.surface_normal(i, x, normal);
+                });
+        }
+
+        pos.mut_add(dp);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+
+        // 가장 가까운 면의 평면에 대해 접어 넣는다.
+        let (i, proj, _) = self.nearest_face(&pos.coordinate);
+        let n : [f64; 3] = self.outward_normal(i);
+        let d : f64 = dot(&sub(&pos.coordinate, &proj), &n);
+        for k in 0..3{
+            pos[k] -= 2f64 * d * n[k];
+        }
+        if let Some(v) = vel{
+            let vn : f64 = dot(&v.coordinate, &n);
+            for k in 0..3{
+                v[k] -= 2f64 * vn * n[k];
+            }
+        }
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+        Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+    }
+}
+
+impl_argument_trait!(ContMeshSystem, "System", ContMeshSystemArguments, 2,
+    sys_type, SystemType, SystemType::ContinuousMesh,
+    bctype, BoundaryCond, BoundaryCond::Reflection;
+    mesh_file, String, "Closed Triangle Mesh File. OBJ or ASCII STL",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
+
+impl ContMeshSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContMeshSystemArguments) -> Result<Self, Error>{
+        // 파일을 읽지 못하면 panic 대신 error를 돌려준다.
+        let sys : Self = Self::from_file(&argument.mesh_file)?;
+        Ok(Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            reflection  : argument.reflection,
+            ..sys
+        })
+    }
+}
+
+impl ContMeshSystemArguments{
+    // 파일 이름 대신 dataset (파일 이름)에는 mesh의 부피 (SystemCore::volume)를 사용한다.
+    pub fn dim(&self) -> usize{
+        3
+    }
+}
+
+
+impl SystemCore<f64> for ContMeshSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is in the system
+        // 임의의 방향으로 쏜 반직선이 면과 만나는 횟수가 홀수이면 내부. 경계 위의 점은 내부로 본다.
+        // pos : vector to check
+
+        // 변이나 꼭짓점을 지나지 않도록 좌표축과 어긋난 방향을 쓴다.
+        const RAY : [f64; 3] = [0.5773502691896258, 0.6324555320336759, 0.5163977794943222];
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if (0..3).any(|i| pos[i] < self.bbox.0[i] || pos[i] > self.bbox.1[i]){
+            return Ok(false);
+        }
+
+        let mut inside : bool = false;
+        for i in 0..self.faces.len(){
+            let (a, b, c) = self.face(i);
+            if let Some(t) = ray_triangle(&pos.coordinate, &RAY, a, b, c){
+                if t > 0f64{
+                    inside = !inside;
+                }
+            }
+        }
+        if inside{
+            return Ok(true);
+        }
+        let scale : f64 = (0..3).map(|i| self.bbox.1[i] - self.bbox.0[i]).fold(0f64, f64::max);
+        Ok(self.nearest_face(&pos.coordinate).2 <= (1e-12 * scale).powi(2))
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_inner(pos, dp, None)
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 반사시키고, 면의 법선 방향 velocity 성분을 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel))
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // bounding box에서 uniform하게 뽑고 mesh 안에 있는지 확인한다.
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        random_pos_by_rejection_to_vec(self, rng, vec)
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.position_out_of_system_to_vec(&mut pos).unwrap();
+        pos
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for i in 0..3{
+            vec[i] = 2f64 * self.bbox.1[i] - self.bbox.0[i] + 1f64;
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 가장 가까운 면까지의 거리. 안쪽이 양수
+        let d : f64 = self.nearest_face(&pos.coordinate).2.sqrt();
+        if self.check_inclusion(pos)?{
+            return Ok(d);
+        }
+        Ok(-d)
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        // 가장 가까운 면의 바깥 방향 normal
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let n : [f64; 3] = self.outward_normal(self.nearest_face(&pos.coordinate).0);
+        for k in 0..3{
+            normal[k] = n[k];
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        // mesh로 둘러싸인 부피
+        Ok(self.volume)
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        Ok((0..self.faces.len()).map(|i| {
+            let (a, b, c) = self.face(i);
+            let n : [f64; 3] = cross(&sub(b, a), &sub(c, a));
+            0.5 * dot(&n, &n).sqrt()
+        }).sum())
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        // 꼭짓점을 모두 포함하는 가장 작은 직육면체
+        Ok((Position::new(self.bbox.0.to_vec()), Position::new(self.bbox.1.to_vec())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const CUBE_OBJ : &str = "# unit cube\n\
+        v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+        f 1 4 3 2\nf 5/1 6/2 7/3 8/4\nf 1//1 2//1 6//1 5//1\nf 4 8 7 3\nf 1 5 8 4\nf -7 -6 -2 -3\n";
+
+    const TETRA_STL : &str = "solid tetra\n\
+        facet normal 0 0 -1\n outer loop\n  vertex 0 0 0\n  vertex 0 1 0\n  vertex 1 0 0\n endloop\nendfacet\n\
+        facet normal 0 -1 0\n outer loop\n  vertex 0 0 0\n  vertex 1 0 0\n  vertex 0 0 1\n endloop\nendfacet\n\
+        facet normal -1 0 0\n outer loop\n  vertex 0 0 0\n  vertex 0 0 1\n  vertex 0 1 0\n endloop\nendfacet\n\
+        facet normal 1 1 1\n outer loop\n  vertex 1 0 0\n  vertex 0 1 0\n  vertex 0 0 1\n endloop\nendfacet\n\
+        endsolid tetra\n";
+
+    fn from_text(text : &str, name : &str) -> Result<ContMeshSystem, Error>{
+        let path = std::env::temp_dir().join(name);
+        {
+            let mut writer = BufWriter::new(File::create(&path).map_err(Error::make_error_io)?);
+            write!(&mut writer, "{}", text).map_err(Error::make_error_io)?;
+        }
+        let sys = ContMeshSystem::from_file(&path);
+        fs::remove_file(&path).map_err(Error::make_error_io)?;
+        sys
+    }
+
+    #[test]
+    fn test_new() -> Result<(), Error>{
+        let sys : ContMeshSystem = from_text(CUBE_OBJ, "moledyn_test_mesh_new.obj")?;
+        assert_eq!(sys.sys_type, SystemType::ContinuousMesh);
+        assert_eq!(sys.bctype, BoundaryCond::Reflection);
+        assert_eq!(sys.dim, 3);
+        assert_eq!(sys.faces.len(), 12);
+        assert!((sys.volume()? - 1.0).abs() < 1e-12);
+
+        // 모든 면이 안쪽을 향하면 뒤집는다.
+        let inverted : Vec<[usize; 3]> = sys.faces.iter().map(|f| [f[0], f[2], f[1]]).collect();
+        let sys2 : ContMeshSystem = ContMeshSystem::new(sys.vertices.clone(), inverted)?;
+        assert_eq!(sys2.faces, sys.faces);
+
+        // 열린 mesh, 방향이 일관되지 않은 mesh
+        let mut faces : Vec<[usize; 3]> = sys.faces.clone();
+        faces.pop();
+        assert_eq!(ContMeshSystem::new(sys.vertices.clone(), faces.clone()).map(|_| ()),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+        faces.push([sys.faces[11][0], sys.faces[11][2], sys.faces[11][1]]);
+        assert!(ContMeshSystem::new(sys.vertices.clone(), faces).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContMeshSystem = from_text(CUBE_OBJ, "moledyn_test_mesh_inclusion.obj")?;
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5, 0.5, 0.5]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.1, 0.9, 0.2]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.0, 0.5, 0.5]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.5, 0.5, 0.5]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5, -0.1, 0.5]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+
+        let tetra : ContMeshSystem = from_text(TETRA_STL, "moledyn_test_mesh_inclusion.stl")?;
+        assert_eq!(tetra.vertices.len(), 4);
+        assert!((tetra.volume()? - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(tetra.check_inclusion(&Position::new(vec![0.1, 0.2, 0.3]))?, true);
+        assert_eq!(tetra.check_inclusion(&Position::new(vec![0.4, 0.4, 0.4]))?, false);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let mut sys : ContMeshSystem = from_text(CUBE_OBJ, "moledyn_test_mesh_bc.obj")?;
+
+        for &rtype in [ReflectionType::Approximate, ReflectionType::Exact].iter(){
+            sys.set_reflection(rtype)?;
+            let mut pos : Position<f64> = Position::new(vec![0.5, 0.5, 0.5]);
+            let mut dp : Position<f64> = Position::new(vec![0.7, 0.0, 0.0]);
+            let mut vel : Position<f64> = Position::new(vec![1.0, 1.0, 0.0]);
+            sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+            assert!((&pos - &Position::new(vec![0.8, 0.5, 0.5])).norm() < 1e-12, "{:?}", pos);
+            assert!((&vel - &Position::new(vec![-1.0, 1.0, 0.0])).norm() < 1e-12);
+        }
+
+        // 세 면에서 연속으로 반사
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![0.7, 0.9, -0.75]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.8, 0.6, 0.25])).norm() < 1e-12, "{:?}", pos);
+
+        // 꼭짓점에서 반사된 직후 같은 면에 다시 닿지 않는다
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![1.0, 1.0, 1.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.5, 0.5, 0.5])).norm() < 1e-12, "{:?}", pos);
+
+        // 기울어진 면에서 반사
+        let mut tetra : ContMeshSystem = from_text(TETRA_STL, "moledyn_test_mesh_bc.stl")?;
+        tetra.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![0.1, 0.1, 0.1]);
+        let mut dp : Position<f64> = Position::new(vec![0.3, 0.3, 0.3]);
+        tetra.check_bc(&mut pos, &mut dp)?;
+        let x : f64 = 2f64 / 3f64 - 0.4;
+        assert!((&pos - &Position::new(vec![x, x, x])).norm() < 1e-12, "{:?}", pos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let tetra : ContMeshSystem = from_text(TETRA_STL, "moledyn_test_mesh_random.stl")?;
+        // 정사면체 x + y + z <= 1 에서 x < 1/2 일 확률은 1 - 1/8
+        let n : usize = 20000;
+        let mut count : usize = 0;
+        for _i in 0..n{
+            let pos : Position<f64> = tetra.random_pos(&mut rng)?;
+            assert!(tetra.check_inclusion(&pos)?);
+            count += (pos[0] < 0.5) as usize;
+        }
+        assert!((count as f64 / n as f64 - 7f64 / 8f64).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file() -> Result<(), Error>{
+        assert_eq!(from_text("v 0 0 0\nf 1 2 3\n", "moledyn_test_mesh_invalid.obj").map(|_| ()),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        assert_eq!(from_text("v 0 0\n", "moledyn_test_mesh_invalid2.obj").map(|_| ()),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidFormat)));
+
+        let path = std::env::temp_dir().join("moledyn_test_mesh_args.obj");
+        {
+            let mut writer = BufWriter::new(File::create(&path).map_err(Error::make_error_io)?);
+            write!(&mut writer, "{}", CUBE_OBJ).map_err(Error::make_error_io)?;
+        }
+        let args : Vec<String> = vec![path.display().to_string(), "Exact".to_string()];
+        let sys : ContMeshSystem = ContMeshSystem::convert_from(&ContMeshSystem::read_args_from_vec(&args)?)?;
+        fs::remove_file(&path).map_err(Error::make_error_io)?;
+        assert_eq!(sys.mesh_file, path.display().to_string());
+        assert_eq!(sys.reflection(), ReflectionType::Exact);
+        assert!((sys.volume()? - 1.0).abs() < 1e-12);
+        assert!(ContMeshSystem::convert_from(&ContMeshSystem::read_args_from_vec(&args)?).is_err());
+        Ok(())
+    }
+}
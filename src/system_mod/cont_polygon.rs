@@ -0,0 +1,542 @@
+// Modules for continous polygonal system.
+// 현미경 segmentation 등으로 얻은 꼭짓점 목록으로 정의되는 2D 다각형 (convex가 아니어도 된다) 시스템.
+// 내부를 삼각형으로 분할해 두고, random_pos는 넓이에 비례해 삼각형을 고른 뒤 그 안에서 uniform하게 뽑는다.
+
+use crate::prelude::*;
+use std::cell::Cell;
+use crate::system_mod::{reflect_surface_exact};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContPolygonSystem{          // 연속 polygon 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition : here, only reflective bc is available
+    pub vertex_file : String,           // 꼭짓점을 읽은 파일. 직접 만든 경우 빈 문자열
+    pub vertices : Vec<[f64; 2]>,       // 꼭짓점 목록. 반시계 방향으로 정렬되어 있다.
+    pub dim : usize,                    // dimension of system : always 2
+    pub reflection : ReflectionType,    // Accuracy of reflection
+    triangles : Vec<[usize; 3]>,        // 내부를 분할한 삼각형
+    cum_area : Vec<f64>,                // 삼각형 넓이의 누적합
+    bbox : ([f64; 2], [f64; 2]),        // bounding box
+}
+
+fn cross(o : &[f64; 2], a : &[f64; 2], b : &[f64; 2]) -> f64{
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn triangulate(vertices : &[[f64; 2]]) -> Result<Vec<[usize; 3]>, Error>{
+    // 반시계 방향 다각형을 ear clipping으로 삼각형 분할
+    let mut idx : Vec<usize> = (0..vertices.len()).collect();
+    let mut triangles : Vec<[usize; 3]> = Vec::with_capacity(vertices.len() - 2);
+    while idx.len() > 3{
+        let m : usize = idx.len();
+        let corner = |k : usize| (idx[(k + m - 1) % m], idx[k], idx[(k + 1) % m]);
+        let ear = (0..m).find(|&k| {
+            let (a, b, c) = corner(k);
+            let (va, vb, vc) = (&vertices[a], &vertices[b], &vertices[c]);
+            cross(va, vb, vc) > 0f64 && idx.iter().all(|&j| {
+                j == a || j == b || j == c ||
+                cross(va, vb, &vertices[j]) < 0f64 || cross(vb, vc, &vertices[j]) < 0f64 || cross(vc, va, &vertices[j]) < 0f64
+            })
+        });
+        match ear{
+            Some(k) => {
+                let (a, b, c) = corner(k);
+                triangles.push([a, b, c]);
+                idx.remove(k);
+            },
+            None => {
+                // 한 직선 위에 있는 꼭짓점은 삼각형 없이 지운다. 그마저 없으면 스스로 교차하는 다각형이다.
+                let k = (0..m).find(|&k| {
+                    let (a, b, c) = corner(k);
+                    cross(&vertices[a], &vertices[b], &vertices[c]) == 0f64
+                }).ok_or(Error::make_error_syntax(ErrorCode::InvalidConfiguration))?;
+                idx.remove(k);
+            },
+        }
+    }
+    triangles.push([idx[0], idx[1], idx[2]]);
+    Ok(triangles)
+}
+
+impl ContPolygonSystem{
+    pub fn new(vertices : Vec<[f64; 2]>) -> Result<Self, Error>{
+        // vertices : 꼭짓점 목록. 시계, 반시계 방향 모두 가능하고, 마지막에 첫 꼭짓점을 반복해도 된다.
+        let mut vertices : Vec<[f64; 2]> = vertices;
+        if vertices.len() > 1 && vertices.first() == vertices.last(){
+            vertices.pop();
+        }
+        if vertices.len() < 3 || vertices.iter().any(|v| !v[0].is_finite() || !v[1].is_finite()){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let n : usize = vertices.len();
+        let area : f64 = (0..n).map(|i| cross(&[0f64, 0f64], &vertices[i], &vertices[(i + 1) % n])).sum::<f64>();
+        if area == 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+        }
+        else if area < 0f64{
+            vertices.reverse();
+        }
+
+        let triangles : Vec<[usize; 3]> = triangulate(&vertices)?;
+        let mut cum_area : Vec<f64> = Vec::with_capacity(triangles.len());
+        let mut total : f64 = 0f64;
+        for t in &triangles{
+            total += 0.5 * cross(&vertices[t[0]], &vertices[t[1]], &vertices[t[2]]);
+            cum_area.push(total);
+        }
+
+        let mut bbox : ([f64; 2], [f64; 2]) = (vertices[0], vertices[0]);
+        for v in &vertices{
+            for (i, x) in v.iter().enumerate(){
+                bbox.0[i] = bbox.0[i].min(*x);
+                bbox.1[i] = bbox.1[i].max(*x);
+            }
+        }
+
+        Ok(ContPolygonSystem{
+            sys_type : SystemType::ContinuousPolygonal,
+            bctype : BoundaryCond::Reflection,
+            vertex_file : String::new(),
+            vertices,
+            dim : 2,
+            reflection : ReflectionType::Approximate,
+            triangles,
+            cum_area,
+            bbox,
+        })
+    }
+
+    pub fn from_file<P>(path : P) -> Result<Self, Error>
+        where P : AsRef<Path>{
+        // 한 줄에 꼭짓점 하나씩 'x y' 혹은 'x,y' 형식으로 적힌 파일을 읽는다. 빈 줄과 #으로 시작하는 줄은 무시한다.
+        let f = File::open(path.as_ref()).map_err(Error::make_error_io)?;
+        let mut vertices : Vec<[f64; 2]> = Vec::new();
+        for line in BufReader::new(f).lines(){
+            let line : String = line.map_err(Error::make_error_io)?;
+            let line : &str = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+            let coord : Vec<f64> = line.split(|c : char| c.is_whitespace() || c == ',' || c == ':')
+                                       .filter(|x| !x.is_empty())
+                                       .map(|x| x.parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidFormat)))
+                                       .collect::<Result<Vec<f64>, Error>>()?;
+            if coord.len() != 2{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+            }
+            vertices.push([coord[0], coord[1]]);
+        }
+
+        let mut sys : Self = Self::new(vertices)?;
+        sys.vertex_file = path.as_ref().display().to_string();
+        Ok(sys)
+    }
+
+    fn edge(&self, i : usize) -> (&[f64; 2], &[f64; 2]){
+        (&self.vertices[i], &self.vertices[(i + 1) % self.vertices.len()])
+    }
+
+    fn project_edge(&self, i : usize, pos : &[f64]) -> [f64; 2]{
+        // i번째 변 위에서 pos와 가장 가까운 점
+        let (a, b) = self.edge(i);
+        let e : [f64; 2] = [b[0] - a[0], b[1] - a[1]];
+        let s : f64 = (((pos[0] - a[0]) * e[0] + (pos[1] - a[1]) * e[1]) / (e[0] * e[0] + e[1] * e[1])).clamp(0f64, 1f64);
+        [a[0] + s * e[0], a[1] + s * e[1]]
+    }
+
+    fn nearest_edge(&self, pos : &[f64]) -> (usize, [f64; 2], f64){
+        // pos에서 가장 가까운 변의 index, 그 변 위로의 projection, 거리의 제곱
+        let mut result : (usize, [f64; 2], f64) = (0, [0f64; 2], f64::INFINITY);
+        for i in 0..self.vertices.len(){
+            let proj : [f64; 2] = self.project_edge(i, pos);
+            let d2 : f64 = (pos[0] - proj[0]).powi(2) + (pos[1] - proj[1]).powi(2);
+            if d2 < result.2{
+                result = (i, proj, d2);
+            }
+        }
+        result
+    }
+
+    fn outward_normal(&self, i : usize) -> [f64; 2]{
+        // 반시계 방향이므로 변 벡터를 시계 방향으로 90도 돌리면 바깥 방향
+        let (a, b) = self.edge(i);
+        let (ex, ey) : (f64, f64) = (b[0] - a[0], b[1] - a[1]);
+        let norm : f64 = (ex * ex + ey * ey).sqrt();
+        [ey / norm, - ex / norm]
+    }
+
+    fn exit_time(&self, pos : &[f64], dp : &[f64], exclude : Option<usize>) -> Option<(usize, f64)>{
+        // pos부터 pos + dp까지 움직일 때 바깥으로 나가는 방향으로 처음 닿는 변의 index와 t in [0, 1]. 닿지 않으면 None
+        // exclude : 직전에 반사된 변. 반올림 오차로 같은 변에 t = 0으로 다시 닿지 않도록 제외한다.
+        let mut result : Option<(usize, f64)> = None;
+        for i in 0..self.vertices.len(){
+            if exclude == Some(i){
+                continue;
+            }
+            let (a, b) = self.edge(i);
+            let e : [f64; 2] = [b[0] - a[0], b[1] - a[1]];
+            let denom : f64 = dp[0] * e[1] - dp[1] * e[0];
+            if denom <= 0f64{
+                continue;
+            }
+            let w : [f64; 2] = [a[0] - pos[0], a[1] - pos[1]];
+            let t : f64 = (w[0] * e[1] - w[1] * e[0]) / denom;
+            let s : f64 = (w[0] * dp[1] - w[1] * dp[0]) / denom;
+            if !(-1e-12..=1f64).contains(&t) || !(0f64..=1f64).contains(&s){
+                continue;
+            }
+            let t : f64 = t.max(0f64);
+            if result.map_or(true, |(_j, x)| t < x){
+                result = Some((i, t));
+            }
+        }
+        result
+    }
+
+    fn surface_normal(&self, i : usize, pos : &mut [f64], normal : &mut [f64]){
+        // 경계 위의 점을 i번째 변 위로 옮기고, 그 변의 unit normal을 normal에 기입
+        let proj : [f64; 2] = self.project_edge(i, pos);
+        let n : [f64; 2] = self.outward_normal(i);
+        pos[0] = proj[0];
+        pos[1] = proj[1];
+        normal[0] = n[0];
+        normal[1] = n[1];
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        if self.reflection == ReflectionType::Exact{
+            // 마지막으로 닿은 변
+            let last : Cell<Option<usize>> = Cell::new(None);
+            return reflect_surface_exact(&mut pos.coordinate, &mut dp.coordinate, vel.map(|v| &mut v.coordinate[..]),
+                |x, dx| {
+                    let hit = self.exit_time(x, dx, last.get());
+                    last.set(hit.map(|(i, _t)| i));
+                    hit.map(|(_i, t)| t)
+                },
+                |x, normal| {
+                    let i : usize = last.get().unwrap_or_else(|| self.nearest_edge(x).0);
+                    self.surface_normal(i, x, normal);
+                });
+        }
+
+        pos.mut_add(dp);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+
+        // 가장 가까운 변에 대해 접어 넣는다.
+        let (i, proj, _) = self.nearest_edge(&pos.coordinate);
+        let n : [f64; 2] = self.outward_normal(i);
+        let d : f64 = (pos[0] - proj[0]) * n[0] + (pos[1] - proj[1]) * n[1];
+        for k in 0..2{
+            pos[k] -= 2f64 * d * n[k];
+        }
+        if let Some(v) = vel{
+            let vn : f64 = v[0] * n[0] + v[1] * n[1];
+            for k in 0..2{
+                v[k] -= 2f64 * vn * n[k];
+            }
+        }
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+        Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+    }
+}
+
+impl_argument_trait!(ContPolygonSystem, "System", ContPolygonSystemArguments, 2,
+    sys_type, SystemType, SystemType::ContinuousPolygonal,
+    bctype, BoundaryCond, BoundaryCond::Reflection;
+    vertex_file, String, "File of Polygon Vertices. ex) one 'x y' pair per line",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
+
+impl ContPolygonSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContPolygonSystemArguments) -> Result<Self, Error>{
+        // 파일을 읽지 못하면 panic 대신 error를 돌려준다.
+        let sys : Self = Self::from_file(&argument.vertex_file)?;
+        Ok(Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            reflection  : argument.reflection,
+            ..sys
+        })
+    }
+}
+
+impl ContPolygonSystemArguments{
+    // 파일 이름 대신 dataset (파일 이름)에는 다각형의 넓이 (SystemCore::volume)를 사용한다.
+    pub fn dim(&self) -> usize{
+        2
+    }
+}
+
+
+impl SystemCore<f64> for ContPolygonSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is in the system
+        // even-odd rule로 확인하고, 경계 위의 점은 내부로 본다.
+        // pos : vector to check
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let (x, y) : (f64, f64) = (pos[0], pos[1]);
+        let mut inside : bool = false;
+        for i in 0..self.vertices.len(){
+            let (a, b) = self.edge(i);
+            if (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) * (b[0] - a[0]) / (b[1] - a[1]){
+                inside = !inside;
+            }
+        }
+        if inside{
+            return Ok(true);
+        }
+        let scale : f64 = (self.bbox.1[0] - self.bbox.0[0]).max(self.bbox.1[1] - self.bbox.0[1]);
+        Ok(self.nearest_edge(&pos.coordinate).2 <= (1e-12 * scale).powi(2))
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_inner(pos, dp, None)
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 반사시키고, 변의 법선 방향 velocity 성분을 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel))
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // 넓이에 비례하는 확률로 삼각형을 고르고, 그 삼각형 안에서 uniform하게 뽑는다.
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        use crate::random_mod::get_uniform;
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let u : f64 = get_uniform(rng) * self.volume()?;
+        let k : usize = self.cum_area.partition_point(|&c| c < u).min(self.triangles.len() - 1);
+        let [a, b, c] = self.triangles[k];
+        let (a, b, c) = (&self.vertices[a], &self.vertices[b], &self.vertices[c]);
+
+        let (mut r1, mut r2) : (f64, f64) = (get_uniform(rng), get_uniform(rng));
+        if r1 + r2 > 1f64{
+            r1 = 1f64 - r1;
+            r2 = 1f64 - r2;
+        }
+        for i in 0..2{
+            vec[i] = a[i] + r1 * (b[i] - a[i]) + r2 * (c[i] - a[i]);
+        }
+        Ok(())
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.position_out_of_system_to_vec(&mut pos).unwrap();
+        pos
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for i in 0..2{
+            vec[i] = 2f64 * self.bbox.1[i] - self.bbox.0[i] + 1f64;
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 가장 가까운 변까지의 거리. 안쪽이 양수
+        let d : f64 = self.nearest_edge(&pos.coordinate).2.sqrt();
+        if self.check_inclusion(pos)?{
+            return Ok(d);
+        }
+        Ok(-d)
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        // 가장 가까운 변의 바깥 방향 normal
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let n : [f64; 2] = self.outward_normal(self.nearest_edge(&pos.coordinate).0);
+        normal[0] = n[0];
+        normal[1] = n[1];
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        // 다각형의 넓이
+        Ok(*self.cum_area.last().unwrap())
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 다각형의 둘레
+        Ok((0..self.vertices.len()).map(|i| {
+            let (a, b) = self.edge(i);
+            ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+        }).sum())
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        // 꼭짓점을 모두 포함하는 가장 작은 직육면체
+        Ok((Position::new(self.bbox.0.to_vec()), Position::new(self.bbox.1.to_vec())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn l_shape() -> Vec<[f64; 2]>{
+        // 넓이 3인 L자 모양. 시계 방향으로 주어도 반시계 방향으로 정렬된다.
+        vec![[0.0, 0.0], [0.0, 2.0], [1.0, 2.0], [1.0, 1.0], [2.0, 1.0], [2.0, 0.0]]
+    }
+
+    #[test]
+    fn test_new() -> Result<(), Error>{
+        let sys : ContPolygonSystem = ContPolygonSystem::new(l_shape())?;
+        assert_eq!(sys.sys_type, SystemType::ContinuousPolygonal);
+        assert_eq!(sys.bctype, BoundaryCond::Reflection);
+        assert_eq!(sys.dim, 2);
+        assert_eq!(sys.vertices[0], [2.0, 0.0]);
+        assert!((sys.volume()? - 3.0).abs() < 1e-12);
+        assert_eq!(sys.triangles.len(), 4);
+
+        assert!(ContPolygonSystem::new(vec![[0.0, 0.0], [1.0, 1.0]]).is_err());
+        assert!(ContPolygonSystem::new(vec![[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContPolygonSystem = ContPolygonSystem::new(l_shape())?;
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5, 1.5]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.5, 0.5]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.5, 1.5]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.0, 1.5]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![-0.1, 0.5]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5])),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let mut sys : ContPolygonSystem = ContPolygonSystem::new(l_shape())?;
+
+        for &rtype in [ReflectionType::Approximate, ReflectionType::Exact].iter(){
+            sys.set_reflection(rtype)?;
+
+            // 안쪽으로 꺾인 변에서 반사
+            let mut pos : Position<f64> = Position::new(vec![0.5, 1.5]);
+            let mut dp : Position<f64> = Position::new(vec![0.8, 0.0]);
+            sys.check_bc(&mut pos, &mut dp)?;
+            assert!((&pos - &Position::new(vec![0.7, 1.5])).norm() < 1e-12, "{:?}", pos);
+
+            let mut pos : Position<f64> = Position::new(vec![1.5, 0.5]);
+            let mut dp : Position<f64> = Position::new(vec![0.0, 0.8]);
+            let mut vel : Position<f64> = Position::new(vec![0.0, 1.0]);
+            sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+            assert!((&pos - &Position::new(vec![1.5, 0.7])).norm() < 1e-12, "{:?}", pos);
+            assert_eq!(vel, Position::new(vec![0.0, -1.0]));
+        }
+
+        // 두 변에서 연속으로 반사
+        let mut pos : Position<f64> = Position::new(vec![0.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![0.9, 2.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.6, 1.5])).norm() < 1e-12, "{:?}", pos);
+
+        // 꼭짓점에서 반사된 직후 같은 변에 다시 닿지 않는다
+        let mut pos : Position<f64> = Position::new(vec![1.5, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![1.0, -1.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![1.5, 0.5])).norm() < 1e-12, "{:?}", pos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        // L자 모양의 각 단위 정사각형에 들어갈 확률은 1/3
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContPolygonSystem = ContPolygonSystem::new(l_shape())?;
+        let n : usize = 30000;
+        let mut count : [usize; 3] = [0; 3];
+        for _i in 0..n{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+            if pos[1] > 1f64{
+                count[0] += 1;
+            }
+            else if pos[0] > 1f64{
+                count[1] += 1;
+            }
+            else{
+                count[2] += 1;
+            }
+        }
+        for c in count.iter(){
+            assert!((*c as f64 / n as f64 - 1f64 / 3f64).abs() < 0.02, "{:?}", count);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file() -> Result<(), Error>{
+        let path = std::env::temp_dir().join("moledyn_test_polygon.dat");
+        {
+            let mut writer = BufWriter::new(File::create(&path).map_err(Error::make_error_io)?);
+            write!(&mut writer, "# L shape\n0 0\n2,0\n\n2 1\n1 1\n1 2\n0 2\n0 0\n").map_err(Error::make_error_io)?;
+        }
+        let sys : ContPolygonSystem = ContPolygonSystem::from_file(&path)?;
+        fs::remove_file(&path).map_err(Error::make_error_io)?;
+        assert_eq!(sys.vertices.len(), 6);
+        assert!((sys.volume()? - 3.0).abs() < 1e-12);
+        assert_eq!(sys.vertex_file, path.display().to_string());
+
+        let args : Vec<String> = vec![path.display().to_string(), "Exact".to_string()];
+        let argument : ContPolygonSystemArguments = ContPolygonSystem::read_args_from_vec(&args)?;
+        assert_eq!(argument.reflection, ReflectionType::Exact);
+        assert!(ContPolygonSystem::from_file(&path).is_err());
+        assert!(ContPolygonSystem::convert_from(&argument).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+// Modules for continous rectangular system.
+// 각 축마다 길이가 다른 연속 직육면체 시스템. 중심을 원점이 아닌 곳에 둘 수도 있다.
+
+use crate::prelude::*;
+use crate::system_mod::{fold_box, signed_distance_from_excess, normal_weights_from_excess};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContRectSystem{              // 연속 rectangular 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition
+    pub sys_size : Position<f64>,       // half length of each axis. ex. i-th domain = (center[i] - sys_size[i], center[i] + sys_size[i])
+    pub center : Position<f64>,         // center of system
+    pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
+}
+
+impl ContRectSystem{
+    pub fn new(bctype : BoundaryCond, sys_size : Position<f64>) -> Self{
+        // 원점이 중심인 시스템
+        // sys_size : half length of each axis
+        let center : Position<f64> = Position::new(vec![0f64; sys_size.dim()]);
+        Self::new_with_center(bctype, sys_size, center)
+    }
+
+    pub fn new_with_center(bctype : BoundaryCond, sys_size : Position<f64>, center : Position<f64>) -> Self{
+        // sys_size : half length of each axis
+        // center : center of system
+        let dim : usize = sys_size.dim();
+        Self::check_arguments(&bctype, &sys_size, &center);
+
+        ContRectSystem{
+            sys_type : SystemType::ContinuousRectangular,
+            bctype,
+            sys_size,
+            center,
+            dim,
+            reflection : ReflectionType::Approximate,
+        }
+    }
+
+    fn check_arguments(bctype : &BoundaryCond, sys_size : &Position<f64>, center : &Position<f64>){
+        let dim : usize = sys_size.dim();
+        if dim == 0 || dim != center.dim() || sys_size.iter().any(|&x| x <= 0f64){
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+
+        match bctype{
+            BoundaryCond::Mixed(d) if *d > dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            BoundaryCond::PerFace(faces) if faces.dim() != dim => panic!("{}", ErrorCode::InvalidArgumentInput),
+            _ => (),
+        }
+    }
+
+    // i번째 축의 (하한, 상한)
+    pub fn bounds(&self, axis : usize) -> (f64, f64){
+        (self.center[axis] - self.sys_size[axis], self.center[axis] + self.sys_size[axis])
+    }
+
+    // i번째 축의 (하한 면, 상한 면)의 boundary condition
+    pub fn face_cond(&self, axis : usize) -> (FaceCond, FaceCond){
+        let face : FaceCond = match &self.bctype{
+            BoundaryCond::Periodic => FaceCond::Periodic,
+            BoundaryCond::Reflection => FaceCond::Reflection,
+            BoundaryCond::Mixed(d) if axis < *d => FaceCond::Reflection,
+            BoundaryCond::Mixed(_) => FaceCond::Periodic,
+            BoundaryCond::Absorbing => FaceCond::Absorbing,
+            BoundaryCond::PartiallyAbsorbing(kappa) => FaceCond::PartiallyAbsorbing(*kappa),
+            BoundaryCond::PerFace(faces) => return (faces.lower(axis), faces.upper(axis)),
+        };
+        (face, face)
+    }
+
+    fn wall_axes(&self) -> impl Iterator<Item = usize> + '_{
+        // periodic하지 않아서 벽이 있는 축들
+        (0..self.dim).filter(move |&i| self.face_cond(i).0 != FaceCond::Periodic)
+    }
+
+    fn check_bc_faces(&self, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                      absorb: Option<(&mut Pcg64, f64, f64)>, vel: Option<&mut Position<f64>>) -> Result<bool, Error>{
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        fold_box(|i| {
+            let (lower, upper) = self.bounds(i);
+            let (face_lower, face_upper) = self.face_cond(i);
+            [(lower, face_lower), (upper, face_upper)]
+        }, self.reflection, pos, dp, absorb, vel)
+    }
+}
+
+impl_argument_trait!(ContRectSystem, "System", ContRectSystemArguments, 4,
+    sys_type, SystemType, SystemType::ContinuousRectangular;
+    bctype, BoundaryCond, "Boundary condition. ex) Reflective, Periodic, integer indicates Mixed, P:R/A indicates per-face",
+    sys_size, Position::<f64>, "Half length of each axis. ex) 2:0.5 = (-2, 2) x (-0.5, 0.5)",
+    center, Position::<f64>, "Center of System. ex) 0:0 = (0,0)",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
+
+impl ContRectSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContRectSystemArguments) -> Self{
+        Self::check_arguments(&argument.bctype, &argument.sys_size, &argument.center);
+
+        Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            sys_size    : argument.sys_size.clone(),
+            center      : argument.center.clone(),
+            dim         : argument.sys_size.dim(),
+            reflection  : argument.reflection,
+        }
+    }
+}
+
+impl ContRectSystemArguments{
+    // sys_size가 Position이므로 dataset (파일 이름)에는 아래의 scalar 값들을 사용한다.
+    pub fn max_half_length(&self) -> f64{
+        self.sys_size.iter().fold(0f64, |acc, &a| acc.max(a))
+    }
+
+    pub fn dim(&self) -> usize{
+        self.sys_size.dim()
+    }
+}
+
+
+impl SystemCore<f64> for ContRectSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is in the system
+        // pos : vector to check
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        for (i, &x) in pos.iter().enumerate(){
+            if (x - self.center[i]).abs() > self.sys_size[i]{
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // 흡수하는 면에 닿으면 agent를 지워야 하므로 AbsorbingBoundary error. check_bc_absorb를 사용해야 한다.
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_faces(pos, dp, None, None).map(|_| ())
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 옮기고, 반사된 성분의 velocity를 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_faces(pos, dp, None, Some(vel)).map(|_| ())
+    }
+
+    fn check_bc_absorb(&self, rng: &mut Pcg64, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                       coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+        // 흡수 경계에 닿으면 true. 흡수되지 않으면 반사 경계처럼 check_bc
+        self.check_bc_faces(pos, dp, Some((rng, coeff_diff, dt)), None)
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        use crate::random_mod::get_uniform;
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for i in 0..self.dim{
+            let (lower, upper) = self.bounds(i);
+            vec[i] = lower + (upper - lower) * get_uniform(rng);
+        }
+        Ok(())
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        for i in 0..self.dim{
+            pos[i] = self.center[i] + 2f64 * self.sys_size[i];
+        }
+        pos
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for i in 0..self.dim{
+            vec[i] = self.center[i] + 2f64 * self.sys_size[i];
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // periodic하지 않은 축의 벽들만 경계로 본다.
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let excess : Vec<f64> = self.wall_axes().map(|i| (pos[i] - self.center[i]).abs() - self.sys_size[i]).collect();
+        Ok(signed_distance_from_excess(&excess))
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let axes : Vec<usize> = self.wall_axes().collect();
+        let excess : Vec<f64> = axes.iter().map(|&i| (pos[i] - self.center[i]).abs() - self.sys_size[i]).collect();
+        let weights : Vec<f64> = normal_weights_from_excess(&excess)?;
+        normal.clear();
+        for (&i, w) in axes.iter().zip(&weights){
+            // 중심 위에서는 양의 방향 벽을 택한다.
+            normal[i] = if pos[i] < self.center[i] { -w } else { *w };
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        Ok(self.sys_size.iter().map(|&a| 2f64 * a).product())
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 벽이 있는 축 i마다 넓이 volume / (2 L_i)인 면이 두 개
+        let volume : f64 = self.volume()?;
+        Ok(self.wall_axes().map(|i| volume / self.sys_size[i]).sum())
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        Ok((&self.center - &self.sys_size, &self.center + &self.sys_size))
+    }
+
+    fn is_periodic(&self, axis: usize) -> bool{
+        axis < self.dim && self.face_cond(axis).0 == FaceCond::Periodic
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_new(){
+        let sys : ContRectSystem = ContRectSystem::new(BoundaryCond::Reflection, Position::new(vec![2.0, 0.5]));
+        assert_eq!(sys.sys_type, SystemType::ContinuousRectangular);
+        assert_eq!(sys.dim, 2);
+        assert_eq!(sys.center, Position::new(vec![0.0, 0.0]));
+
+        let sys : ContRectSystem = ContRectSystem::new_with_center(BoundaryCond::Periodic,
+                                        Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, -1.0]));
+        assert_eq!(sys.bounds(0), (-1.0, 3.0));
+        assert_eq!(sys.bounds(1), (-1.5, -0.5));
+        assert_eq!(sys.face_cond(1), (FaceCond::Periodic, FaceCond::Periodic));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid(){
+        ContRectSystem::new_with_center(BoundaryCond::Reflection, Position::new(vec![2.0, 0.5]), Position::new(vec![0.0]));
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContRectSystem = ContRectSystem::new_with_center(BoundaryCond::Reflection,
+                                        Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, 0.0]));
+        assert_eq!(sys.check_inclusion(&Position::new(vec![2.5, 0.4]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![-1.5, 0.0]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.0, 0.6]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.0])),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        // x : (-1, 3), y : (-1.5, -0.5)
+        let mut sys : ContRectSystem = ContRectSystem::new_with_center(BoundaryCond::Mixed(1),
+                                            Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, -1.0]));
+        let mut pos : Position<f64> = Position::new(vec![2.5, -1.0]);
+        let mut dp : Position<f64> = Position::new(vec![1.0, 0.75]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![2.5, -1.25])).norm() < 1e-12);
+
+        let mut vel : Position<f64> = Position::new(vec![1.0, 1.0]);
+        let mut dp : Position<f64> = Position::new(vec![-12.5, 0.0]);       // -1, 3, -1에서 세번 반사
+        assert_eq!(sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel),
+                   Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep)));
+        assert!((&pos - &Position::new(vec![2.5, -1.25])).norm() < 1e-12);
+        sys.set_reflection(ReflectionType::Exact)?;
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![0.0, -1.25])).norm() < 1e-12);
+        assert_eq!(vel, Position::new(vec![-1.0, 1.0]));
+
+        // 흡수 경계
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContRectSystem = ContRectSystem::new(BoundaryCond::Absorbing, Position::new(vec![2.0, 0.5]));
+        let mut pos : Position<f64> = Position::new(vec![1.5, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.0, 0.6]);
+        assert_eq!(sys.check_bc(&mut pos, &mut dp), Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)));
+        assert_eq!(sys.check_bc_absorb(&mut rng, &mut pos, &mut dp, 1.0, 1e-3), Ok(true));
+        assert_eq!(pos, Position::new(vec![1.5, 0.0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContRectSystem = ContRectSystem::new_with_center("R:P/P".parse()?,
+                                        Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, -1.0]));
+        let n : usize = 10000;
+        let mut mean : Position<f64> = Position::new(vec![0.0, 0.0]);
+        for _i in 0..n{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+            mean.mut_add(&pos);
+        }
+        mean.mut_scalar_mul(1f64 / n as f64);
+        assert!((&mean - &sys.center).norm() < 0.05);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        // (-1, 3) x (-1.5, -0.5)
+        let sys : ContRectSystem = ContRectSystem::new_with_center(BoundaryCond::Reflection,
+                                        Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, -1.0]));
+        let mut normal : Position<f64> = Position::new(vec![0.0; 2]);
+
+        let pos : Position<f64> = Position::new(vec![0.0, -1.3]);
+        assert!((sys.signed_distance(&pos)? - 0.2).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, -1.0]));
+
+        // 모서리 밖에서는 모서리까지의 거리
+        let pos : Position<f64> = Position::new(vec![6.0, 3.5]);
+        assert!((sys.signed_distance(&pos)? + 5.0).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&pos, &mut normal)?;
+        assert!((&normal - &Position::new(vec![0.6, 0.8])).norm() < 1e-12);
+
+        assert_eq!(sys.volume()?, 4.0);
+        assert_eq!(sys.surface_area()?, 10.0);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-1.0, -1.5]), Position::new(vec![3.0, -0.5])));
+
+        // periodic한 축에는 벽이 없다.
+        let sys : ContRectSystem = ContRectSystem::new_with_center("R:P".parse()?,
+                                        Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, -1.0]));
+        assert!((sys.signed_distance(&Position::new(vec![0.0, -1.4]))? - 1.0).abs() < 1e-12);
+        assert_eq!(sys.surface_area()?, 2.0);
+        let sys : ContRectSystem = ContRectSystem::new(BoundaryCond::Periodic, Position::new(vec![2.0, 0.5]));
+        assert_eq!(sys.signed_distance(&Position::new(vec![0.0, 0.0]))?, f64::INFINITY);
+        assert_eq!(sys.boundary_normal_to_vec(&Position::new(vec![0.0, 0.0]), &mut normal),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_argument() -> Result<(), Error>{
+        let args : Vec<String> = ["Reflective", "2:0.5", "1:0", "Exact"].iter().map(|x| x.to_string()).collect();
+        let argument : ContRectSystemArguments = ContRectSystem::read_args_from_vec(&args)?;
+        let sys : ContRectSystem = ContRectSystem::convert_from(&argument);
+        let mut res : ContRectSystem = ContRectSystem::new_with_center(BoundaryCond::Reflection,
+                            Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, 0.0]));
+        res.set_reflection(ReflectionType::Exact)?;
+        assert_eq!(sys, res);
+        Ok(())
+    }
+}
@@ -0,0 +1,368 @@
+// Modules for continous shell system.
+// 두 반지름 사이의 annulus, 혹은 spherical shell 시스템. 안쪽 구는 agent가 들어갈 수 없는 영역 (ex. 핵)이다.
+
+use crate::prelude::*;
+use crate::system_mod::{reflect_surface_exact, sphere_exit_time, sphere_entry_time, unit_ball_volume};
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContShellSystem{             // 연속 shell 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition : here, only reflective bc is available
+    pub sys_inner : f64,                // radius of inner surface
+    pub sys_size : f64,                 // radius of outer surface
+    pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection
+}
+
+impl ContShellSystem{
+    pub fn new(inner : f64, outer : f64, dim : usize) -> Self{
+        // inner : radius of inner surface
+        // outer : radius of outer surface
+        // dim : dimension of system
+        if inner < 0f64 || outer <= inner || dim == 0{
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+
+        ContShellSystem{
+            sys_type : SystemType::ContinuousShell,
+            bctype : BoundaryCond::Reflection,
+            sys_inner : inner,
+            sys_size : outer,
+            dim,
+            reflection : ReflectionType::Exact,       // 반지름 방향으로 접는 방법은 안쪽 구를 통과하는 step을 놓친다.
+        }
+    }
+}
+
+impl_argument_trait!(ContShellSystem, "System", ContShellSystemArguments, 4,
+    sys_type, SystemType, SystemType::ContinuousShell,
+    bctype, BoundaryCond, BoundaryCond::Reflection;
+    sys_inner, f64, "Radius of Inner Surface",
+    sys_size, f64, "Radius of Outer Surface",
+    dim, usize, "Dimension of System",
+    reflection, ReflectionType, "Reflection method. ex) Approximate, Exact");
+
+impl ContShellSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContShellSystemArguments) -> Self{
+        if argument.sys_inner < 0f64 || argument.sys_size <= argument.sys_inner || argument.dim == 0{
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+
+        Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            sys_inner   : argument.sys_inner,
+            sys_size    : argument.sys_size,
+            dim         : argument.dim,
+            reflection  : argument.reflection,
+        }
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let (r_in, r_out) : (f64, f64) = (self.sys_inner, self.sys_size);
+        if self.reflection == ReflectionType::Exact{
+            // 바깥 구면과 안쪽 구면 중 먼저 닿는 곳에서 반사
+            return reflect_surface_exact(&mut pos.coordinate, &mut dp.coordinate, vel.map(|v| &mut v.coordinate[..]),
+                |x, dx| {
+                    match (sphere_exit_time(x, dx, r_out), sphere_entry_time(x, dx, r_in)){
+                        (Some(t1), Some(t2)) => Some(t1.min(t2)),
+                        (t1, t2) => t1.or(t2),
+                    }
+                },
+                |x, normal| {
+                    let s : f64 = x.iter().map(|y| y * y).sum::<f64>().sqrt();
+                    let r : f64 = if (s - r_in).abs() < (s - r_out).abs() { r_in } else { r_out };
+                    for (y, n) in x.iter_mut().zip(normal.iter_mut()){
+                        *n = *y / s;
+                        *y = r * *n;
+                    }
+                });
+        }
+
+        pos.mut_add(dp);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+
+        // 반지름 방향으로만 접어 넣는다.
+        let s : f64 = pos.norm();
+        let r : f64 = if s > r_out { r_out } else { r_in };
+        if let Some(v) = vel{
+            let vn : f64 = pos.inner_product(v)? / s;
+            for i in 0..self.dim{
+                v[i] -= 2f64 * vn * pos[i] / s;
+            }
+        }
+        pos.mut_scalar_mul((2f64 * r - s) / s);
+        if self.check_inclusion(pos)?{
+            return Ok(());
+        }
+        Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+    }
+}
+
+
+impl SystemCore<f64> for ContShellSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is in the system
+        // pos : vector to check
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let r : f64 = pos.norm();
+        Ok(self.sys_inner <= r && r <= self.sys_size)
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_inner(pos, dp, None)
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같은 방식으로 위치를 반사시키고, 반지름 방향 velocity 성분을 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel))
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // System 내부의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // 방향은 gaussian vector로 뽑고, 반지름은 r^dim이 uniform하도록 뽑는다.
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        use crate::random_mod::{get_uniform, get_gaussian_to_vec};
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let d : i32 = self.dim as i32;
+        let (v_in, v_out) : (f64, f64) = (self.sys_inner.powi(d), self.sys_size.powi(d));
+        let r : f64 = (v_in + (v_out - v_in) * get_uniform(rng)).powf(1f64 / self.dim as f64);
+
+        let mut norm : f64 = 0f64;
+        while norm == 0f64{
+            vec.clear();
+            get_gaussian_to_vec(rng, vec);
+            norm = vec.norm();
+        }
+        vec.mut_scalar_mul(r / norm);
+        Ok(())
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        let r : f64 = self.sys_size;
+        let dim : usize = self.dim;
+        Position::new(vec![2f64 * r; dim])
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let r : f64 = self.sys_size;
+        for x in &mut vec.coordinate{
+            *x = 2f64 * r;
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 바깥 구면과 안쪽 구면 중 가까운 것까지의 거리. 안쪽 반지름이 0이면 바깥 구면만 경계이다.
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let r : f64 = pos.norm();
+        if self.sys_inner == 0f64{
+            return Ok(self.sys_size - r);
+        }
+        Ok((self.sys_size - r).min(r - self.sys_inner))
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        // 바깥 구면에서는 pos 방향, 안쪽 구면에서는 중심 방향. 중심에서는 첫번째 축을 기준으로 한다.
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let r : f64 = pos.norm();
+        let sign : f64 = if self.sys_inner > 0f64 && r - self.sys_inner < self.sys_size - r { -1f64 } else { 1f64 };
+        if r == 0f64{
+            normal.clear();
+            normal[0] = sign;
+            return Ok(());
+        }
+        for (n, x) in normal.coordinate.iter_mut().zip(&pos.coordinate){
+            *n = sign * x / r;
+        }
+        Ok(())
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        let d : i32 = self.dim as i32;
+        Ok(unit_ball_volume(self.dim) * (self.sys_size.powi(d) - self.sys_inner.powi(d)))
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        // 두 구면의 넓이의 합. d 차원 구의 겉넓이는 d V_d R^(d-1)
+        let d : i32 = self.dim as i32;
+        let inner : f64 = if self.sys_inner > 0f64 { self.sys_inner.powi(d - 1) } else { 0f64 };
+        Ok(self.dim as f64 * unit_ball_volume(self.dim) * (self.sys_size.powi(d - 1) + inner))
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let r : f64 = self.sys_size;
+        Ok((Position::new(vec![-r; self.dim]), Position::new(vec![r; self.dim])))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_new(){
+        let sys : ContShellSystem = ContShellSystem::new(1.0, 3.0, 2);
+        assert_eq!(sys.sys_type, SystemType::ContinuousShell);
+        assert_eq!(sys.bctype, BoundaryCond::Reflection);
+        assert_eq!(sys.sys_inner, 1.0);
+        assert_eq!(sys.sys_size, 3.0);
+        assert_eq!(sys.dim, 2);
+        assert_eq!(sys.reflection(), ReflectionType::Exact);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid(){
+        ContShellSystem::new(3.0, 1.0, 2);
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContShellSystem = ContShellSystem::new(1.0, 3.0, 2);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![2.0, 0.0]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5, 0.5]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![3.0, 1.0]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![2.0])),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let mut sys : ContShellSystem = ContShellSystem::new(1.0, 3.0, 2);
+
+        // 끝점이 annulus 안에 있더라도 안쪽 구를 지나가는 step은 안쪽 구면에서 반사된다.
+        // (2, 0) -> (1, 0)에서 반사 -> (3, 0)에서 반사 -> (2, 0)
+        let mut pos : Position<f64> = Position::new(vec![2.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![-4.0, 0.0]);
+        let mut vel : Position<f64> = Position::new(vec![-1.0, 0.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![2.0, 0.0])).norm() < 1e-12, "{:?}", pos);
+        assert!((&vel - &Position::new(vec![-1.0, 0.0])).norm() < 1e-12);
+
+        // 근사적인 방법에서는 안쪽 구면에서 반지름 방향으로 접힌다.
+        sys.set_reflection(ReflectionType::Approximate)?;
+        let mut pos : Position<f64> = Position::new(vec![2.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![-1.5, 0.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![1.5, 0.0])).norm() < 1e-12);
+
+        // (2, 0.5)에서 (-1, 0)방향으로 움직이면 안쪽 원의 (sqrt(3)/2, 1/2)에서 반사된다.
+        sys.set_reflection(ReflectionType::Exact)?;
+        let mut pos : Position<f64> = Position::new(vec![2.0, 0.5]);
+        let mut dp : Position<f64> = Position::new(vec![-1.5, 0.0]);
+        let mut vel : Position<f64> = Position::new(vec![-1.0, 0.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        let x0 : f64 = 3f64.sqrt() / 2f64;
+        let remain : f64 = 1.5 - (2f64 - x0);
+        let answer = Position::new(vec![x0 + remain * 0.5, 0.5 + remain * x0]);
+        assert!((&pos - &answer).norm() < 1e-12, "{:?} {:?}", pos, answer);
+        assert!((&vel - &Position::new(vec![0.5, x0])).norm() < 1e-12);
+
+        // 바깥 구면과 안쪽 구면에서 모두 반사
+        let mut pos : Position<f64> = Position::new(vec![2.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![-4.5, 0.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![1.5, 0.0])).norm() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        // 2D annulus에서 r < 2일 확률은 (4 - 1) / (9 - 1)
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContShellSystem = ContShellSystem::new(1.0, 3.0, 2);
+        let n : usize = 20000;
+        let mut count : usize = 0;
+        for _i in 0..n{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+            count += (pos.norm() < 2f64) as usize;
+        }
+        assert!((count as f64 / n as f64 - 3f64 / 8f64).abs() < 0.02);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        use std::f64::consts::PI;
+        let sys : ContShellSystem = ContShellSystem::new(1.0, 3.0, 2);
+        let mut normal : Position<f64> = Position::new(vec![0.0; 2]);
+
+        assert!((sys.signed_distance(&Position::new(vec![0.0, 2.5]))? - 0.5).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![0.0, 2.5]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 1.0]));
+        assert!((sys.signed_distance(&Position::new(vec![0.0, -1.5]))? - 0.5).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![0.0, -1.5]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![0.0, 1.0]));
+
+        // 안쪽 구 안과 바깥 구 밖은 음수
+        assert!((sys.signed_distance(&Position::new(vec![0.5, 0.0]))? + 0.5).abs() < 1e-12);
+        assert!((sys.signed_distance(&Position::new(vec![4.0, 0.0]))? + 1.0).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![0.0, 0.0]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![-1.0, 0.0]));
+
+        assert!((sys.volume()? - 8.0 * PI).abs() < 1e-12);
+        assert!((sys.surface_area()? - 8.0 * PI).abs() < 1e-12);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-3.0; 2]), Position::new(vec![3.0; 2])));
+
+        // 안쪽 반지름이 0이면 원과 같다.
+        let sys : ContShellSystem = ContShellSystem::new(0.0, 3.0, 3);
+        assert!((sys.signed_distance(&Position::new(vec![0.0, 0.1, 0.0]))? - 2.9).abs() < 1e-12);
+        assert!((sys.surface_area()? - 36.0 * PI).abs() < 1e-12);
+        Ok(())
+    }
+}
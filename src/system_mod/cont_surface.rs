@@ -0,0 +1,326 @@
+// Modules for continous sphere surface system.
+// 반지름 sys_size인 n-sphere의 표면 (dim 차원 공간 속의 dim - 1 차원 곡면) 위에서만 움직이는 시스템.
+// 세포막 위의 receptor 탐색처럼 막에 붙어서 확산하는 문제를 위한 것이다.
+// 경계가 없는 닫힌 곡면이므로 check_bc는 반사 대신 곡면 위의 intrinsic Brownian motion을 계산한다 :
+//      변위를 접평면에 projection한 후, 그 방향의 geodesic을 따라 같은 길이만큼 움직인다 (exponential map).
+
+use crate::prelude::*;
+use crate::system_mod::unit_ball_volume;
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContSphereSurfaceSystem{     // 연속 구면 시스템
+    pub sys_type : SystemType,          // System type
+    pub bctype : BoundaryCond,          // Boundary condition : 경계가 없으므로 Periodic
+    pub sys_size : f64,                 // radius of sphere
+    pub dim : usize,                    // dimension of embedding space. 곡면은 dim - 1 차원이다.
+}
+
+impl ContSphereSurfaceSystem{
+    pub fn new(sys_size : f64, dim : usize) -> Self{
+        // sys_size : radius of sphere
+        // dim : dimension of embedding space
+        if sys_size <= 0f64 || dim < 2{
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+
+        ContSphereSurfaceSystem{
+            sys_type : SystemType::ContinuousSphereSurface,
+            bctype : BoundaryCond::Periodic,
+            sys_size,
+            dim,
+        }
+    }
+
+    // 구면 위의 두 점 사이의 geodesic distance. 두 점은 원점에서 본 방향만 사용한다.
+    pub fn geodesic_distance(&self, pos1 : &Position<f64>, pos2 : &Position<f64>) -> Result<f64, Error>{
+        Ok(self.sys_size * geodesic_angle(pos1, pos2)?)
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: Option<&mut Position<f64>>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let r : f64 = pos.norm();
+        if r == 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        // 접평면으로 projection
+        let radial : f64 = dp.inner_product(pos)? / r;
+        for i in 0..self.dim{
+            dp[i] -= radial * pos[i] / r;
+        }
+        let s : f64 = dp.norm();
+
+        // 원점 방향 normal n과 진행 방향 u로 이루어진 평면에서 각도 theta만큼 회전
+        //      n' = cos(theta) n + sin(theta) u, u' = - sin(theta) n + cos(theta) u
+        let theta : f64 = s / self.sys_size;
+        let (sin, cos) : (f64, f64) = theta.sin_cos();
+        if let Some(v) = vel{
+            // velocity의 u 성분은 u'으로 옮기고 (parallel transport), 나머지 접평면 성분은 그대로 둔다.
+            let vn : f64 = v.inner_product(pos)? / r;
+            let vu : f64 = if s > 0f64 { v.inner_product(dp)? / s } else { 0f64 };
+            for i in 0..self.dim{
+                let (n, u) : (f64, f64) = (pos[i] / r, if s > 0f64 { dp[i] / s } else { 0f64 });
+                v[i] += - vn * n - vu * u + vu * (- sin * n + cos * u);
+            }
+        }
+        for i in 0..self.dim{
+            let (n, u) : (f64, f64) = (pos[i] / r, if s > 0f64 { dp[i] / s } else { 0f64 });
+            pos[i] = self.sys_size * (cos * n + sin * u);
+        }
+        Ok(())
+    }
+}
+
+// 원점에서 본 두 방향 사이의 각도. acos보다 작은 각도에서 정확하다.
+pub(crate) fn geodesic_angle(pos1 : &Position<f64>, pos2 : &Position<f64>) -> Result<f64, Error>{
+    if pos1.dim() != pos2.dim(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+    let (r1, r2) : (f64, f64) = (pos1.norm(), pos2.norm());
+    if r1 == 0f64 || r2 == 0f64{
+        return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+    }
+    let chord : f64 = pos1.iter().zip(pos2.iter()).map(|(x, y)| (x / r1 - y / r2).powi(2)).sum::<f64>().sqrt();
+    Ok(2f64 * (0.5 * chord).min(1f64).asin())
+}
+
+impl_argument_trait!(ContSphereSurfaceSystem, "System", ContSphereSurfaceSystemArguments, 2,
+    sys_type, SystemType, SystemType::ContinuousSphereSurface,
+    bctype, BoundaryCond, BoundaryCond::Periodic;
+    sys_size, f64, "Radius of Sphere",
+    dim, usize, "Dimension of Embedding Space. ex) 3 : 2D surface of ball in 3D");
+
+impl ContSphereSurfaceSystem{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContSphereSurfaceSystemArguments) -> Self{
+        Self{
+            sys_type    : argument.sys_type,
+            bctype      : argument.bctype.clone(),
+            ..Self::new(argument.sys_size, argument.dim)
+        }
+    }
+}
+
+
+impl SystemCore<f64> for ContSphereSurfaceSystem{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // Return whether a position vector is on the sphere
+        // pos : vector to check
+
+        if self.dim != pos.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Ok((pos.norm() - self.sys_size).abs() <= 1e-9 * self.sys_size)
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // 변위를 접평면에 projection한 후 exponential map으로 구면 위의 새 위치를 계산
+        // pos : initial position of ptl
+        // dp : displacement of ptl. 접평면 성분만 남는다.
+        self.check_bc_inner(pos, dp, None)
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // check_bc와 같고, velocity는 geodesic을 따라 parallel transport한다. 법선 성분은 버린다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel))
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // 구면 위의 임의의 위치를 uniform하게 뽑아 반환
+        // rng : random number generator
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // 구면 위의 임의의 위치를 uniform하게 뽑아서 mutable reference에 기입
+        // gaussian vector의 방향은 구면 위에서 uniform하다.
+        // rng : random number generator
+        // vec : 결과 적을 mutable reference
+
+        use crate::random_mod::get_gaussian_to_vec;
+
+        if vec.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        let mut norm : f64 = 0f64;
+        while norm == 0f64{
+            vec.clear();
+            get_gaussian_to_vec(rng, vec);
+            norm = vec.norm();
+        }
+        vec.mut_scalar_mul(self.sys_size / norm);
+        Ok(())
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        // system 밖의 점을 하나 출력해주는 함수
+        // 구의 중심은 geodesic distance를 정의할 수 없으므로 구 바깥의 점을 쓴다.
+        Position::new(vec![2f64 * self.sys_size; self.dim])
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        // system 밖의 점을 하나 vector에 적어주는 함수
+
+        if self.dim != vec.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        for x in &mut vec.coordinate{
+            *x = 2f64 * self.sys_size;
+        }
+        Ok(())
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // 닫힌 곡면이므로 경계가 없다. 곡면 밖의 점은 곡면까지의 거리에 -를 붙인다.
+        if self.check_inclusion(pos)?{
+            return Ok(f64::INFINITY);
+        }
+        Ok(- (pos.norm() - self.sys_size).abs())
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        if self.dim != pos.dim() || self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration))
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        // agent가 움직이는 dim - 1 차원 곡면의 넓이, d V_d R^(d-1)
+        Ok(self.dim as f64 * unit_ball_volume(self.dim) * self.sys_size.powi(self.dim as i32 - 1))
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        Ok(0f64)
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        let r : f64 = self.sys_size;
+        Ok((Position::new(vec![-r; self.dim]), Position::new(vec![r; self.dim])))
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_new(){
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(2.0, 3);
+        assert_eq!(sys.sys_type, SystemType::ContinuousSphereSurface);
+        assert_eq!(sys.bctype, BoundaryCond::Periodic);
+        assert_eq!(sys.sys_size, 2.0);
+        assert_eq!(sys.dim, 3);
+
+        // 경계가 없으므로 reflection 방식을 고를 수 없다.
+        let mut sys = sys;
+        assert_eq!(sys.reflection(), ReflectionType::Approximate);
+        assert_eq!(sys.set_reflection(ReflectionType::Exact), Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid(){
+        ContSphereSurfaceSystem::new(1.0, 1);
+    }
+
+    #[test]
+    fn test_check_inclusion() -> Result<(), Error>{
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(2.0, 3);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.0, 2.0, 0.0]))?, true);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.0, 1.9, 0.0]))?, false);
+        assert_eq!(sys.check_inclusion(&sys.position_out_of_system())?, false);
+        assert!((sys.geodesic_distance(&Position::new(vec![1.0, 0.0, 0.0]), &Position::new(vec![0.0, 0.0, 5.0]))? - PI).abs() < 1e-12);
+        assert!((sys.geodesic_distance(&Position::new(vec![1.0, 0.0, 0.0]), &Position::new(vec![-1.0, 0.0, 0.0]))? - 2.0 * PI).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(1.0, 3);
+
+        // 법선 성분은 버리고, 적도를 따라 pi/2만큼 움직인다.
+        let mut pos : Position<f64> = Position::new(vec![1.0, 0.0, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.3, PI / 2.0, 0.0]);
+        let mut vel : Position<f64> = Position::new(vec![0.5, 1.0, 2.0]);
+        sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+        assert!((&pos - &Position::new(vec![0.0, 1.0, 0.0])).norm() < 1e-12, "{:?}", pos);
+        assert!((&vel - &Position::new(vec![-1.0, 0.0, 2.0])).norm() < 1e-12, "{:?}", vel);
+
+        // 한 바퀴를 돌면 제자리
+        let mut dp : Position<f64> = Position::new(vec![0.0, 0.0, 2.0 * PI]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![0.0, 1.0, 0.0])).norm() < 1e-12, "{:?}", pos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_pos() -> Result<(), Error>{
+        // 2-sphere에서 z > R/2 일 확률은 1/4 (Archimedes)
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(2.0, 3);
+        let n : usize = 20000;
+        let mut count : usize = 0;
+        for _i in 0..n{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+            count += (pos[2] > 1f64) as usize;
+        }
+        assert!((count as f64 / n as f64 - 0.25).abs() < 0.01);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diffusion() -> Result<(), Error>{
+        // S^{d-1} 위의 Brownian motion : <cos theta(t)> = exp(-(d - 1) D t / R^2)
+        use crate::random_mod::get_gaussian_to_vec_nonstandard;
+
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let (coeff_diff, dt, num_step) : (f64, f64, usize) = (1.0, 1e-3, 250);
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(1.0, 3);
+        let start : Position<f64> = Position::new(vec![0.0, 0.0, 1.0]);
+        let mut dp : Position<f64> = Position::new(vec![0.0; 3]);
+        let n : usize = 2000;
+        let mut sum : f64 = 0f64;
+        for _i in 0..n{
+            let mut pos : Position<f64> = start.clone();
+            for _j in 0..num_step{
+                dp.clear();
+                get_gaussian_to_vec_nonstandard(&mut rng, &mut dp, 0f64, (2f64 * coeff_diff * dt).sqrt());
+                sys.check_bc(&mut pos, &mut dp)?;
+            }
+            assert!(sys.check_inclusion(&pos)?);
+            sum += pos[2];
+        }
+        let answer : f64 = (-2f64 * coeff_diff * dt * num_step as f64).exp();
+        assert!((sum / n as f64 - answer).abs() < 0.03, "{} {}", sum / n as f64, answer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(2.0, 3);
+        let mut normal : Position<f64> = Position::new(vec![0.0; 3]);
+
+        assert_eq!(sys.signed_distance(&Position::new(vec![0.0, 2.0, 0.0]))?, f64::INFINITY);
+        assert!((sys.signed_distance(&Position::new(vec![0.0, 1.5, 0.0]))? + 0.5).abs() < 1e-12);
+        assert_eq!(sys.boundary_normal_to_vec(&Position::new(vec![0.0, 2.0, 0.0]), &mut normal),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+        assert!((sys.volume()? - 16.0 * PI).abs() < 1e-12);
+        assert_eq!(sys.surface_area()?, 0.0);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-2.0; 3]), Position::new(vec![2.0; 3])));
+
+        let sys : ContSphereSurfaceSystem = ContSphereSurfaceSystem::new(2.0, 2);
+        assert!((sys.volume()? - 4.0 * PI).abs() < 1e-12);
+        Ok(())
+    }
+}
@@ -0,0 +1,908 @@
+// Module for crowded system
+// 임의의 시스템 안에 움직이지 않는 구형, 혹은 직육면체 장애물 (crowder)을 배치한 시스템.
+// agent는 장애물 표면에서 반사되고, random_pos는 장애물 바깥에서만 뽑는다.
+// 장애물은 uniform grid에 등록해 두고 한 step이 닿을 수 있는 근처의 장애물만 확인하므로,
+// 장애물이 많아도 step 당 계산량은 거의 일정하다.
+
+use crate::prelude::*;
+use crate::error::MAX_TRIAL;
+use crate::system_mod::{reflect_surface_exact, unit_ball_volume};
+
+
+// =====================================================================================
+// ===  Implement Obstacle =============================================================
+// =====================================================================================
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub enum Obstacle{                          // Static excluded-volume inclusion
+    Sphere(Position<f64>, f64),             // center, radius
+    Cuboid(Position<f64>, Position<f64>),   // center, half length of each axis
+}
+
+impl Obstacle{
+    pub fn center(&self) -> &Position<f64>{
+        match self{
+            Obstacle::Sphere(center, _) | Obstacle::Cuboid(center, _) => center,
+        }
+    }
+
+    pub fn dim(&self) -> usize{
+        self.center().dim()
+    }
+
+    // 같은 모양을 center로 옮긴 장애물
+    pub fn with_center(&self, center : Position<f64>) -> Self{
+        match self{
+            Obstacle::Sphere(_, r) => Obstacle::Sphere(center, *r),
+            Obstacle::Cuboid(_, half) => Obstacle::Cuboid(center, half.clone()),
+        }
+    }
+
+    // axis 방향으로 center에서 가장 먼 점까지의 거리
+    pub fn half_width(&self, axis : usize) -> f64{
+        match self{
+            Obstacle::Sphere(_, r) => *r,
+            Obstacle::Cuboid(_, half) => half[axis],
+        }
+    }
+
+    // 장애물의 dim 차원 부피
+    pub fn volume(&self) -> f64{
+        match self{
+            Obstacle::Sphere(center, r) => unit_ball_volume(center.dim()) * r.powi(center.dim() as i32),
+            Obstacle::Cuboid(_, half) => half.iter().map(|h| 2f64 * h).product(),
+        }
+    }
+
+    // 장애물 표면의 dim - 1 차원 넓이
+    pub fn surface_area(&self) -> f64{
+        match self{
+            Obstacle::Sphere(center, r) => {
+                let dim : usize = center.dim();
+                dim as f64 * unit_ball_volume(dim) * r.powi(dim as i32 - 1)
+            },
+            Obstacle::Cuboid(_, half) => {
+                // 축 i마다 넓이 volume / (2 h_i)인 면이 두 개
+                let volume : f64 = self.volume();
+                half.iter().map(|h| volume / h).sum()
+            },
+        }
+    }
+
+    // 장애물의 내부에 있는지 확인. 표면은 내부가 아니다.
+    pub fn contains(&self, pos : &[f64]) -> bool{
+        match self{
+            Obstacle::Sphere(center, r) => {
+                pos.iter().zip(center.iter()).map(|(x, c)| (x - c) * (x - c)).sum::<f64>() < r * r
+            },
+            Obstacle::Cuboid(center, half) => {
+                pos.iter().zip(center.iter()).zip(half.iter()).all(|((x, c), h)| (x - c).abs() < *h)
+            },
+        }
+    }
+
+    // 두 장애물이 겹치는지 확인
+    pub fn overlaps(&self, other : &Obstacle) -> bool{
+        match (self, other){
+            (Obstacle::Sphere(c1, r1), Obstacle::Sphere(c2, r2)) => {
+                c1.iter().zip(c2.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f64>() < (r1 + r2) * (r1 + r2)
+            },
+            (Obstacle::Cuboid(c1, h1), Obstacle::Cuboid(c2, h2)) => {
+                (0..c1.dim()).all(|i| (c1[i] - c2[i]).abs() < h1[i] + h2[i])
+            },
+            (Obstacle::Sphere(c, r), Obstacle::Cuboid(cb, h)) | (Obstacle::Cuboid(cb, h), Obstacle::Sphere(c, r)) => {
+                // 구의 중심에서 직육면체까지의 거리
+                let d2 : f64 = (0..c.dim()).map(|i| ((c[i] - cb[i]).abs() - h[i]).max(0f64).powi(2)).sum();
+                d2 < r * r
+            },
+        }
+    }
+
+    fn nearest_face(center : &Position<f64>, half : &Position<f64>, pos : &[f64]) -> usize{
+        // 직육면체에서 pos와 가장 가까운 면의 축
+        let mut axis : usize = 0;
+        let mut min : f64 = f64::INFINITY;
+        for i in 0..pos.len(){
+            let d : f64 = (half[i] - (pos[i] - center[i]).abs()).abs();
+            if d < min{
+                min = d;
+                axis = i;
+            }
+        }
+        axis
+    }
+
+    // 표면까지의 거리. 안쪽이면 음수
+    pub fn surface_distance(&self, pos : &[f64]) -> f64{
+        match self{
+            Obstacle::Sphere(center, r) => {
+                pos.iter().zip(center.iter()).map(|(x, c)| (x - c) * (x - c)).sum::<f64>().sqrt() - r
+            },
+            Obstacle::Cuboid(center, half) => {
+                let mut outside : f64 = 0f64;
+                let mut inside : f64 = f64::NEG_INFINITY;
+                for i in 0..pos.len(){
+                    let d : f64 = (pos[i] - center[i]).abs() - half[i];
+                    outside += d.max(0f64).powi(2);
+                    inside = inside.max(d);
+                }
+                if outside > 0f64 { outside.sqrt() } else { inside }
+            },
+        }
+    }
+
+    // 밖에서 pos부터 pos + dp까지 움직일 때 장애물 표면에 닿는 t in [0, 1]. 닿지 않으면 None
+    pub fn entry_time(&self, pos : &[f64], dp : &[f64]) -> Option<f64>{
+        match self{
+            Obstacle::Sphere(center, r) => {
+                let (mut a, mut b, mut c) : (f64, f64, f64) = (0f64, 0f64, 0f64);
+                for ((x, dx), x0) in pos.iter().zip(dp.iter()).zip(center.iter()){
+                    a += dx * dx;
+                    b += (x - x0) * dx;
+                    c += (x - x0) * (x - x0);
+                }
+                let disc : f64 = b * b - a * (c - r * r);
+                if b >= 0f64 || a == 0f64 || disc <= 0f64{
+                    return None;
+                }
+                let t : f64 = (- b - disc.sqrt()) / a;
+                if t <= 1f64 { Some(t.max(0f64)) } else { None }
+            },
+            Obstacle::Cuboid(center, half) => {
+                // slab method
+                let (mut t_near, mut t_far) : (f64, f64) = (f64::NEG_INFINITY, f64::INFINITY);
+                for i in 0..pos.len(){
+                    let (lower, upper) : (f64, f64) = (center[i] - half[i], center[i] + half[i]);
+                    if dp[i] == 0f64{
+                        if pos[i] <= lower || pos[i] >= upper{
+                            return None;
+                        }
+                        continue;
+                    }
+                    let (t1, t2) : (f64, f64) = ((lower - pos[i]) / dp[i], (upper - pos[i]) / dp[i]);
+                    t_near = t_near.max(t1.min(t2));
+                    t_far = t_far.min(t1.max(t2));
+                }
+                if t_near >= t_far || t_far <= 0f64 || t_near > 1f64{
+                    return None;
+                }
+                Some(t_near.max(0f64))
+            },
+        }
+    }
+
+    // 표면 근처의 점을 표면으로 옮기고, 그 점의 바깥 방향 unit normal을 normal에 기입
+    pub fn surface_normal(&self, pos : &mut [f64], normal : &mut [f64]){
+        match self{
+            Obstacle::Sphere(center, r) => {
+                let s : f64 = pos.iter().zip(center.iter()).map(|(x, c)| (x - c) * (x - c)).sum::<f64>().sqrt();
+                for ((x, n), c) in pos.iter_mut().zip(normal.iter_mut()).zip(center.iter()){
+                    *n = (*x - c) / s;
+                    *x = c + r * *n;
+                }
+            },
+            Obstacle::Cuboid(center, half) => {
+                let k : usize = Self::nearest_face(center, half, pos);
+                let sign : f64 = if pos[k] >= center[k] { 1f64 } else { -1f64 };
+                for n in normal.iter_mut(){
+                    *n = 0f64;
+                }
+                normal[k] = sign;
+                pos[k] = center[k] + sign * half[k];
+            },
+        }
+    }
+
+    // 장애물 안쪽의 점을 가장 가까운 표면에 대해 접어 바깥으로 보낸다. vel이 주어지면 법선 성분을 뒤집는다.
+    pub fn fold(&self, pos : &mut [f64], vel : Option<&mut [f64]>){
+        let mut surface : Vec<f64> = pos.to_vec();
+        let mut normal : Vec<f64> = vec![0f64; pos.len()];
+        self.surface_normal(&mut surface, &mut normal);
+        match self{
+            Obstacle::Sphere(center, r) => {
+                // 원형 시스템처럼 반지름 방향으로만 접는다.
+                let s : f64 = pos.iter().zip(center.iter()).map(|(x, c)| (x - c) * (x - c)).sum::<f64>().sqrt();
+                for (x, c) in pos.iter_mut().zip(center.iter()){
+                    *x = c + (*x - c) * (2f64 * r - s) / s;
+                }
+            },
+            Obstacle::Cuboid(_, _) => {
+                for ((x, y), n) in pos.iter_mut().zip(surface.iter()).zip(normal.iter()){
+                    if *n != 0f64{
+                        *x = 2f64 * y - *x;
+                    }
+                }
+            },
+        }
+        if let Some(v) = vel{
+            let vn : f64 = normal.iter().zip(v.iter()).map(|(n, vx)| n * vx).sum();
+            for (n, vx) in normal.iter().zip(v.iter_mut()){
+                *vx -= 2f64 * vn * n;
+            }
+        }
+    }
+}
+
+// ex) Sphere 1,2 0.5 / Cuboid 1,2 0.5,0.25
+impl Display for Obstacle{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            Obstacle::Sphere(center, r) => write!(f, "Sphere {} {}", center, r),
+            Obstacle::Cuboid(center, half) => write!(f, "Cuboid {} {}", center, half),
+        }
+    }
+}
+
+impl FromStr for Obstacle{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.len() != 3{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidFormat));
+        }
+        let center : Position<f64> = split[1].parse()?;
+        match split[0]{
+            "Sphere" => {
+                let r = split[2].parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidFormat))?;
+                if r <= 0f64{
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+                }
+                Ok(Obstacle::Sphere(center, r))
+            },
+            "Cuboid" => {
+                let half : Position<f64> = split[2].parse()?;
+                if half.dim() != center.dim() || half.iter().any(|&h| h <= 0f64){
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+                }
+                Ok(Obstacle::Cuboid(center, half))
+            },
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidFormat)),
+        }
+    }
+}
+
+
+// =====================================================================================
+// ===  Implement ObstacleGrid =========================================================
+// =====================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+struct ObstacleGrid{                        // Uniform grid for spatial indexing of obstacles
+    cell_size : f64,
+    cells : HashMap<Vec<i64>, Vec<usize>>,  // 각 cell과 겹치는 장애물의 index
+}
+
+impl ObstacleGrid{
+    fn new(cell_size : f64) -> Self{
+        ObstacleGrid{
+            cell_size,
+            cells : HashMap::new(),
+        }
+    }
+
+    fn cell_range(&self, lower : &[f64], upper : &[f64]) -> (Vec<i64>, Vec<i64>){
+        (lower.iter().map(|x| (x / self.cell_size).floor() as i64).collect(),
+         upper.iter().map(|x| (x / self.cell_size).floor() as i64).collect())
+    }
+
+    fn for_each_cell<F>(&self, lower : &[f64], upper : &[f64], mut f : F) where F : FnMut(&Vec<i64>){
+        // (lower, upper) box와 겹치는 모든 cell에 대해 f를 호출
+        let (min, max) = self.cell_range(lower, upper);
+        let mut key : Vec<i64> = min.clone();
+        loop{
+            f(&key);
+            let mut i : usize = 0;
+            while i < key.len(){
+                if key[i] < max[i]{
+                    key[i] += 1;
+                    break;
+                }
+                key[i] = min[i];
+                i += 1;
+            }
+            if i == key.len(){
+                return;
+            }
+        }
+    }
+
+    fn num_cells(&self, lower : &[f64], upper : &[f64]) -> f64{
+        let (min, max) = self.cell_range(lower, upper);
+        min.iter().zip(max.iter()).map(|(a, b)| (b - a + 1) as f64).product()
+    }
+
+    fn insert(&mut self, idx : usize, obstacle : &Obstacle){
+        let dim : usize = obstacle.dim();
+        let center : &Position<f64> = obstacle.center();
+        let lower : Vec<f64> = (0..dim).map(|i| center[i] - obstacle.half_width(i)).collect();
+        let upper : Vec<f64> = (0..dim).map(|i| center[i] + obstacle.half_width(i)).collect();
+        let mut keys : Vec<Vec<i64>> = Vec::new();
+        self.for_each_cell(&lower, &upper, |key| keys.push(key.clone()));
+        for key in keys{
+            self.cells.entry(key).or_default().push(idx);
+        }
+    }
+
+    fn candidates(&self, lower : &[f64], upper : &[f64], num_obstacle : usize, vec : &mut Vec<usize>){
+        // (lower, upper) box와 겹칠 수 있는 장애물의 index를 vec에 기입
+        vec.clear();
+        if self.num_cells(lower, upper) > num_obstacle as f64{
+            // 확인할 cell이 장애물보다 많으면 모두 확인하는 것이 빠르다.
+            vec.extend(0..num_obstacle);
+            return;
+        }
+        self.for_each_cell(lower, upper, |key| {
+            if let Some(list) = self.cells.get(key){
+                vec.extend(list.iter());
+            }
+        });
+        vec.sort_unstable();
+        vec.dedup();
+    }
+}
+
+
+// =====================================================================================
+// ===  Implement CrowdedSystem ========================================================
+// =====================================================================================
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrowdedSystem<S>{            // base system with static obstacles
+    pub base : S,                       // 장애물이 없을 때의 시스템
+    pub obstacles : Vec<Obstacle>,      // 장애물 목록
+    pub dim : usize,                    // dimension of system
+    pub reflection : ReflectionType,    // Accuracy of reflection at obstacles
+    grid : ObstacleGrid,                // spatial index of obstacles
+}
+
+impl<S : SystemCore<f64>> CrowdedSystem<S>{
+    pub fn new(base : S, obstacles : Vec<Obstacle>) -> Result<Self, Error>{
+        // base : 장애물이 없을 때의 시스템
+        // obstacles : 장애물 목록. 서로 겹치지 않는다고 가정한다.
+        let dim : usize = base.position_out_of_system().dim();
+        if obstacles.iter().any(|x| x.dim() != dim){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        let mut max_width : f64 = 0f64;
+        for x in &obstacles{
+            for i in 0..dim{
+                max_width = max_width.max(x.half_width(i));
+            }
+        }
+        let mut grid = ObstacleGrid::new(if max_width > 0f64 { 2f64 * max_width } else { 1f64 });
+        for (idx, x) in obstacles.iter().enumerate(){
+            grid.insert(idx, x);
+        }
+
+        Ok(CrowdedSystem{
+            reflection : base.reflection(),
+            base,
+            obstacles,
+            dim,
+            grid,
+        })
+    }
+
+    pub fn new_random(base : S, rng : &mut Pcg64, shape : &Obstacle, num_obstacle : usize) -> Result<Self, Error>{
+        // 서로 겹치지 않고 base 안에 완전히 들어가도록 장애물을 임의로 배치
+        // shape : 장애물의 모양과 크기. center는 무시한다.
+        // num_obstacle : 장애물의 수
+        let mut sys : Self = Self::new(base, vec![shape.clone()])?;
+        sys.obstacles.clear();
+        sys.grid.cells.clear();
+
+        let mut candidates : Vec<usize> = Vec::new();
+        for idx in 0..num_obstacle{
+            let mut placed : bool = false;
+            for _i in 0..MAX_TRIAL{
+                let obstacle : Obstacle = shape.with_center(sys.base.random_pos(rng)?);
+                if !sys.check_obstacle_inside(&obstacle)?{
+                    continue;
+                }
+                let center : &Position<f64> = obstacle.center();
+                let lower : Vec<f64> = (0..sys.dim).map(|i| center[i] - obstacle.half_width(i)).collect();
+                let upper : Vec<f64> = (0..sys.dim).map(|i| center[i] + obstacle.half_width(i)).collect();
+                sys.grid.candidates(&lower, &upper, sys.obstacles.len(), &mut candidates);
+                if candidates.iter().any(|&j| sys.obstacles[j].overlaps(&obstacle)){
+                    continue;
+                }
+
+                sys.grid.insert(idx, &obstacle);
+                sys.obstacles.push(obstacle);
+                placed = true;
+                break;
+            }
+            if !placed{
+                return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+            }
+        }
+        Ok(sys)
+    }
+
+    pub fn from_file<P>(base : S, path : P) -> Result<Self, Error>
+        where P : AsRef<Path>{
+        // 한 줄에 장애물 하나씩 적힌 파일에서 장애물을 읽는다. 빈 줄과 #으로 시작하는 줄은 무시한다.
+        // ex) Sphere 1,2 0.5
+        //     Cuboid -1,0 0.5,0.25
+        let f = File::open(path).map_err(Error::make_error_io)?;
+        let mut obstacles : Vec<Obstacle> = Vec::new();
+        for line in BufReader::new(f).lines(){
+            let line : String = line.map_err(Error::make_error_io)?;
+            let line : &str = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+            obstacles.push(line.parse()?);
+        }
+        Self::new(base, obstacles)
+    }
+
+    pub fn export_obstacles<W : Write>(&self, writer : &mut W) -> Result<(), Error>{
+        // from_file로 다시 읽을 수 있는 형식으로 장애물을 기록
+        for x in &self.obstacles{
+            writeln!(writer, "{}", x).map_err(Error::make_error_io)?;
+        }
+        Ok(())
+    }
+
+    // 장애물이 차지하는 부피의 비율을 구할 때 등에 사용
+    pub fn num_obstacle(&self) -> usize{
+        self.obstacles.len()
+    }
+
+    fn check_obstacle_inside(&self, obstacle : &Obstacle) -> Result<bool, Error>{
+        // 장애물의 각 축 방향 끝점 (직육면체는 꼭짓점)이 모두 base 안에 있는지 확인
+        let center : &Position<f64> = obstacle.center();
+        let mut pos : Position<f64> = center.clone();
+        match obstacle{
+            Obstacle::Cuboid(_, half) if self.dim < 16 => {
+                for corner in 0..(1usize << self.dim){
+                    for i in 0..self.dim{
+                        pos[i] = center[i] + if corner & (1 << i) == 0 { - half[i] } else { half[i] };
+                    }
+                    if !self.base.check_inclusion(&pos)?{
+                        return Ok(false);
+                    }
+                }
+            },
+            _ => {
+                for i in 0..self.dim{
+                    for &sign in [-1f64, 1f64].iter(){
+                        pos[i] = center[i] + sign * obstacle.half_width(i);
+                        if !self.base.check_inclusion(&pos)?{
+                            return Ok(false);
+                        }
+                    }
+                    pos[i] = center[i];
+                }
+            },
+        }
+        Ok(true)
+    }
+
+    fn near_obstacles(&self, pos : &[f64], radius : f64, vec : &mut Vec<usize>){
+        // pos에서 radius 이내에 있을 수 있는 장애물
+        if self.obstacles.is_empty(){
+            vec.clear();
+            return;
+        }
+        let lower : Vec<f64> = pos.iter().map(|x| x - radius).collect();
+        let upper : Vec<f64> = pos.iter().map(|x| x + radius).collect();
+        self.grid.candidates(&lower, &upper, self.obstacles.len(), vec);
+    }
+
+    // pos를 포함하는 장애물의 index
+    pub fn obstacle_at(&self, pos : &Position<f64>) -> Option<usize>{
+        let mut candidates : Vec<usize> = Vec::new();
+        self.near_obstacles(&pos.coordinate, 0f64, &mut candidates);
+        candidates.into_iter().find(|&i| self.obstacles[i].contains(&pos.coordinate))
+    }
+
+    fn nearest_boundary(&self, pos : &Position<f64>) -> Result<(f64, Option<usize>), Error>{
+        // 가장 가까운 경계까지의 signed distance와, 그 경계가 장애물이면 장애물의 index
+        let base : f64 = self.base.signed_distance(pos)?;
+        if base < 0f64{
+            return Ok((base, None));
+        }
+        let mut candidates : Vec<usize> = Vec::new();
+        if base.is_finite(){
+            self.near_obstacles(&pos.coordinate, base, &mut candidates);
+        }
+        else{
+            candidates.extend(0..self.obstacles.len());
+        }
+        let mut nearest : (f64, Option<usize>) = (base, None);
+        for idx in candidates{
+            let distance : f64 = self.obstacles[idx].surface_distance(&pos.coordinate);
+            if distance < nearest.0{
+                nearest = (distance, Some(idx));
+            }
+        }
+        Ok(nearest)
+    }
+
+    fn check_bc_inner(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, mut vel: Option<&mut Position<f64>>,
+                      absorb: Option<(&mut Pcg64, f64, f64)>) -> Result<bool, Error>{
+        // 장애물에서 먼저 반사시킨 후, 전체 변위에 대해 base의 boundary condition을 적용한다.
+        // 흡수되면 true를 반환하고 pos는 움직이기 전 그대로 둔다.
+        if self.dim != pos.dim() || self.dim != dp.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        // 한 step 동안 반사되어도 시작점에서 |dp| 이상 멀어지지 않는다.
+        let mut candidates : Vec<usize> = Vec::new();
+        self.near_obstacles(&pos.coordinate, dp.norm(), &mut candidates);
+        let start : Position<f64> = pos.clone();
+        if !candidates.is_empty(){
+            let obstacles : Vec<&Obstacle> = candidates.iter().map(|&i| &self.obstacles[i]).collect();
+            // Approximate : 가장 가까운 표면에 대해 한번 접는다. 접은 위치가 다른 장애물 안이면
+            //               (장애물 사이의 틈이 변위보다 좁은 경우) exact reflection으로 계산한다.
+            //               끝점이 장애물 밖이어도 선분이 장애물을 관통하면 exact reflection으로 계산한다.
+            let mut exact : bool = self.reflection == ReflectionType::Exact;
+            if !exact{
+                let vel_start : Option<Position<f64>> = vel.as_deref().cloned();
+                pos.mut_add(dp);
+                match obstacles.iter().find(|o| o.contains(&pos.coordinate)){
+                    Some(o) => {
+                        o.fold(&mut pos.coordinate, vel.as_deref_mut().map(|v| &mut v.coordinate[..]));
+                        if obstacles.iter().any(|o| o.contains(&pos.coordinate)){
+                            pos.clone_from(&start);
+                            if let (Some(v), Some(v0)) = (vel.as_deref_mut(), vel_start){
+                                v.clone_from(&v0);
+                            }
+                            exact = true;
+                        }
+                    },
+                    None => {
+                        if obstacles.iter().any(|o| o.entry_time(&start.coordinate, &dp.coordinate).is_some()){
+                            pos.clone_from(&start);
+                            exact = true;
+                        }
+                    },
+                }
+            }
+            if exact{
+                if let Err(e) = reflect_surface_exact(&mut pos.coordinate, &mut dp.coordinate, vel.as_deref_mut().map(|v| &mut v.coordinate[..]),
+                    |x, dx| obstacles.iter().filter_map(|o| o.entry_time(x, dx)).fold(None, |t : Option<f64>, s| Some(t.map_or(s, |t| t.min(s)))),
+                    |x, normal| {
+                        let nearest = obstacles.iter().min_by(|a, b| a.surface_distance(x).abs()
+                                        .total_cmp(&b.surface_distance(x).abs())).unwrap();
+                        nearest.surface_normal(x, normal);
+                    }){
+                    pos.clone_from(&start);
+                    return Err(e);
+                }
+            }
+
+            // 장애물에서 반사된 후의 전체 변위
+            for i in 0..self.dim{
+                dp[i] = pos[i] - start[i];
+            }
+            pos.clone_from(&start);
+        }
+
+        match absorb{
+            Some((rng, coeff_diff, dt)) => {
+                if self.base.check_bc_absorb(rng, pos, dp, coeff_diff, dt)?{
+                    return Ok(true);
+                }
+            },
+            None => match vel.as_deref_mut(){
+                Some(v) => self.base.check_bc_with_velocity(pos, dp, v)?,
+                None => self.base.check_bc(pos, dp)?,
+            },
+        }
+
+        // 벽에서 반사되거나 periodic boundary를 넘어 장애물 안으로 들어간 경우
+        if let Some(i) = self.obstacle_at(pos){
+            self.obstacles[i].fold(&mut pos.coordinate, vel.map(|v| &mut v.coordinate[..]));
+            if self.obstacle_at(pos).is_some() || !self.base.check_inclusion(pos)?{
+                pos.clone_from(&start);
+                return Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep));
+            }
+        }
+        Ok(false)
+    }
+}
+
+
+impl<S : SystemCore<f64>> SystemCore<f64> for CrowdedSystem<S>{
+    fn check_inclusion(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        // base 안에 있고 어떤 장애물 안에도 있지 않은지 확인
+        Ok(self.base.check_inclusion(pos)? && self.obstacle_at(pos).is_none())
+    }
+
+    fn check_bc(&self, pos: &mut Position<f64>, dp: &mut Position<f64>) -> Result<(), Error>{
+        // check boundary condition
+        // pos : initial position of ptl
+        // dp : displacement of ptl
+        self.check_bc_inner(pos, dp, None, None).map(|_| ())
+    }
+
+    fn check_bc_with_velocity(&self, pos: &mut Position<f64>, dp: &mut Position<f64>, vel: &mut Position<f64>) -> Result<(), Error>{
+        // 장애물과 벽에서 반사될 때마다 법선 방향 velocity 성분을 뒤집는다.
+        if vel.dim() != self.dim{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        self.check_bc_inner(pos, dp, Some(vel), None).map(|_| ())
+    }
+
+    fn check_bc_absorb(&self, rng: &mut Pcg64, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                       coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+        // 장애물은 항상 반사하고, base의 흡수 경계에 닿으면 true
+        self.check_bc_inner(pos, dp, None, Some((rng, coeff_diff, dt)))
+    }
+
+    fn reflection(&self) -> ReflectionType{
+        self.reflection
+    }
+
+    fn set_reflection(&mut self, rtype: ReflectionType) -> Result<(), Error>{
+        // 장애물과 base 모두 같은 방식으로 반사시킨다.
+        self.base.set_reflection(rtype)?;
+        self.reflection = rtype;
+        Ok(())
+    }
+
+    fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<f64>, Error>{
+        // 장애물 바깥의 임의의 위치를 uniform하게 뽑아 반환
+        let mut pos : Position<f64> = Position::new(vec![0f64; self.dim]);
+        self.random_pos_to_vec(rng, &mut pos)?;
+        Ok(pos)
+    }
+
+    fn random_pos_to_vec(&self, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>{
+        // base에서 uniform하게 뽑은 위치 중 장애물 바깥에 있는 것을 택한다.
+        for _i in 0..MAX_TRIAL{
+            self.base.random_pos_to_vec(rng, vec)?;
+            if self.obstacle_at(vec).is_none(){
+                return Ok(());
+            }
+        }
+        Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration))
+    }
+
+    fn position_out_of_system(&self) -> Position<f64>{
+        self.base.position_out_of_system()
+    }
+
+    fn position_out_of_system_to_vec(&self, vec: &mut Position<f64>) -> Result<(), Error>{
+        self.base.position_out_of_system_to_vec(vec)
+    }
+
+    fn signed_distance(&self, pos: &Position<f64>) -> Result<f64, Error>{
+        // base의 경계와 장애물 표면 중 가까운 것까지의 거리. 장애물 안쪽은 시스템 밖이므로 음수
+        // 장애물은 base 안에 있으므로 base 밖의 점은 base까지의 거리를 그대로 쓴다.
+        let (distance, _) = self.nearest_boundary(pos)?;
+        Ok(distance)
+    }
+
+    fn boundary_normal_to_vec(&self, pos: &Position<f64>, normal: &mut Position<f64>) -> Result<(), Error>{
+        // 장애물 표면에서는 장애물의 바깥 방향 normal의 반대 방향이 시스템의 바깥 방향이다.
+        if self.dim != normal.dim(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        match self.nearest_boundary(pos)?{
+            (_, Some(idx)) => {
+                let mut surface : Vec<f64> = pos.coordinate.clone();
+                self.obstacles[idx].surface_normal(&mut surface, &mut normal.coordinate);
+                normal.mut_scalar_mul(-1f64);
+                Ok(())
+            },
+            (_, None) => self.base.boundary_normal_to_vec(pos, normal),
+        }
+    }
+
+    fn volume(&self) -> Result<f64, Error>{
+        // 장애물은 서로 겹치지 않고 base 안에 있다.
+        Ok(self.base.volume()? - self.obstacles.iter().map(|x| x.volume()).sum::<f64>())
+    }
+
+    fn surface_area(&self) -> Result<f64, Error>{
+        Ok(self.base.surface_area()? + self.obstacles.iter().map(|x| x.surface_area()).sum::<f64>())
+    }
+
+    fn bounding_box(&self) -> Result<(Position<f64>, Position<f64>), Error>{
+        self.base.bounding_box()
+    }
+
+    fn is_periodic(&self, axis: usize) -> bool{
+        self.base.is_periodic(axis)
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_obstacle() -> Result<(), Error>{
+        let sphere : Obstacle = "Sphere 1,2 0.5".parse()?;
+        let cuboid : Obstacle = "Cuboid 1:0 0.5:0.25".parse()?;
+        assert_eq!(sphere, Obstacle::Sphere(Position::new(vec![1.0, 2.0]), 0.5));
+        assert_eq!(cuboid, Obstacle::Cuboid(Position::new(vec![1.0, 0.0]), Position::new(vec![0.5, 0.25])));
+        assert_eq!(format!("{}", cuboid), "Cuboid 1,0 0.5,0.25");
+        assert_eq!(format!("{}", sphere).parse::<Obstacle>()?, sphere);
+        assert!("Sphere 1,2".parse::<Obstacle>().is_err());
+        assert!("Cuboid 1,2 0.5".parse::<Obstacle>().is_err());
+        assert!("Cone 1,2 0.5".parse::<Obstacle>().is_err());
+
+        assert!(sphere.contains(&[1.2, 2.2]) && !sphere.contains(&[1.5, 2.0]));
+        assert!(cuboid.contains(&[1.4, 0.2]) && !cuboid.contains(&[1.4, 0.3]));
+        assert!(!sphere.overlaps(&cuboid));
+        assert!(sphere.overlaps(&Obstacle::Cuboid(Position::new(vec![1.0, 1.0]), Position::new(vec![0.5, 0.6]))));
+        assert!(!sphere.overlaps(&Obstacle::Sphere(Position::new(vec![2.0, 2.0]), 0.5)));
+        assert!(cuboid.overlaps(&Obstacle::Cuboid(Position::new(vec![1.9, 0.0]), Position::new(vec![0.5, 0.25]))));
+
+        assert_eq!(sphere.entry_time(&[0.0, 2.0], &[1.0, 0.0]), Some(0.5));
+        assert_eq!(sphere.entry_time(&[0.0, 2.0], &[-1.0, 0.0]), None);
+        assert_eq!(cuboid.entry_time(&[1.0, 1.0], &[0.0, -1.0]), Some(0.75));
+        assert_eq!(cuboid.entry_time(&[0.0, 1.0], &[0.0, -1.0]), None);
+        assert!((cuboid.surface_distance(&[1.0, 1.0]) - 0.75).abs() < 1e-12);
+        assert!((cuboid.surface_distance(&[1.0, 0.1]) + 0.15).abs() < 1e-12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_bc() -> Result<(), Error>{
+        let base : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Reflection, 5.0, 2);
+        let obstacles : Vec<Obstacle> = vec![Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 1.0),
+                                             Obstacle::Cuboid(Position::new(vec![3.0, 0.0]), Position::new(vec![0.5, 1.0]))];
+        let mut sys = CrowdedSystem::new(base, obstacles)?;
+        assert_eq!(sys.dim, 2);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![0.5, 0.5]))?, false);
+        assert_eq!(sys.check_inclusion(&Position::new(vec![1.5, 0.0]))?, true);
+        assert_eq!(sys.obstacle_at(&Position::new(vec![3.2, 0.5])), Some(1));
+
+        for &rtype in [ReflectionType::Approximate, ReflectionType::Exact].iter(){
+            sys.set_reflection(rtype)?;
+            assert_eq!(sys.base.reflection, rtype);
+
+            // 구에서 반사
+            let mut pos : Position<f64> = Position::new(vec![-2.0, 0.0]);
+            let mut dp : Position<f64> = Position::new(vec![1.5, 0.0]);
+            sys.check_bc(&mut pos, &mut dp)?;
+            assert!((&pos - &Position::new(vec![-1.5, 0.0])).norm() < 1e-12, "{:?}", pos);
+
+            // 직육면체에서 반사
+            let mut pos : Position<f64> = Position::new(vec![3.0, 2.0]);
+            let mut dp : Position<f64> = Position::new(vec![0.0, -1.2]);
+            let mut vel : Position<f64> = Position::new(vec![1.0, -1.0]);
+            sys.check_bc_with_velocity(&mut pos, &mut dp, &mut vel)?;
+            assert!((&pos - &Position::new(vec![3.0, 1.2])).norm() < 1e-12, "{:?}", pos);
+            assert_eq!(vel, Position::new(vec![1.0, 1.0]));
+
+            // 벽에서 반사
+            let mut pos : Position<f64> = Position::new(vec![-4.5, 4.0]);
+            let mut dp : Position<f64> = Position::new(vec![-1.0, 0.0]);
+            sys.check_bc(&mut pos, &mut dp)?;
+            assert!((&pos - &Position::new(vec![-4.5, 4.0])).norm() < 1e-12, "{:?}", pos);
+
+            // 끝점이 장애물 밖이어도 관통하지 않는다
+            let mut pos : Position<f64> = Position::new(vec![-1.5, 0.0]);
+            let mut dp : Position<f64> = Position::new(vec![3.0, 0.0]);
+            sys.check_bc(&mut pos, &mut dp)?;
+            assert!((&pos - &Position::new(vec![-3.5, 0.0])).norm() < 1e-12, "{:?}", pos);
+        }
+
+        // 구와 직육면체 사이에서 여러번 반사
+        let mut pos : Position<f64> = Position::new(vec![1.5, 0.0]);
+        let mut dp : Position<f64> = Position::new(vec![2.0, 0.0]);
+        sys.check_bc(&mut pos, &mut dp)?;
+        assert!((&pos - &Position::new(vec![1.5, 0.0])).norm() < 1e-12, "{:?}", pos);
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_random() -> Result<(), Error>{
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let base : ContCircSystem = ContCircSystem::new(10.0, 2);
+        let sys = CrowdedSystem::new_random(base, &mut rng, &Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 0.5), 100)?;
+        assert_eq!(sys.num_obstacle(), 100);
+        for (i, x) in sys.obstacles.iter().enumerate(){
+            assert!(sys.check_obstacle_inside(x)?);
+            for y in sys.obstacles.iter().skip(i + 1){
+                assert!(!x.overlaps(y));
+            }
+        }
+
+        // grid를 통해 찾은 결과와 모든 장애물을 확인한 결과가 같다.
+        for _i in 0..1000{
+            let pos : Position<f64> = sys.base.random_pos(&mut rng)?;
+            let brute : Option<usize> = sys.obstacles.iter().position(|x| x.contains(&pos.coordinate));
+            assert_eq!(sys.obstacle_at(&pos), brute);
+        }
+        for _i in 0..1000{
+            let pos : Position<f64> = sys.random_pos(&mut rng)?;
+            assert!(sys.check_inclusion(&pos)?);
+        }
+
+        // 너무 많은 장애물은 배치할 수 없다.
+        let base : ContCircSystem = ContCircSystem::new(1.0, 2);
+        let res = CrowdedSystem::new_random(base, &mut rng, &Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 0.5), 10);
+        assert_eq!(res.map(|_| ()), Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_diffusion() -> Result<(), Error>{
+        // 장애물 사이를 움직이는 agent는 항상 장애물 밖에 있다.
+        use crate::random_mod::get_gaussian_to_vec_nonstandard;
+
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let base : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Periodic, 5.0, 2);
+        let mut sys = CrowdedSystem::new_random(base, &mut rng, &Obstacle::Cuboid(Position::new(vec![0.0, 0.0]),
+                                                Position::new(vec![0.5, 0.3])), 30)?;
+        let mut dp : Position<f64> = Position::new(vec![0.0, 0.0]);
+        for &rtype in [ReflectionType::Approximate, ReflectionType::Exact].iter(){
+            sys.set_reflection(rtype)?;
+            let mut pos : Position<f64> = sys.random_pos(&mut rng)?;
+            for _i in 0..10000{
+                dp.clear();
+                get_gaussian_to_vec_nonstandard(&mut rng, &mut dp, 0f64, 0.1);
+                sys.check_bc(&mut pos, &mut dp)?;
+                assert!(sys.check_inclusion(&pos)?, "{:?}", pos);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file() -> Result<(), Error>{
+        let mut rng : Pcg64 = rng_seed(12341234);
+        let base : ContCircSystem = ContCircSystem::new(5.0, 2);
+        let sys = CrowdedSystem::new_random(base.clone(), &mut rng, &Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 0.3), 20)?;
+
+        let path = std::env::temp_dir().join("moledyn_test_crowded.dat");
+        {
+            let mut writer = BufWriter::new(File::create(&path).map_err(Error::make_error_io)?);
+            write!(&mut writer, "# obstacles\n\n").map_err(Error::make_error_io)?;
+            sys.export_obstacles(&mut writer)?;
+        }
+        let sys2 = CrowdedSystem::from_file(base, &path)?;
+        fs::remove_file(&path).map_err(Error::make_error_io)?;
+        assert_eq!(sys2.num_obstacle(), 20);
+        for (x, y) in sys.obstacles.iter().zip(sys2.obstacles.iter()){
+            assert!((x.center() - y.center()).norm() < 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_geometry() -> Result<(), Error>{
+        use std::f64::consts::PI;
+        let base : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Reflection, 5.0, 2);
+        let obstacles : Vec<Obstacle> = vec![Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 1.0),
+                                             Obstacle::Cuboid(Position::new(vec![3.0, 0.0]), Position::new(vec![0.5, 1.0]))];
+        let sys = CrowdedSystem::new(base, obstacles)?;
+        let mut normal : Position<f64> = Position::new(vec![0.0; 2]);
+
+        // 장애물 표면에서의 normal은 장애물 쪽을 향한다.
+        assert!((sys.signed_distance(&Position::new(vec![1.5, 0.0]))? - 0.5).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![1.5, 0.0]), &mut normal)?;
+        assert!((&normal - &Position::new(vec![-1.0, 0.0])).norm() < 1e-12);
+        assert!((sys.signed_distance(&Position::new(vec![2.3, 0.0]))? - 0.2).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![2.3, 0.0]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![1.0, 0.0]));
+        assert!((sys.signed_distance(&Position::new(vec![0.5, 0.0]))? + 0.5).abs() < 1e-12);
+
+        // base의 벽
+        assert!((sys.signed_distance(&Position::new(vec![-4.8, 0.0]))? - 0.2).abs() < 1e-12);
+        sys.boundary_normal_to_vec(&Position::new(vec![-4.8, 0.0]), &mut normal)?;
+        assert_eq!(normal, Position::new(vec![-1.0, 0.0]));
+        assert!((sys.signed_distance(&Position::new(vec![6.0, 0.0]))? + 1.0).abs() < 1e-12);
+
+        assert!((sys.volume()? - (100.0 - PI - 2.0)).abs() < 1e-12);
+        assert!((sys.surface_area()? - (40.0 + 2.0 * PI + 6.0)).abs() < 1e-12);
+        assert_eq!(sys.bounding_box()?, (Position::new(vec![-5.0; 2]), Position::new(vec![5.0; 2])));
+
+        // 경계가 없는 base에서는 장애물만 경계이다.
+        let base : ContCubicSystem = ContCubicSystem::new(BoundaryCond::Periodic, 5.0, 2);
+        let sys = CrowdedSystem::new(base, vec![Obstacle::Sphere(Position::new(vec![0.0, 0.0]), 1.0)])?;
+        assert!((sys.signed_distance(&Position::new(vec![-4.8, 0.0]))? - 3.8).abs() < 1e-12);
+        Ok(())
+    }
+}
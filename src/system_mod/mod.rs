@@ -5,6 +5,7 @@
 //
 
 use crate::prelude::*;
+use std::cmp::Ordering;
 
 // =====================================================================================
 // ===  Implement System ===============================================================
@@ -19,6 +20,37 @@ pub trait SystemCore<T>{
     // Error : 변위가 너무 커서 bc를 계산하는 것이 의미가 없어지는 경우.
     fn check_bc(&self, pos: &mut Position<T>, dp: &mut Position<T>) -> Result<(), Error>;
 
+    // check_bc와 같지만 velocity가 있는 ptl을 위해 반사된 경계에서 velocity의 법선 성분도 뒤집는다.
+    // pos : 원래 위치, dp : 미소변위, vel : ptl의 velocity
+    // 시스템이 지원하지 않으면 FeatureNotProvided
+    fn check_bc_with_velocity(&self, _pos: &mut Position<T>, _dp: &mut Position<T>, _vel: &mut Position<T>) -> Result<(), Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // check_bc와 같지만 흡수 경계에서 agent가 사라졌으면 true를 반환한다. 이때 pos는 움직이기 전 그대로 둔다.
+    // PartiallyAbsorbing(kappa) 경계에 부딪히면 kappa sqrt(pi dt / D)의 확률로 흡수되고, 아니면 반사된다.
+    // rng : random number generator, coeff_diff : diffusion coefficient, dt : step 크기
+    // 흡수 경계가 없는 시스템은 check_bc를 그대로 사용한다.
+    fn check_bc_absorb(&self, _rng: &mut Pcg64, pos: &mut Position<T>, dp: &mut Position<T>,
+                       _coeff_diff: f64, _dt: f64) -> Result<bool, Error>{
+        self.check_bc(pos, dp)?;
+        Ok(false)
+    }
+
+    // Reflection을 계산하는 방식
+    // 반사 경계가 없는 시스템은 Approximate를 반환한다.
+    fn reflection(&self) -> ReflectionType{
+        ReflectionType::Approximate
+    }
+
+    // Reflection을 계산하는 방식을 바꾼다.
+    // Approximate : 경계 밖으로 나간 만큼 접어 넣는다. 빠르지만 step이 크면 TooLargeTimeStep
+    // Exact : 경로가 경계와 만나는 점을 찾아 specular reflection을 반복한다. 여러번 부딪혀도 된다.
+    // 방식을 고를 수 없는 시스템은 FeatureNotProvided error를 반환한다.
+    fn set_reflection(&mut self, _rtype: ReflectionType) -> Result<(), Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
     // System 내부의 임의의 위치를 uniform하게 뽑아 반환
     fn random_pos(&self, rng: &mut Pcg64) -> Result<Position<T>, Error>;
 
@@ -30,11 +62,51 @@ pub trait SystemCore<T>{
 
     // system 밖의 점을 하나 vector에 적어주는 함수
     fn position_out_of_system_to_vec(&self, vec: &mut Position<T>) -> Result<(), Error>;
+
+    // pos에서 가장 가까운 경계까지의 거리. 시스템 안에서 양수, 밖에서 음수이다.
+    // periodic한 방향으로는 경계가 없다고 보며, 경계가 전혀 없으면 f64::INFINITY
+    // 시스템이 지원하지 않으면 FeatureNotProvided
+    fn signed_distance(&self, _pos: &Position<T>) -> Result<f64, Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // pos에서 가장 가까운 경계 위의 점에서 바깥 방향 unit normal을 normal에 기입
+    // 경계가 전혀 없으면 InvalidConfiguration
+    fn boundary_normal_to_vec(&self, _pos: &Position<T>, _normal: &mut Position<T>) -> Result<(), Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // 시스템의 dim 차원 부피
+    fn volume(&self) -> Result<f64, Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // 경계의 dim - 1 차원 넓이. periodic한 면은 경계가 아니므로 포함하지 않는다.
+    fn surface_area(&self) -> Result<f64, Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // 시스템을 포함하는 가장 작은 직육면체의 (하한, 상한)
+    fn bounding_box(&self) -> Result<(Position<T>, Position<T>), Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
+
+    // axis 방향으로 periodic한지 확인. periodic한 방향은 bounding_box의 양 끝이 이어져 있다.
+    fn is_periodic(&self, _axis: usize) -> bool{
+        false
+    }
 }
 
 pub mod cont_circ;
 pub mod cont_cubic;
 pub mod cont_cyl;
+pub mod cont_rect;
+pub mod cont_shell;
+pub mod cont_ellip;
+pub mod cont_polygon;
+pub mod cont_mesh;
+pub mod cont_surface;
+pub mod crowded;
 
 
 // =====================================================================================
@@ -47,6 +119,11 @@ pub enum SystemType{                        // System type
     ContinuousCircular,
     ContinuousRectangular,
     ContinuousCylindrical(usize),
+    ContinuousShell,                        // 두 반지름 사이의 annulus, spherical shell
+    ContinuousEllipsoidal,
+    ContinuousPolygonal,                    // 꼭짓점 파일로 주어진 2D polygon
+    ContinuousMesh,                         // 닫힌 triangle mesh로 주어진 3D 시스템
+    ContinuousSphereSurface,                // n-sphere의 표면
     Lattice,
     Network,
 }
@@ -58,6 +135,11 @@ impl Display for SystemType{
             SystemType::ContinuousCircular => write!(f, "Continuous Circular system."),
             SystemType::ContinuousRectangular => write!(f, "Continuous Rectangular system."),
             SystemType::ContinuousCylindrical(d) => write!(f, "Continuous Cylindrical system. 0..{0:} : Circular, {0:}.. : Rectangular", d),
+            SystemType::ContinuousShell => write!(f, "Continuous Shell system."),
+            SystemType::ContinuousEllipsoidal => write!(f, "Continuous Ellipsoidal system."),
+            SystemType::ContinuousPolygonal => write!(f, "Continuous Polygonal system."),
+            SystemType::ContinuousMesh => write!(f, "Continuous Mesh system."),
+            SystemType::ContinuousSphereSurface => write!(f, "Continuous Sphere Surface system."),
             SystemType::Lattice => write!(f, "Lattice system."),
             SystemType::Network => write!(f, "Network system."),
         }
@@ -75,6 +157,11 @@ impl FromStr for SystemType{
                 match split[1]{
                     "Circular" => Ok(SystemType::ContinuousCircular),
                     "Rectangular" => Ok(SystemType::ContinuousRectangular),
+                    "Shell" => Ok(SystemType::ContinuousShell),
+                    "Ellipsoidal" => Ok(SystemType::ContinuousEllipsoidal),
+                    "Polygonal" => Ok(SystemType::ContinuousPolygonal),
+                    "Mesh" => Ok(SystemType::ContinuousMesh),
+                    "Sphere" => Ok(SystemType::ContinuousSphereSurface),
                     "Cylindrical" => {
                         let d = split[3][3..].parse::<usize>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
                         Ok(SystemType::ContinuousCylindrical(d))
@@ -111,11 +198,61 @@ impl Default for SystemType{
 // =====================================================================================
 
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd)]
+#[derive(Clone, Debug)]
 pub enum BoundaryCond{                      // Boundary condition
     Periodic,                               // Only valid for Rectanuglar system or Lattice
     Reflection,                             //
     Mixed(usize),                           // Cylindrical system 등에서 각 dimension마다 boundary condition이 다를 수도 있다
+    Absorbing,                              // 경계에 닿으면 agent가 사라진다
+    PartiallyAbsorbing(f64),                // Robin boundary. -D dc/dn = kappa c
+    PerFace(FaceBoundary),                  // Box system에서 각 축의 양쪽 면마다 다른 boundary condition
+}
+
+// kappa는 total_cmp로 비교해서 f64를 담고 있어도 Eq, Ord를 유지한다. 순서는 variant를 선언한 순서.
+impl Ord for BoundaryCond{
+    fn cmp(&self, other: &Self) -> Ordering{
+        fn rank(bctype: &BoundaryCond) -> usize{
+            match bctype{
+                BoundaryCond::Periodic => 0,
+                BoundaryCond::Reflection => 1,
+                BoundaryCond::Mixed(_) => 2,
+                BoundaryCond::Absorbing => 3,
+                BoundaryCond::PartiallyAbsorbing(_) => 4,
+                BoundaryCond::PerFace(_) => 5,
+            }
+        }
+        match (self, other){
+            (BoundaryCond::Mixed(x), BoundaryCond::Mixed(y)) => x.cmp(y),
+            (BoundaryCond::PartiallyAbsorbing(x), BoundaryCond::PartiallyAbsorbing(y)) => x.total_cmp(y),
+            (BoundaryCond::PerFace(x), BoundaryCond::PerFace(y)) => x.cmp(y),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for BoundaryCond{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>{
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for BoundaryCond{
+    fn eq(&self, other: &Self) -> bool{
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BoundaryCond{}
+
+impl BoundaryCond{
+    // 흡수 경계인지 확인
+    pub fn is_absorbing(&self) -> bool{
+        match self{
+            BoundaryCond::Absorbing | BoundaryCond::PartiallyAbsorbing(_) => true,
+            BoundaryCond::PerFace(faces) => faces.is_absorbing(),
+            _ => false,
+        }
+    }
 }
 
 // Formatting
@@ -125,6 +262,9 @@ impl Display for BoundaryCond{
             BoundaryCond::Periodic => write!(f, "Periodic Boundary Condition"),
             BoundaryCond::Reflection => write!(f, "Reflective Boundary Condtion"),
             BoundaryCond::Mixed(dim) => write!(f, "Mixed Boundary Condition. 0..{0:} : Reflection, {0:}.. : Periodic", dim),
+            BoundaryCond::Absorbing => write!(f, "Absorbing Boundary Condition"),
+            BoundaryCond::PartiallyAbsorbing(kappa) => write!(f, "Partially Absorbing Boundary Condition with kappa {}", kappa),
+            BoundaryCond::PerFace(faces) => write!(f, "Per-face Boundary Condition {}", faces),
         }
     }
 }
@@ -143,6 +283,21 @@ impl FromStr for BoundaryCond{
                 let x = split[3][3..].parse::<usize>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
                 Ok(BoundaryCond::Mixed(x))
             },
+            "Absorbing"     => Ok(BoundaryCond::Absorbing),
+            "Per-face"      => Ok(BoundaryCond::PerFace(split[split.len() - 1].parse::<FaceBoundary>()?)),
+            string if string.contains(':') || string.contains('/') => {
+                // ex) P:R/A -> x : periodic, y : reflective at -L, absorbing at +L
+                Ok(BoundaryCond::PerFace(string.parse::<FaceBoundary>()?))
+            },
+            "Partially"     => {
+                let kappa = split[split.len() - 1].parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                Ok(BoundaryCond::PartiallyAbsorbing(kappa))
+            },
+            string if string.starts_with("PartiallyAbsorbing(") && string.ends_with(')') => {
+                // ex) PartiallyAbsorbing(0.5)
+                let kappa = string[19..string.len() - 1].parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                Ok(BoundaryCond::PartiallyAbsorbing(kappa))
+            },
             string => {
                 let x = string.parse::<usize>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
                 if x <= 0{
@@ -161,15 +316,607 @@ impl Default for BoundaryCond{
     }
 }
 
+
+#[derive(Copy, Clone, Debug)]
+pub enum FaceCond{                          // Boundary condition of a single face
+    Periodic,                               // 반대쪽 면도 Periodic이어야 한다
+    Reflection,
+    Absorbing,
+    PartiallyAbsorbing(f64),
+}
+
+// BoundaryCond와 같이 kappa는 total_cmp로 비교한다.
+impl Ord for FaceCond{
+    fn cmp(&self, other: &Self) -> Ordering{
+        fn rank(face: &FaceCond) -> usize{
+            match face{
+                FaceCond::Periodic => 0,
+                FaceCond::Reflection => 1,
+                FaceCond::Absorbing => 2,
+                FaceCond::PartiallyAbsorbing(_) => 3,
+            }
+        }
+        match (self, other){
+            (FaceCond::PartiallyAbsorbing(x), FaceCond::PartiallyAbsorbing(y)) => x.total_cmp(y),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for FaceCond{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering>{
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for FaceCond{
+    fn eq(&self, other: &Self) -> bool{
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for FaceCond{}
+
+impl Display for FaceCond{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            FaceCond::Periodic => write!(f, "Periodic"),
+            FaceCond::Reflection => write!(f, "Reflective"),
+            FaceCond::Absorbing => write!(f, "Absorbing"),
+            FaceCond::PartiallyAbsorbing(kappa) => write!(f, "PartiallyAbsorbing({})", kappa),
+        }
+    }
+}
+
+impl FromStr for FaceCond{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim(){
+            "Periodic" | "P"    => Ok(FaceCond::Periodic),
+            "Reflective" | "R"  => Ok(FaceCond::Reflection),
+            "Absorbing" | "A"   => Ok(FaceCond::Absorbing),
+            string => {
+                // ex) PartiallyAbsorbing(0.5), PA(0.5)
+                let inner : &str = string.strip_prefix("PartiallyAbsorbing(").or_else(|| string.strip_prefix("PA("))
+                    .and_then(|x| x.strip_suffix(')'))
+                    .ok_or(Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                let kappa = inner.parse::<f64>().map_err(|_y| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                Ok(FaceCond::PartiallyAbsorbing(kappa))
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FaceBoundary{                    // Per-face boundary condition of box system
+    faces : Vec<[FaceCond; 2]>,             // 축 i의 [x_i = -L 인 면, x_i = +L 인 면]
+}
+
+impl FaceBoundary{
+    pub fn new(faces : &[(FaceCond, FaceCond)]) -> Result<Self, Error>{
+        // faces : 각 축마다 (x_i = -L 인 면, x_i = +L 인 면)의 boundary condition
+        // Periodic은 한 축의 양쪽 면에 같이 주어져야 한다.
+        if faces.is_empty(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+        if faces.iter().any(|(l, u)| (*l == FaceCond::Periodic) != (*u == FaceCond::Periodic)){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        Ok(FaceBoundary{
+            faces : faces.iter().map(|&(l, u)| [l, u]).collect(),
+        })
+    }
+
+    pub fn dim(&self) -> usize{
+        self.faces.len()
+    }
+
+    pub fn lower(&self, axis : usize) -> FaceCond{
+        self.faces[axis][0]
+    }
+
+    pub fn upper(&self, axis : usize) -> FaceCond{
+        self.faces[axis][1]
+    }
+
+    pub fn is_periodic(&self, axis : usize) -> bool{
+        self.lower(axis) == FaceCond::Periodic
+    }
+
+    // 흡수하는 면이 있는지 확인
+    pub fn is_absorbing(&self) -> bool{
+        self.faces.iter().flatten()
+            .any(|x| matches!(x, FaceCond::Absorbing | FaceCond::PartiallyAbsorbing(_)))
+    }
+}
+
+// 각 축은 ':'로 구분하고, 양쪽 면이 다르면 '/'로 구분한다. ex) Periodic:Reflective/Absorbing
+impl Display for FaceBoundary{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        for (i, [lower, upper]) in self.faces.iter().enumerate(){
+            if i > 0{
+                write!(f, ":")?;
+            }
+            if lower == upper{
+                write!(f, "{}", lower)?;
+            }
+            else{
+                write!(f, "{}/{}", lower, upper)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for FaceBoundary{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut faces : Vec<(FaceCond, FaceCond)> = Vec::new();
+        for axis in s.trim().split(':'){
+            let sides : Vec<&str> = axis.split('/').collect();
+            match sides.len(){
+                1 => {
+                    let x = sides[0].parse::<FaceCond>()?;
+                    faces.push((x, x));
+                },
+                2 => faces.push((sides[0].parse::<FaceCond>()?, sides[1].parse::<FaceCond>()?)),
+                _ => return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+            }
+        }
+        FaceBoundary::new(&faces)
+    }
+}
+
+
+
+// =====================================================================================
+// ===  Implement ReflectionType =======================================================
+// =====================================================================================
+
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd)]
+pub enum ReflectionType{                    // Accuracy of reflection at reflective boundary
+    #[default]
+    Approximate,                            // 경계 밖으로 나간 만큼 접어 넣는 방식
+    Exact,                                  // 경로와 경계의 교점에서 specular reflection
+}
+
+impl_fmt_for_type!(ReflectionType,
+    ReflectionType::Approximate => "Approximate reflection",
+    ReflectionType::Exact => "Exact reflection");
+
+impl FromStr for ReflectionType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim(){
+            "Approximate reflection" | "Approximate" => Ok(ReflectionType::Approximate),
+            "Exact reflection" | "Exact" | "Specular" => Ok(ReflectionType::Exact),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+pub fn absorption_probability(kappa: f64, coeff_diff: f64, dt: f64) -> f64{
+    // Partially absorbing 경계에 한번 부딪혔을 때 흡수될 확률. dt -> 0에서 Robin boundary condition을 준다.
+    // Erban, R., & Chapman, S. J. (2007). Reactive boundary conditions for stochastic simulations of
+    // reaction–diffusion processes. Physical Biology, 4(1), 16.
+    if coeff_diff <= 0f64{
+        return 1f64;
+    }
+    (kappa * (PI * dt / coeff_diff).sqrt()).clamp(0f64, 1f64)
+}
+
+pub(crate) fn absorb_or_reflect(reflective: &dyn SystemCore<f64>, bctype: &BoundaryCond, rng: &mut Pcg64,
+                                pos: &mut Position<f64>, dp: &mut Position<f64>, coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+    // 흡수 경계를 가진 시스템의 check_bc_absorb
+    // reflective : 같은 모양이지만 경계가 반사하는 시스템. 흡수되지 않았을 때 사용한다.
+    // bctype : 원래 시스템의 boundary condition
+    let probability : f64 = match bctype{
+        BoundaryCond::Absorbing => 1f64,
+        BoundaryCond::PartiallyAbsorbing(kappa) => absorption_probability(*kappa, coeff_diff, dt),
+        _ => {
+            reflective.check_bc(pos, dp)?;
+            return Ok(false);
+        },
+    };
+
+    pos.mut_add(dp);
+    if reflective.check_inclusion(pos)?{
+        return Ok(false);
+    }
+    pos.mut_sub(dp);
+    if check_crossing(rng, probability){
+        return Ok(true);
+    }
+    reflective.check_bc(pos, dp)?;
+    Ok(false)
+}
+
+// 한 step 동안 허용하는 최대 반사 횟수. 이보다 많으면 step이 지나치게 큰 것으로 본다.
+const MAX_REFLECTION : usize = 10000;
+
+pub(crate) fn reflect_ball_exact(pos: &mut [f64], dp: &mut [f64], mut vel: Option<&mut [f64]>, radius: f64) -> Result<(), Error>{
+    // 원점이 중심이고 반지름이 radius인 ball 안에서 pos부터 pos + dp까지 움직이며 경계에서 specular reflection
+    // 경계와 만나는 점을 찾아 그 점의 법선에 대해 남은 변위를 반사시키는 것을 반복한다.
+    // pos : ball 안의 초기 위치. 결과가 저장된다.
+    // dp : 변위. 마지막으로 반사된 후의 남은 변위가 저장된다.
+    // vel : 주어지면 반사될 때마다 법선 성분을 뒤집는다.
+    let r2 : f64 = radius * radius;
+    for _i in 0..MAX_REFLECTION{
+        let (mut a, mut b, mut c, mut end) : (f64, f64, f64, f64) = (0f64, 0f64, 0f64, 0f64);
+        for (x, dx) in pos.iter().zip(dp.iter()){
+            a += dx * dx;
+            b += x * dx;
+            c += x * x;
+            end += (x + dx) * (x + dx);
+        }
+        if end <= r2{
+            for (x, dx) in pos.iter_mut().zip(dp.iter()){
+                *x += dx;
+            }
+            return Ok(());
+        }
+
+        // |pos + t dp| = radius 인 t in (0, 1]
+        let t : f64 = ((- b + (b * b - a * (c - r2)).max(0f64).sqrt()) / a).clamp(0f64, 1f64);
+        for (x, dx) in pos.iter_mut().zip(dp.iter_mut()){
+            *x += t * *dx;
+            *dx *= 1f64 - t;
+        }
+
+        // 경계점에서의 법선 n = pos / |pos|
+        let norm : f64 = pos.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let dn : f64 = pos.iter().zip(dp.iter()).map(|(x, dx)| x * dx).sum::<f64>() / norm;
+        for (x, dx) in pos.iter_mut().zip(dp.iter_mut()){
+            *dx -= 2f64 * dn * *x / norm;
+            *x *= radius / norm;                                    // 반올림 오차로 밖에 있지 않도록
+        }
+        if let Some(v) = vel.as_mut(){
+            let vn : f64 = pos.iter().zip(v.iter()).map(|(x, vx)| x * vx).sum::<f64>() / radius;
+            for (x, vx) in pos.iter().zip(v.iter_mut()){
+                *vx -= 2f64 * vn * x / radius;
+            }
+        }
+    }
+    Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+}
+
+pub(crate) fn reflect_surface_exact<H, S>(pos: &mut [f64], dp: &mut [f64], mut vel: Option<&mut [f64]>,
+                                         first_hit: H, surface: S) -> Result<(), Error>
+    where H : Fn(&[f64], &[f64]) -> Option<f64>,
+          S : Fn(&mut [f64], &mut [f64]){
+    // 곡면으로 둘러싸인 시스템에서 pos부터 pos + dp까지 움직이며 경계에서 specular reflection
+    // first_hit : pos에서 dp 방향으로 처음 경계에 닿는 t in (0, 1]. 닿지 않으면 None
+    // surface : 경계 위의 점을 반올림 오차 없이 경계로 옮기고, 그 점의 unit normal을 두번째 인자에 적는다.
+    // dp에는 마지막으로 반사된 후의 남은 변위가 저장되고, vel이 주어지면 반사될 때마다 법선 성분을 뒤집는다.
+    let mut normal : Vec<f64> = Vec::new();
+    for _i in 0..MAX_REFLECTION{
+        let t : f64 = match first_hit(pos, dp){
+            Some(t) => t,
+            None => {
+                for (x, dx) in pos.iter_mut().zip(dp.iter()){
+                    *x += dx;
+                }
+                return Ok(());
+            },
+        };
+        for (x, dx) in pos.iter_mut().zip(dp.iter_mut()){
+            *x += t * *dx;
+            *dx *= 1f64 - t;
+        }
+
+        normal.resize(pos.len(), 0f64);
+        surface(pos, &mut normal);
+        let dn : f64 = normal.iter().zip(dp.iter()).map(|(n, dx)| n * dx).sum();
+        for (n, dx) in normal.iter().zip(dp.iter_mut()){
+            *dx -= 2f64 * dn * n;
+        }
+        if let Some(v) = vel.as_mut(){
+            let vn : f64 = normal.iter().zip(v.iter()).map(|(n, vx)| n * vx).sum();
+            for (n, vx) in normal.iter().zip(v.iter_mut()){
+                *vx -= 2f64 * vn * n;
+            }
+        }
+    }
+    Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep))
+}
+
+const MAX_REJECTION : usize = 1000000;
+
+pub fn random_pos_by_rejection_to_vec<S>(sys: &S, rng: &mut Pcg64, vec: &mut Position<f64>) -> Result<(), Error>
+    where S: SystemCore<f64> + ?Sized{
+    // 직접 uniform하게 뽑는 방법이 없는 저차원(2, 3차원) 시스템을 위한 random_pos_to_vec
+    // bounding_box에서 uniform하게 뽑은 뒤 check_inclusion으로 거른다.
+    // 수락 확률은 (시스템 부피) / (bounding box 부피)이므로 고차원이나 bounding box에 비해 매우 얇은 시스템에서는 쓰지 않는다.
+    // MAX_REJECTION 번 연속으로 거절되면 InvalidConfiguration
+    use crate::random_mod::get_uniform;
+
+    let (lower, upper) = sys.bounding_box()?;
+    if vec.dim() != lower.dim(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+    for _i in 0..MAX_REJECTION{
+        for (x, (a, b)) in vec.iter_mut().zip(lower.iter().zip(upper.iter())){
+            *x = a + (b - a) * get_uniform(rng);
+        }
+        if sys.check_inclusion(vec)?{
+            return Ok(());
+        }
+    }
+    Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration))
+}
+
+pub(crate) fn unit_ball_volume(dim: usize) -> f64{
+    // dim 차원 단위 ball의 부피. V_d = 2 pi / d V_(d-2), V_0 = 1, V_1 = 2
+    let (mut v, mut d) : (f64, usize) = if dim % 2 == 0 { (1f64, 2) } else { (2f64, 3) };
+    while d <= dim{
+        v *= 2f64 * std::f64::consts::PI / d as f64;
+        d += 2;
+    }
+    v
+}
+
+fn carlson_rf(x: f64, y: f64, z: f64) -> f64{
+    // Carlson symmetric elliptic integral R_F(x, y, z). 많아야 하나의 인자가 0이다.
+    // duplication theorem으로 세 인자가 거의 같아질 때까지 줄인 후 Taylor 전개한다.
+    // B. C. Carlson (1995). Numerical computation of real or complex elliptic integrals. Numerical Algorithms, 10, 13.
+    let (mut x, mut y, mut z) : (f64, f64, f64) = (x, y, z);
+    loop{
+        let lambda : f64 = x.sqrt() * y.sqrt() + y.sqrt() * z.sqrt() + z.sqrt() * x.sqrt();
+        x = 0.25 * (x + lambda);
+        y = 0.25 * (y + lambda);
+        z = 0.25 * (z + lambda);
+        let mean : f64 = (x + y + z) / 3f64;
+        let (dx, dy, dz) : (f64, f64, f64) = ((mean - x) / mean, (mean - y) / mean, (mean - z) / mean);
+        if dx.abs().max(dy.abs()).max(dz.abs()) < 1e-3{
+            let e2 : f64 = dx * dy - dz * dz;
+            let e3 : f64 = dx * dy * dz;
+            return (1f64 + (e2 / 24f64 - 0.1 - 3f64 * e3 / 44f64) * e2 + e3 / 14f64) / mean.sqrt();
+        }
+    }
+}
+
+fn carlson_rd(x: f64, y: f64, z: f64) -> f64{
+    // Carlson symmetric elliptic integral R_D(x, y, z) = R_J(x, y, z, z). z > 0이고 x, y 중 많아야 하나가 0이다.
+    let (mut x, mut y, mut z) : (f64, f64, f64) = (x, y, z);
+    let (mut sum, mut fac) : (f64, f64) = (0f64, 1f64);
+    loop{
+        let lambda : f64 = x.sqrt() * y.sqrt() + y.sqrt() * z.sqrt() + z.sqrt() * x.sqrt();
+        sum += fac / (z.sqrt() * (z + lambda));
+        fac *= 0.25;
+        x = 0.25 * (x + lambda);
+        y = 0.25 * (y + lambda);
+        z = 0.25 * (z + lambda);
+        let mean : f64 = 0.2 * (x + y + 3f64 * z);
+        let (dx, dy, dz) : (f64, f64, f64) = ((mean - x) / mean, (mean - y) / mean, (mean - z) / mean);
+        if dx.abs().max(dy.abs()).max(dz.abs()) < 1e-3{
+            let (ea, eb) : (f64, f64) = (dx * dy, dz * dz);
+            let (ec, ed) : (f64, f64) = (ea - eb, ea - 6f64 * eb);
+            let ee : f64 = ed + 2f64 * ec;
+            let (c1, c2, c3, c4) : (f64, f64, f64, f64) = (3f64 / 14f64, 1f64 / 6f64, 9f64 / 22f64, 3f64 / 26f64);
+            let series : f64 = 1f64 + ed * (- c1 + 0.25 * c3 * ed - 1.5 * c4 * dz * ee)
+                                + dz * (c2 * ee + dz * (- c3 * ec + dz * c4 * ea));
+            return 3f64 * sum + fac * series / (mean * mean.sqrt());
+        }
+    }
+}
+
+pub(crate) fn carlson_rg(x: f64, y: f64, z: f64) -> f64{
+    // Carlson symmetric elliptic integral R_G(x, y, z). 모든 인자는 0 이상이고 많아야 하나가 0이다.
+    //      2 R_G(x, y, z) = z R_F - (x - z)(y - z) R_D / 3 + sqrt(x y / z), z는 가장 큰 인자
+    let mut args : [f64; 3] = [x, y, z];
+    args.sort_by(|a, b| a.total_cmp(b));
+    let [x, y, z] = args;
+    0.5 * (z * carlson_rf(x, y, z) - (x - z) * (y - z) * carlson_rd(x, y, z) / 3f64 + (x * y / z).sqrt())
+}
+
+pub(crate) fn signed_distance_from_excess(excess: &[f64]) -> f64{
+    // 여러 개의 평평한(혹은 구형) 경계의 교집합인 영역에서의 signed distance
+    // excess[j] : j번째 경계 밖으로 나간 거리. 안쪽이면 음수
+    // 밖에 있으면 나간 거리들의 norm, 안에 있으면 가장 가까운 경계까지의 거리
+    if excess.is_empty(){
+        return f64::INFINITY;
+    }
+    let outside : f64 = excess.iter().map(|q| q.max(0f64).powi(2)).sum::<f64>().sqrt();
+    if outside > 0f64{
+        return -outside;
+    }
+    -excess.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+}
+
+pub(crate) fn normal_weights_from_excess(excess: &[f64]) -> Result<Vec<f64>, Error>{
+    // signed_distance_from_excess에서 가장 가까운 경계 위의 점의 normal을
+    // 각 경계의 바깥 방향 unit normal의 가중치로 반환
+    if excess.is_empty(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidConfiguration));
+    }
+    let outside : f64 = excess.iter().map(|q| q.max(0f64).powi(2)).sum::<f64>().sqrt();
+    if outside > 0f64{
+        return Ok(excess.iter().map(|q| q.max(0f64) / outside).collect());
+    }
+    let mut idx : usize = 0;
+    for (j, q) in excess.iter().enumerate(){
+        if *q > excess[idx]{
+            idx = j;
+        }
+    }
+    let mut weights = vec![0f64; excess.len()];
+    weights[idx] = 1f64;
+    Ok(weights)
+}
+
+pub(crate) fn sphere_exit_time(pos: &[f64], dp: &[f64], radius: f64) -> Option<f64>{
+    // 반지름 radius인 구 안에서 pos부터 pos + dp까지 움직일 때 구면에 닿는 t in (0, 1]. 끝점이 안에 있으면 None
+    let (mut a, mut b, mut c, mut end) : (f64, f64, f64, f64) = (0f64, 0f64, 0f64, 0f64);
+    for (x, dx) in pos.iter().zip(dp.iter()){
+        a += dx * dx;
+        b += x * dx;
+        c += x * x;
+        end += (x + dx) * (x + dx);
+    }
+    if end <= radius * radius{
+        return None;
+    }
+    Some(((- b + (b * b - a * (c - radius * radius)).max(0f64).sqrt()) / a).clamp(0f64, 1f64))
+}
+
+pub(crate) fn sphere_entry_time(pos: &[f64], dp: &[f64], radius: f64) -> Option<f64>{
+    // 반지름 radius인 구 밖에서 pos부터 pos + dp까지 움직일 때 구면에 닿는 t in (0, 1]. 닿지 않으면 None
+    let (mut a, mut b, mut c) : (f64, f64, f64) = (0f64, 0f64, 0f64);
+    for (x, dx) in pos.iter().zip(dp.iter()){
+        a += dx * dx;
+        b += x * dx;
+        c += x * x;
+    }
+    let disc : f64 = b * b - a * (c - radius * radius);
+    if b >= 0f64 || a == 0f64 || disc <= 0f64{                  // 멀어지고 있거나 구를 지나지 않는다.
+        return None;
+    }
+    let t : f64 = (- b - disc.sqrt()) / a;
+    if t <= 1f64 { Some(t.max(0f64)) } else { None }
+}
+
+pub(crate) fn fold_reflect_exact(x: &mut f64, length: f64) -> bool{
+    // (-length, length) 사이에서 양쪽 벽에 몇번 부딪혀도 되는 reflection.
+    // 반사된 횟수가 홀수이면 true (velocity의 부호가 바뀐다)
+    if x.abs() <= length{
+        return false;
+    }
+    let period : f64 = 4f64 * length;
+    let y : f64 = (*x + length).rem_euclid(period);
+    let odd : bool = ((*x + length) / (2f64 * length)).floor().rem_euclid(2f64) == 1f64;
+    *x = if y > 2f64 * length { period - y } else { y } - length;
+    odd
+}
+
+pub(crate) fn fold_periodic_exact(x: &mut f64, length: f64){
+    // (-length, length) 사이의 periodic boundary. 한 step에 여러 period를 건너도 된다.
+    if x.abs() <= length{
+        return;
+    }
+    *x = (*x + length).rem_euclid(2f64 * length) - length;
+}
+
+pub(crate) fn fold_box<F>(walls: F, reflection: ReflectionType, pos: &mut Position<f64>, dp: &mut Position<f64>,
+                          mut absorb: Option<(&mut Pcg64, f64, f64)>, vel: Option<&mut Position<f64>>) -> Result<bool, Error>
+    where F : Fn(usize) -> [(f64, FaceCond); 2]{
+    // 좌표축에 수직인 벽으로 둘러싸인 box에서 각 면마다 다른 boundary condition.
+    // 벽이 좌표축에 수직이므로 각 성분을 따로 접는다. 흡수되면 true를 반환하고 pos는 움직이기 전 그대로 둔다.
+    // walls : i번째 축의 [(하한, 그 면의 bc), (상한, 그 면의 bc)]
+    // absorb : (rng, coeff_diff, dt). None이면 흡수하는 면에 닿았을 때 AbsorbingBoundary error
+    // vel : 주어지면 반사될 때마다 해당 성분을 뒤집는다.
+    // Approximate reflection에서는 한 성분이 한번만 접힐 수 있고, Exact에서는 여러번 접혀도 된다.
+    let dim : usize = pos.dim();
+    if dim != dp.dim(){
+        return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+    }
+
+    let outside = |i : usize, x : f64| -> bool{
+        let [(lower, _), (upper, _)] = walls(i);
+        x < lower || x > upper
+    };
+    if (0..dim).all(|i| !outside(i, pos[i] + dp[i])){
+        pos.mut_add(dp);
+        return Ok(false);
+    }
+
+    // 접힌 위치는 따로 계산하고, 흡수되거나 error가 나면 pos를 바꾸지 않는다.
+    let max_fold : usize = match reflection{
+        ReflectionType::Approximate => 1,
+        ReflectionType::Exact => MAX_REFLECTION,
+    };
+    let mut folded : Vec<(f64, bool)> = Vec::with_capacity(dim);
+    for i in 0..dim{
+        let [(lower, face_lower), (upper, face_upper)] = walls(i);
+        let (mut x, mut flip) : (f64, bool) = (pos[i] + dp[i], false);
+        let mut num_fold : usize = 0;
+        loop{
+            let (face, wall) : (FaceCond, f64) = if x > upper{
+                (face_upper, upper)
+            } else if x < lower{
+                (face_lower, lower)
+            } else{
+                break;
+            };
+
+            if num_fold == max_fold{
+                return Err(Error::make_error_syntax(ErrorCode::TooLargeTimeStep));
+            }
+            num_fold += 1;
+
+            let probability : f64 = match (face, absorb.as_ref()){
+                (FaceCond::Periodic, _) => {
+                    x += if wall == upper { lower - upper } else { upper - lower };
+                    continue;
+                },
+                (FaceCond::Reflection, _) => 0f64,
+                (_, None) => return Err(Error::make_error_syntax(ErrorCode::AbsorbingBoundary)),
+                (FaceCond::Absorbing, Some(_)) => 1f64,
+                (FaceCond::PartiallyAbsorbing(kappa), Some(&(_, coeff_diff, dt))) => absorption_probability(kappa, coeff_diff, dt),
+            };
+            if probability > 0f64{
+                if let Some((rng, _, _)) = absorb.as_mut(){
+                    if check_crossing(rng, probability){
+                        return Ok(true);
+                    }
+                }
+            }
+            x = 2f64 * wall - x;
+            flip = !flip;
+        }
+        folded.push((x, flip));
+    }
+
+    let mut vel = vel;
+    for (i, &(x, flip)) in folded.iter().enumerate(){
+        pos[i] = x;
+        if let Some(v) = vel.as_mut(){
+            if flip{
+                v[i] = - v[i];
+            }
+        }
+    }
+    Ok(false)
+}
+
+
 #[cfg(test)]
 mod tests{
     use super::*;
     use crate::{impl_fmt_test, impl_fromstr_test};
 
+    #[test]
+    fn test_unit_ball_volume(){
+        use std::f64::consts::PI;
+        assert_eq!(unit_ball_volume(0), 1.0);
+        assert_eq!(unit_ball_volume(1), 2.0);
+        assert!((unit_ball_volume(2) - PI).abs() < 1e-12);
+        assert!((unit_ball_volume(3) - 4.0 * PI / 3.0).abs() < 1e-12);
+        assert!((unit_ball_volume(4) - PI * PI / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_carlson_rg(){
+        // Carlson (1995)의 test value
+        use std::f64::consts::PI;
+        assert!((carlson_rg(0.0, 16.0, 16.0) - PI).abs() < 1e-12);
+        assert!((carlson_rg(2.0, 3.0, 4.0) - 1.7255030280692).abs() < 1e-12);
+        assert!((carlson_rg(0.0, 0.0796, 4.0) - 1.0284758090288).abs() < 1e-12);
+        assert!((carlson_rg(4.0, 0.0796, 0.0) - 1.0284758090288).abs() < 1e-12);
+    }
+
     impl_fmt_test!(test_fmt_systemtype,
         SystemType::ContinuousCircular => "Continuous Circular system.",
         SystemType::ContinuousRectangular => "Continuous Rectangular system.",
         SystemType::ContinuousCylindrical(1) => "Continuous Cylindrical system. 0..1 : Circular, 1.. : Rectangular",
+        SystemType::ContinuousShell => "Continuous Shell system.",
+        SystemType::ContinuousEllipsoidal => "Continuous Ellipsoidal system.",
+        SystemType::ContinuousPolygonal => "Continuous Polygonal system.",
+        SystemType::ContinuousMesh => "Continuous Mesh system.",
+        SystemType::ContinuousSphereSurface => "Continuous Sphere Surface system.",
         SystemType::Lattice => "Lattice system.",
         SystemType::Network => "Network system.");
 
@@ -183,6 +930,11 @@ mod tests{
         SystemType::ContinuousCircular => "Continuous Circular system.",
         SystemType::ContinuousRectangular => "Continuous Rectangular system.",
         SystemType::ContinuousCylindrical(1) => "Continuous Cylindrical system. 0..1 : Circular, 1.. : Rectangular",
+        SystemType::ContinuousShell => "Continuous Shell system.",
+        SystemType::ContinuousEllipsoidal => "Continuous Ellipsoidal system.",
+        SystemType::ContinuousPolygonal => "Continuous Polygonal system.",
+        SystemType::ContinuousMesh => "Continuous Mesh system.",
+        SystemType::ContinuousSphereSurface => "Continuous Sphere Surface system.",
         SystemType::Lattice => "Lattice system.",
         SystemType::Network => "Network system.");
 
@@ -191,6 +943,134 @@ mod tests{
         BoundaryCond::Periodic => "Periodic Boundary Condition",
         BoundaryCond::Reflection => "Reflective Boundary Condtion",
         BoundaryCond::Mixed(1) => "Mixed Boundary Condition. 0..1 : Reflection, 1.. : Periodic");
+
+    #[test]
+    fn test_absorbing_boundarycond(){
+        assert_eq!(format!("{}", BoundaryCond::Absorbing), "Absorbing Boundary Condition");
+        assert_eq!(format!("{}", BoundaryCond::PartiallyAbsorbing(0.5)),
+            "Partially Absorbing Boundary Condition with kappa 0.5");
+        assert_eq!("Absorbing Boundary Condition".parse::<BoundaryCond>(), Ok(BoundaryCond::Absorbing));
+        assert_eq!("Partially Absorbing Boundary Condition with kappa 0.5".parse::<BoundaryCond>(),
+            Ok(BoundaryCond::PartiallyAbsorbing(0.5)));
+        assert_eq!("PartiallyAbsorbing(2.5)".parse::<BoundaryCond>(), Ok(BoundaryCond::PartiallyAbsorbing(2.5)));
+        assert!("PartiallyAbsorbing(x)".parse::<BoundaryCond>().is_err());
+
+        assert!(BoundaryCond::PartiallyAbsorbing(0.5).is_absorbing());
+        assert!(!BoundaryCond::Mixed(1).is_absorbing());
+        assert!((absorption_probability(1.0, PI, 0.01) - 0.1).abs() < 1e-12);
+        assert_eq!(absorption_probability(100.0, 1.0, 1.0), 1f64);
+
+        // kappa가 있어도 Eq이고, 순서는 variant를 선언한 순서를 따른다.
+        fn is_eq<T : Eq>(_x : &T){}
+        is_eq(&BoundaryCond::PartiallyAbsorbing(0.5));
+        let kappa_nan = BoundaryCond::PartiallyAbsorbing(f64::NAN);
+        assert_eq!(kappa_nan, kappa_nan);
+        let sorted = [BoundaryCond::Periodic, BoundaryCond::Reflection, BoundaryCond::Mixed(1), BoundaryCond::Mixed(2),
+                      BoundaryCond::Absorbing, BoundaryCond::PartiallyAbsorbing(0.5), BoundaryCond::PartiallyAbsorbing(1.0)];
+        assert!(sorted.windows(2).all(|x| x[0] < x[1]));
+    }
+
+    #[test]
+    fn test_per_face_boundarycond() -> Result<(), Error>{
+        let faces : FaceBoundary = FaceBoundary::new(&[(FaceCond::Periodic, FaceCond::Periodic),
+                                                       (FaceCond::Reflection, FaceCond::Absorbing),
+                                                       (FaceCond::PartiallyAbsorbing(0.5), FaceCond::Reflection)])?;
+        assert_eq!(faces.dim(), 3);
+        assert_eq!(faces.upper(1), FaceCond::Absorbing);
+        assert!(faces.is_periodic(0) && !faces.is_periodic(1));
+        assert!(faces.is_absorbing());
+        assert!(BoundaryCond::PerFace(faces.clone()).is_absorbing());
+
+        let string : &str = "Periodic:Reflective/Absorbing:PartiallyAbsorbing(0.5)/Reflective";
+        assert_eq!(format!("{}", faces), string);
+        assert_eq!(format!("{}", BoundaryCond::PerFace(faces.clone())), format!("Per-face Boundary Condition {}", string));
+        assert_eq!(format!("Per-face Boundary Condition {}", string).parse::<BoundaryCond>(), Ok(BoundaryCond::PerFace(faces.clone())));
+        assert_eq!("P:R/A:PA(0.5)/R".parse::<BoundaryCond>(), Ok(BoundaryCond::PerFace(faces)));
+        assert_eq!("R/A".parse::<BoundaryCond>()?, BoundaryCond::PerFace(FaceBoundary::new(&[(FaceCond::Reflection, FaceCond::Absorbing)])?));
+
+        // Periodic은 양쪽 면에 같이 주어져야 한다.
+        assert!("P/R:R".parse::<BoundaryCond>().is_err());
+        assert!("R/A/P".parse::<FaceBoundary>().is_err());
+        assert!("R:X".parse::<FaceBoundary>().is_err());
+        assert_eq!(FaceBoundary::new(&[]), Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        assert!(!"R:P".parse::<FaceBoundary>()?.is_absorbing());
+
+        // 각 면은 자신의 kappa를 가진다.
+        let faces : FaceBoundary = "PA(0.5):PA(1)/PA(2)".parse()?;
+        assert_eq!(faces.lower(0), FaceCond::PartiallyAbsorbing(0.5));
+        assert_eq!(faces.lower(1), FaceCond::PartiallyAbsorbing(1.0));
+        assert_eq!(faces.upper(1), FaceCond::PartiallyAbsorbing(2.0));
+        assert_ne!(faces, "PA(0.5):PA(1)/PA(3)".parse::<FaceBoundary>()?);
+
+        // dimension에 제한이 없다.
+        let mut sides : Vec<(FaceCond, FaceCond)> = vec![(FaceCond::Reflection, FaceCond::Absorbing); 40];
+        sides[39] = (FaceCond::PartiallyAbsorbing(0.1), FaceCond::PartiallyAbsorbing(0.2));
+        let faces : FaceBoundary = FaceBoundary::new(&sides)?;
+        assert_eq!(faces.dim(), 40);
+        assert_eq!(faces.upper(38), FaceCond::Absorbing);
+        assert_eq!(faces.lower(38), FaceCond::Reflection);
+        assert_eq!(faces.upper(39), FaceCond::PartiallyAbsorbing(0.2));
+        assert_eq!(format!("{}", faces).parse::<FaceBoundary>()?, faces);
+        Ok(())
+    }
+
+    impl_fmt_test!(test_fmt_reflectiontype,
+        ReflectionType::Approximate => "Approximate reflection",
+        ReflectionType::Exact => "Exact reflection");
+
+    impl_fromstr_test!(test_fromstr_reflectiontype,
+        ReflectionType,
+        ReflectionType::Approximate => "Approximate reflection",
+        ReflectionType::Exact => "Exact reflection");
+
+    #[test]
+    fn test_reflect_ball_exact() -> Result<(), Error>{
+        // 반지름 1인 원에서 (0, 0)부터 (3.5, 0)까지 움직이면 벽에 두번 부딪혀 (-0.5, 0)
+        let mut pos = vec![0.0, 0.0];
+        let mut dp = vec![3.5, 0.0];
+        let mut vel = vec![1.0, 1.0];
+        reflect_ball_exact(&mut pos, &mut dp, Some(&mut vel), 1.0)?;
+        assert!((pos[0] + 0.5).abs() < 1e-12 && pos[1].abs() < 1e-12);
+        assert!((vel[0] - 1.0).abs() < 1e-12 && (vel[1] - 1.0).abs() < 1e-12);
+
+        // 45도로 부딪히면 수직 방향으로 꺾인다.
+        let s : f64 = 0.5f64.sqrt();
+        let mut pos = vec![0.0, 0.0];
+        let mut dp = vec![2.0 * s, 2.0 * s];
+        reflect_ball_exact(&mut pos, &mut dp, None, 1.0)?;
+        assert!((pos[0] - 0.0).abs() < 1e-12 && (pos[1] - 0.0).abs() < 1e-12);
+
+        // 긴 step에서도 길이는 보존되고, 안쪽에 남는다.
+        let mut rng = rng_seed(12341234);
+        for _i in 0..100{
+            let mut pos = vec![0.3, -0.2, 0.1];
+            let mut dp = crate::random_mod::get_gaussian_vec(&mut rng, 3).coordinate;
+            dp.iter_mut().for_each(|x| *x *= 10.0);
+            reflect_ball_exact(&mut pos, &mut dp, None, 1.0)?;
+            assert!(pos.iter().map(|x| x * x).sum::<f64>() <= 1.0 + 1e-12);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fold_exact(){
+        let mut x : f64 = 2.5;
+        assert_eq!(fold_reflect_exact(&mut x, 1.0), true);
+        assert!((x - (-0.5)).abs() < 1e-12);
+        let mut x : f64 = -4.5;
+        assert_eq!(fold_reflect_exact(&mut x, 1.0), false);
+        assert!((x - (-0.5)).abs() < 1e-12);
+        let mut x : f64 = 0.5;
+        assert_eq!(fold_reflect_exact(&mut x, 1.0), false);
+        assert_eq!(x, 0.5);
+
+        let mut x : f64 = 4.5;
+        fold_periodic_exact(&mut x, 1.0);
+        assert!((x - 0.5).abs() < 1e-12);
+        let mut x : f64 = -1.5;
+        fold_periodic_exact(&mut x, 1.0);
+        assert!((x - 0.5).abs() < 1e-12);
+    }
 }
 
 
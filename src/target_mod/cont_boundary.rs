@@ -51,6 +51,11 @@ impl TargetCore<f64> for ContBoundaryTarget{
         self.target_type.clone()
     }
 
+    // Return the position of target
+    fn target_pos(&self) -> &Position<f64>{
+        &self.target_pos
+    }
+
     // Check whether a searcher finds the target
     fn check_find(&self, pos: &Position<f64>) -> Result<bool, Error>{
         let d = self.distance(pos)?;
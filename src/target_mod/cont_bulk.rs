@@ -1,6 +1,7 @@
 // Module for target in bulk of continous system
 
 use crate::prelude::*;
+use crate::bridge::{sphere_crossing_probability, check_crossing};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub struct ContBulkTarget{
@@ -48,6 +49,11 @@ impl TargetCore<f64> for ContBulkTarget{
         self.target_type.clone()
     }
 
+    // Return the position of target
+    fn target_pos(&self) -> &Position<f64>{
+        &self.target_pos
+    }
+
     // Check whether a searcher finds the target
     fn check_find(&self, pos: &Position<f64>) -> Result<bool, Error>{
         let d = self.distance(pos)?;
@@ -58,6 +64,16 @@ impl TargetCore<f64> for ContBulkTarget{
         }
         return Ok(false);
     }
+
+    // Check whether a searcher finds the target during a step, using Brownian bridge
+    fn check_find_bridge(&self, rng: &mut Pcg64, prev: &Position<f64>, pos: &Position<f64>,
+                         coeff_diff: f64, dt: f64) -> Result<bool, Error>{
+        if self.check_find(pos)?{
+            return Ok(true);
+        }
+        let p : f64 = sphere_crossing_probability(&self.target_pos, self.target_size, prev, pos, coeff_diff, dt)?;
+        Ok(check_crossing(rng, p))
+    }
 }
 
 
@@ -103,4 +119,24 @@ mod tests{
         assert_eq!(target.check_find(&pos2), Ok(true));
         assert_eq!(target.check_find(&pos3), Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
     }
+
+    #[test]
+    fn test_check_find_bridge(){
+        let mut rng = rng_seed(12341234);
+        let target : ContBulkTarget = ContBulkTarget::new(Position::<f64>::new(vec![0.0, 0.0]), 1.0);
+        let prev : Position<f64> = Position::<f64>::new(vec![1.1, 0.0]);
+        let far : Position<f64> = Position::<f64>::new(vec![0.0, 5.0]);
+        let near : Position<f64> = Position::<f64>::new(vec![0.0, 1.1]);
+
+        assert_eq!(target.check_find_bridge(&mut rng, &prev, &Position::<f64>::new(vec![0.0, 0.5]), 1.0, 1e-2), Ok(true));
+        assert_eq!(target.check_find_bridge(&mut rng, &prev, &far, 1.0, 1e-2), Ok(false));
+
+        // exp(-0.01 / 0.01) 의 확률로 찾는다.
+        let n : usize = 10000;
+        let mut count : usize = 0;
+        for _i in 0..n{
+            count += target.check_find_bridge(&mut rng, &prev, &near, 1.0, 1e-2).unwrap() as usize;
+        }
+        assert!((count as f64 / n as f64 - (-1f64).exp()).abs() < 0.02);
+    }
 }
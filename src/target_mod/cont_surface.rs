@@ -0,0 +1,110 @@
+// Module for target patch on surface of sphere
+// 구면 위의 원형 patch (geodesic ball). ContSphereSurfaceSystem 위에서 막 단백질 등이 receptor를 찾는 문제에 사용한다.
+
+use crate::prelude::*;
+use crate::system_mod::cont_surface::geodesic_angle;
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct ContSurfaceTarget{
+    pub target_type : TargetType,
+    pub target_pos : Position::<f64>,   // patch 중심의 방향. agent가 움직이는 구면 위로 projection해서 사용한다.
+    pub target_size : f64,              // patch의 geodesic radius
+}
+
+impl ContSurfaceTarget{
+    // Generate Target
+    pub fn new(pos : Position::<f64>, r : f64) -> ContSurfaceTarget{
+        if pos.norm() == 0f64 || r <= 0f64{
+            panic!("{:?}", ErrorCode::InvalidArgumentInput);
+        }
+        ContSurfaceTarget{
+            target_type : TargetType::ContinuousOnSurface,
+            target_pos : pos,
+            target_size : r,
+        }
+    }
+
+    // Geodesic distance between center of target and given position, along the sphere through given position
+    pub fn distance(&self, other_pos: &Position<f64>) -> Result<f64, Error>{
+        // 구의 반지름은 target_pos가 아닌 agent의 위치에서 얻는다. 즉 target_pos를 시스템의 구면 위로 projection한다.
+        Ok(other_pos.norm() * geodesic_angle(&self.target_pos, other_pos)?)
+    }
+}
+
+impl_argument_trait!(ContSurfaceTarget, "Target", ContSurfaceTargetArguments, 2,
+    target_type, TargetType, TargetType::ContinuousOnSurface;
+    target_pos, Position::<f64>, "Direction of Target center on Sphere ex) 0:0:1 = north pole",
+    target_size, f64, "Geodesic Radius of Target");
+
+impl ContSurfaceTarget{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &ContSurfaceTargetArguments) -> Self{
+        Self{
+            target_type : argument.target_type,
+            ..Self::new(argument.target_pos.clone(), argument.target_size)
+        }
+    }
+}
+
+
+impl TargetCore<f64> for ContSurfaceTarget{
+    // Return the type of target
+    fn target_type(&self) -> TargetType{
+        self.target_type
+    }
+
+    // Return the position of target
+    fn target_pos(&self) -> &Position<f64>{
+        &self.target_pos
+    }
+
+    // Check whether a searcher finds the target
+    fn check_find(&self, pos: &Position<f64>) -> Result<bool, Error>{
+        let d = self.distance(pos)?;
+        let rad : f64 = self.target_size;
+
+        if d < rad{
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn test_ttype(){
+        let target : ContSurfaceTarget = ContSurfaceTarget::new(Position::<f64>::new(vec![0.0, 0.0, 2.0]), 1.0);
+        assert_eq!(target.target_type(), TargetType::ContinuousOnSurface);
+        assert_eq!(target.target_size, 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid(){
+        ContSurfaceTarget::new(Position::<f64>::new(vec![0.0, 0.0, 0.0]), 1.0);
+    }
+
+    #[test]
+    fn test_check_find(){
+        // 반지름 2인 구에서 geodesic radius 1은 north pole에서 0.5 rad
+        let target : ContSurfaceTarget = ContSurfaceTarget::new(Position::<f64>::new(vec![0.0, 0.0, 2.0]), 1.0);
+        let pos : Position<f64> = Position::<f64>::new(vec![2.0 * 0.4f64.sin(), 0.0, 2.0 * 0.4f64.cos()]);
+        let pos2 : Position<f64> = Position::<f64>::new(vec![0.0, 2.0 * 0.6f64.sin(), 2.0 * 0.6f64.cos()]);
+        let pos3 : Position<f64> = Position::<f64>::new(vec![1.0, 2.0]);
+
+        assert!((target.distance(&pos).unwrap() - 0.8).abs() < 1e-12);
+        assert_eq!(target.check_find(&pos), Ok(true));
+        assert_eq!(target.check_find(&pos2), Ok(false));
+        assert_eq!(target.check_find(&pos3), Err(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+
+        // target_pos의 크기와 상관없이 agent가 있는 구면 위의 거리
+        let target : ContSurfaceTarget = ContSurfaceTarget::new(Position::<f64>::new(vec![0.0, 0.0, 1.0]), 1.0);
+        assert!((target.distance(&pos).unwrap() - 0.8).abs() < 1e-12);
+        assert_eq!(target.check_find(&pos2), Ok(false));
+    }
+}
@@ -15,12 +15,24 @@ pub trait TargetCore<T>{
     // Return the type of target
     fn target_type(&self) -> TargetType;
 
+    // Return the position of target
+    fn target_pos(&self) -> &Position<T>;
+
     // Check whether a searcher finds the target
     fn check_find(&self, pos: &Position<T>) -> Result<bool, Error>;
+
+    // check_find와 같지만 prev에서 pos로 가는 step 동안 target을 지났을 확률 (Brownian bridge)까지 고려한다.
+    // coeff_diff : diffusion coefficient, dt : step 크기
+    // Target이 지원하지 않으면 FeatureNotProvided
+    fn check_find_bridge(&self, _rng: &mut Pcg64, _prev: &Position<T>, _pos: &Position<T>,
+                         _coeff_diff: f64, _dt: f64) -> Result<bool, Error>{
+        Err(Error::make_error_syntax(ErrorCode::FeatureNotProvided))
+    }
 }
 
 
 pub mod cont_bulk;
+pub mod cont_surface;
 // pub mod cont_boundary;
 
 
@@ -33,6 +45,7 @@ pub mod cont_bulk;
 pub enum TargetType{
     ContinuousInBulk,
     ContinuousAtBoundary,
+    ContinuousOnSurface,
     LatticeInBulk,
     LatticeAtBoundary,
     NetworkSingleNode,
@@ -41,6 +54,7 @@ pub enum TargetType{
 impl_fmt_for_type!(TargetType,
     TargetType::ContinuousInBulk => "Target in Bulk of Continous System.",
     TargetType::ContinuousAtBoundary => "Target at Boundary of Continous System.",
+    TargetType::ContinuousOnSurface => "Target Patch on Surface of Continous System.",
     TargetType::LatticeInBulk => "Target in Bulk of Lattice System.",
     TargetType::LatticeAtBoundary => "Target at Boundary of Lattice System.",
     TargetType::NetworkSingleNode => "Target is a Single Node in Network.");
@@ -48,6 +62,7 @@ impl_fmt_for_type!(TargetType,
 impl_fromstr_for_type!(TargetType,
     TargetType::ContinuousInBulk => "Target in Bulk of Continous System.",
     TargetType::ContinuousAtBoundary => "Target at Boundary of Continous System.",
+    TargetType::ContinuousOnSurface => "Target Patch on Surface of Continous System.",
     TargetType::LatticeInBulk => "Target in Bulk of Lattice System.",
     TargetType::LatticeAtBoundary => "Target at Boundary of Lattice System.",
     TargetType::NetworkSingleNode => "Target is a Single Node in Network.");
@@ -66,6 +81,7 @@ mod tests{
     impl_fmt_test!(test_fmt_targettype,
         TargetType::ContinuousInBulk => "Target in Bulk of Continous System.",
         TargetType::ContinuousAtBoundary => "Target at Boundary of Continous System.",
+        TargetType::ContinuousOnSurface => "Target Patch on Surface of Continous System.",
         TargetType::LatticeInBulk => "Target in Bulk of Lattice System.",
         TargetType::LatticeAtBoundary => "Target at Boundary of Lattice System.",
         TargetType::NetworkSingleNode => "Target is a Single Node in Network.");
@@ -74,6 +90,7 @@ mod tests{
         TargetType,
         TargetType::ContinuousInBulk => "Target in Bulk of Continous System.",
         TargetType::ContinuousAtBoundary => "Target at Boundary of Continous System.",
+        TargetType::ContinuousOnSurface => "Target Patch on Surface of Continous System.",
         TargetType::LatticeInBulk => "Target in Bulk of Lattice System.",
         TargetType::LatticeAtBoundary => "Target at Boundary of Lattice System.",
         TargetType::NetworkSingleNode => "Target is a Single Node in Network.");
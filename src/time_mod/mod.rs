@@ -23,6 +23,13 @@ pub trait TimeIterator
     fn into_diff(&self) -> TimeDiffIterator<Self>;
 }
 
+pub trait TimeGrid{
+    // time grid 전체가 필요한 경우 (e.g. 미리 path를 생성하는 fractional Brownian motion) 사용하는 정보
+    fn time_type(&self) -> TimeType;
+
+    fn tmax(&self) -> f64;
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
 pub struct TimeDiffIterator<T>{
     pub timeiter : T,
@@ -44,6 +51,7 @@ impl<T : Iterator + TimeIterator> Iterator for TimeDiffIterator<T>{
 pub enum TimeType{
     Constant(f64),
     Exponential(f64, f64, usize),
+    Adaptive(f64, f64, f64),
 }
 
 
@@ -52,6 +60,7 @@ impl Display for TimeType{
         match self{
             TimeType::Constant(dt) => write!(f, "Constant Time step with size {0:.5e}", dt),
             TimeType::Exponential(dt_min, dt_max, length) => write!(f, "{}", format_args!("Exponential time step increase from {0:.5e} to {1:.5e} Each increase occurs in every {2:} step", dt_min, dt_max, length)),
+            TimeType::Adaptive(dt_min, dt_max, accuracy) => write!(f, "{}", format_args!("Adaptive time step between {0:.5e} and {1:.5e} with accuracy {2:.5e}", dt_min, dt_max, accuracy)),
         }
     }
 }
@@ -70,6 +79,12 @@ impl FromStr for TimeType{
                 let length = split[13].parse::<usize>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
                 Ok(TimeType::Exponential(dt_min, dt_max, length))
             },
+            "Adaptive" if split.len() == 10 => {
+                let dt_min = split[4].parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                let dt_max = split[6].parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                let accuracy = split[9].parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                Ok(TimeType::Adaptive(dt_min, dt_max, accuracy))
+            },
             _ => {
                 if split.len() == 1{
                     split[0].parse::<f64>().map(|c| TimeType::Constant(c))
@@ -149,6 +164,16 @@ impl TimeIterator for ConstStep{
     }
 }
 
+impl TimeGrid for ConstStep{
+    fn time_type(&self) -> TimeType{
+        self.titype
+    }
+
+    fn tmax(&self) -> f64{
+        self.tmax
+    }
+}
+
 impl Iterator for ConstStep{
     type Item = f64;
 
@@ -301,6 +326,16 @@ impl TimeIterator for ExponentialStep{
     }
 }
 
+impl TimeGrid for ExponentialStep{
+    fn time_type(&self) -> TimeType{
+        self.titype
+    }
+
+    fn tmax(&self) -> f64{
+        self.tmax
+    }
+}
+
 impl Iterator for ExponentialStep{
     type Item = f64;
 
@@ -365,6 +400,175 @@ impl ExponentialStep{
     }
 }
 
+// =============================================================================
+// 매 step마다 agent의 상태로부터 dt를 고르는 time iterator
+// target이나 벽까지의 최소 거리 d, 가장 큰 drift 속력 F에 대해
+//      dt = min( (eps d)^2 / 2D,  eps d / F )
+// 즉 한 step 동안의 diffusion length와 drift displacement가 d의 eps 배를 넘지 않도록 하고, [dt_min, dt_max]로 제한한다.
+// 멀리 있을 때는 큰 step으로 빠르게, 흡수 직전에는 작은 step으로 정확하게 움직인다.
+// 다음 step의 dt는 가장 최근에 adapt로 알려준 상태로 정해지며, 아직 알려주지 않았다면 dt_min을 사용한다.
+// into_diff로 얻은 TimeDiffIterator에서도 adapt를 호출할 수 있으므로 기존의 time loop 안에서 그대로 사용할 수 있다.
+
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct AdaptiveStep{
+    pub dt_min  : f64,                  // minimal time step
+    pub dt_max  : f64,                  // maximal time step
+    pub accuracy : f64,                 // ratio between displacement in a step and minimal distance
+    pub current : f64,                  // current time
+    pub dt      : f64,                  // time step for next step
+    pub tmax    : f64,                  // maximum time
+    pub num_step : usize,               // number of steps since renew
+    pub dt_smallest : f64,              // smallest time step used since renew
+    pub dt_largest : f64,               // largest time step used since renew
+    pub record  : bool,                 // whether every time step is recorded in history
+    pub history : Vec<f64>,             // sequence of time steps used since renew
+}
+
+impl AdaptiveStep{
+    #[allow(dead_code)]
+    pub fn new(dt_min : f64, dt_max : f64, accuracy : f64) -> Result<Self, Error>{
+        // dt_min, dt_max : 시간 간격의 범위
+        // accuracy : 한 step 동안의 displacement와 최소 거리의 비 eps
+        if dt_min < 1e-15 || dt_min > dt_max || accuracy <= 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        Ok(Self{
+            dt_min,
+            dt_max,
+            accuracy,
+            current : 0f64,
+            dt      : dt_min,
+            tmax    : f64::MAX,
+            num_step : 0,
+            dt_smallest : f64::INFINITY,
+            dt_largest : 0f64,
+            record  : false,
+            history : Vec::new(),
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn set_record(&mut self, record : bool){
+        // true이면 사용한 dt를 모두 history에 기록한다.
+        self.record = record;
+    }
+
+    pub fn adapt(&mut self, coeff_diff : f64, distance : f64, force : f64) -> Result<f64, Error>{
+        // 다음 step의 dt를 정하고 반환한다.
+        // coeff_diff : diffusion coefficient of agents
+        // distance : 모든 agent 중 target 혹은 벽까지의 최소 거리
+        // force : 가장 큰 drift 속력 (pair force 등). 없으면 0
+        if coeff_diff < 0f64 || distance.is_nan() || force.is_nan(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+
+        let length : f64 = self.accuracy * distance.max(0f64);
+        let mut dt : f64 = self.dt_max;
+        if coeff_diff > 0f64{
+            dt = dt.min(length * length / (2f64 * coeff_diff));
+        }
+        if force.abs() > 0f64{
+            dt = dt.min(length / force.abs());
+        }
+        self.dt = dt.max(self.dt_min);
+        Ok(self.dt)
+    }
+}
+
+impl TimeDiffIterator<AdaptiveStep>{
+    pub fn adapt(&mut self, coeff_diff : f64, distance : f64, force : f64) -> Result<f64, Error>{
+        // time loop 안에서 다음 step의 dt를 정한다. AdaptiveStep::adapt 참조
+        self.timeiter.adapt(coeff_diff, distance, force)
+    }
+}
+
+impl TimeIterator for AdaptiveStep{
+    fn current_time(&self) -> f64{
+        self.current
+    }
+
+    fn dt(&self) -> f64{
+        self.dt
+    }
+
+    fn renew(&mut self){
+        self.current = 0f64;
+        self.num_step = 0;
+        self.dt_smallest = f64::INFINITY;
+        self.dt_largest = 0f64;
+        self.history.clear();
+        self.dt = self.dt_min;
+    }
+
+    fn set_tmax(&mut self, tmax : f64) -> Result<(), Error>{
+        if tmax < 0f64{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        else if tmax < 1e-15{
+            self.tmax = f64::MAX;
+        }
+        else{
+            self.tmax = tmax;
+        }
+
+        Ok(())
+    }
+
+    fn into_diff(&self) -> TimeDiffIterator<Self>{
+        // 이후의 dt는 TimeDiffIterator::adapt로 정한다.
+        TimeDiffIterator{
+            timeiter : self.clone(),
+        }
+    }
+}
+
+impl TimeGrid for AdaptiveStep{
+    fn time_type(&self) -> TimeType{
+        TimeType::Adaptive(self.dt_min, self.dt_max, self.accuracy)
+    }
+
+    fn tmax(&self) -> f64{
+        self.tmax
+    }
+}
+
+impl Iterator for AdaptiveStep{
+    type Item = f64;
+
+    fn next(&mut self) -> Option<Self::Item>{
+        let time = self.current;
+        if time <= self.tmax{
+            self.current += self.dt;
+            self.num_step += 1;
+            self.dt_smallest = self.dt_smallest.min(self.dt);
+            self.dt_largest = self.dt_largest.max(self.dt);
+            if self.record{
+                self.history.push(self.dt);
+            }
+            Some(time)
+        }
+        else{
+            None
+        }
+    }
+}
+
+impl_argument_trait!(AdaptiveStep, "Time Iterator", AdaptiveStepArguments, 4;
+    dt_min, f64, "Minimal time step size",
+    dt_max, f64, "Maximal time step size",
+    accuracy, f64, "Ratio between displacement in a step and distance to target or wall. ex) 0.1",
+    tmax, f64, "Upper limit of time for computing. ex) 1.0, 0 means INFINITE");
+
+impl AdaptiveStep{
+    #[allow(dead_code)]
+    pub fn convert_from(argument : &AdaptiveStepArguments) -> Result<Self, Error>{
+        let mut timeiter = Self::new(argument.dt_min, argument.dt_max, argument.accuracy)?;
+        timeiter.set_tmax(argument.tmax)?;
+        Ok(timeiter)
+    }
+}
+
 #[cfg(test)]
 mod tests{
     use super::*;
@@ -584,6 +788,76 @@ tmax      : Upper limit of time for computing. ex) 1.0, 0 means INFINITE\n");
 
         Ok(())
     }
-}
 
+    #[test]
+    fn test_adaptive() -> Result<(), Error>{
+        let res = format!("{}", TimeType::Adaptive(1e-5, 1e-1, 0.1));
+        assert_eq!(res, "Adaptive time step between 1.00000e-5 and 1.00000e-1 with accuracy 1.00000e-1");
+        assert_eq!(TimeType::from_str(&res), Ok(TimeType::Adaptive(1e-5, 1e-1, 0.1)));
+
+        let mut timeiter = AdaptiveStep::new(1e-4, 1e-1, 0.1)?;
+        timeiter.set_tmax(1f64)?;
+        timeiter.set_record(true);
+
+        // 멀리 있으면 dt_max, 가까워지면 diffusion, force 조건, 너무 가까우면 dt_min
+        assert_eq!(timeiter.adapt(1.0, 100.0, 0.0)?, 1e-1);
+        assert!((timeiter.adapt(1.0, 1.0, 0.0)? - 5e-3).abs() < 1e-15);
+        assert!((timeiter.adapt(1.0, 1.0, 100.0)? - 1e-3).abs() < 1e-15);
+        assert_eq!(timeiter.adapt(1.0, 0.0, 0.0)?, 1e-4);
+        assert_eq!(timeiter.adapt(-1.0, 1.0, 0.0), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+
+        // 거리가 줄어드는 동안 dt도 줄어들고, 사용한 dt가 기록된다.
+        let mut distance : f64 = 10f64;
+        let mut prev : f64 = -1f64;
+        timeiter.adapt(1.0, distance, 0.0)?;
+        while let Some(time) = timeiter.next(){
+            assert!(time > prev);
+            prev = time;
+            distance *= 0.5;
+            timeiter.adapt(1.0, distance, 0.0)?;
+        }
+        assert_eq!(timeiter.num_step, timeiter.history.len());
+        assert_eq!(timeiter.dt_smallest, 1e-4);
+        assert_eq!(timeiter.dt_largest, 1e-1);
+        assert!(timeiter.history.windows(2).all(|w| w[0] >= w[1]));
+        assert!(timeiter.current_time() > 1f64);
+
+        timeiter.renew();
+        assert_eq!(timeiter.num_step, 0);
+        assert!(timeiter.history.is_empty());
+
+        let args : Vec<String> = ["1", "1e-4", "1e-1", "0.1", "0.0"].iter().map(|x| x.to_string()).collect();
+        let res = AdaptiveStep::read_args_from_vec(&args[1..]);
+        assert_eq!(res, Ok(AdaptiveStepArguments{dt_min : 1e-4, dt_max : 1e-1, accuracy : 0.1, tmax : 0.0}));
+        let timeiter = AdaptiveStep::convert_from(&res?)?;
+        assert_eq!(timeiter.tmax, std::f64::MAX);
+        assert_eq!(timeiter.time_type(), TimeType::Adaptive(1e-4, 1e-1, 0.1));
+        let args : Vec<String> = ["1", "1e-1", "1e-4", "0.1", "0.0"].iter().map(|x| x.to_string()).collect();
+        assert_eq!(AdaptiveStep::convert_from(&AdaptiveStep::read_args_from_vec(&args[1..])?),
+                   Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        Ok(())
+    }
 
+    #[test]
+    fn test_adaptive_into_diff() -> Result<(), Error>{
+        // TimeDiffIterator에서 adapt한 dt가 다음 step에 사용된다.
+        let mut timeiter = AdaptiveStep::new(1e-4, 1e-1, 0.1)?;
+        timeiter.set_tmax(1f64)?;
+        let mut iter = timeiter.into_diff();
+        assert_eq!(iter.next(), Some((0f64, 1e-4)));
+
+        let mut distance : f64 = 100f64;
+        iter.adapt(1.0, distance, 0.0)?;
+        let mut steps : Vec<(f64, f64)> = vec![];
+        while let Some((time, dt)) = iter.next(){
+            steps.push((time, dt));
+            distance *= 0.5;
+            iter.adapt(1.0, distance, 0.0)?;
+        }
+        assert_eq!(steps[0], (1e-4, 1e-1));
+        assert!(steps.windows(2).all(|w| (w[0].0 + w[0].1 - w[1].0).abs() < 1e-12 && w[0].1 >= w[1].1));
+        assert_eq!(steps.last().unwrap().1, 1e-4);
+        assert_eq!(iter.timeiter.dt_smallest, 1e-4);
+        Ok(())
+    }
+}
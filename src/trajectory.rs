@@ -0,0 +1,505 @@
+// Module for exporting trajectory of agents
+//
+// Agent들의 위치를 일정 step, 혹은 일정 시간 간격마다 파일에 기록한다.
+// OVITO, VMD 등에서 바로 열어볼 수 있도록 extended XYZ 형식과 LAMMPS dump 형식을 제공한다.
+
+use crate::prelude::*;
+
+// =====================================================================================
+// ===  Implement TrajFormat ===========================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Default)]
+pub enum TrajFormat{                        // Format of trajectory file
+    #[default]
+    ExtendedXYZ,                            // extended XYZ format (.xyz)
+    LammpsDump,                             // LAMMPS dump format (.dump, .lammpstrj)
+}
+
+impl_fmt_for_type!(TrajFormat,
+    TrajFormat::ExtendedXYZ => "Extended XYZ format",
+    TrajFormat::LammpsDump => "LAMMPS dump format");
+
+impl FromStr for TrajFormat{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        if split.is_empty(){
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        }
+        match split[0]{
+            "Extended" | "XYZ" | "xyz" => Ok(TrajFormat::ExtendedXYZ),
+            "LAMMPS" | "Lammps" | "lammps" | "dump" => Ok(TrajFormat::LammpsDump),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+// =====================================================================================
+// ===  Implement RecordType ===========================================================
+// =====================================================================================
+
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub enum RecordType{                        // When to record a frame
+    Step(usize),                            // every K steps
+    Time(f64),                              // every fixed simulated-time interval
+}
+
+impl Display for RecordType{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result{
+        match self{
+            RecordType::Step(k) => write!(f, "Record every {} steps", k),
+            RecordType::Time(period) => write!(f, "Record every {} time", period),
+        }
+    }
+}
+
+impl RecordType{
+    pub fn check_period(self) -> Result<Self, Error>{
+        // 기록 간격은 양수여야 한다. Step(0)이나 Time(p <= 0)은 InvalidArgumentInput
+        match self{
+            RecordType::Step(k) if k > 0 => Ok(self),
+            RecordType::Time(period) if period > 0f64 => Ok(self),
+            _ => Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)),
+        }
+    }
+}
+
+impl FromStr for RecordType{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split : Vec<&str> = s.split_whitespace().collect();
+        let rtype : RecordType = if split.len() == 1{
+            // 정수는 step 간격, 실수는 시간 간격으로 해석
+            if let Ok(k) = split[0].parse::<usize>(){
+                RecordType::Step(k)
+            }
+            else{
+                let period = split[0].parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                RecordType::Time(period)
+            }
+        }
+        else if split.len() == 4 && split[0] == "Record"{
+            match split[3]{
+                "steps" => {
+                    let k = split[2].parse::<usize>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                    RecordType::Step(k)
+                },
+                "time" => {
+                    let period = split[2].parse::<f64>().map_err(|_e| Error::make_error_syntax(ErrorCode::InvalidArgumentInput))?;
+                    RecordType::Time(period)
+                },
+                _ => {
+                    return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+                }
+            }
+        }
+        else{
+            return Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput));
+        };
+        rtype.check_period()
+    }
+}
+
+impl Default for RecordType{
+    fn default() -> Self{
+        RecordType::Step(1)
+    }
+}
+
+// =====================================================================================
+// ===  Bounds and properties ==========================================================
+// =====================================================================================
+
+pub trait TrajectoryAgent : AgentCore<f64>{
+    // Trajectory에 함께 기록할 agent의 정보
+    // 기본값은 type 1, size 1
+    fn agent_kind(&self) -> usize{
+        1
+    }
+
+    fn agent_size(&self) -> f64{
+        1f64
+    }
+}
+
+impl TrajectoryAgent for ContPassiveIndepAgent{}
+
+impl TrajectoryAgent for ContPassiveMergeAgent{
+    fn agent_size(&self) -> f64{
+        self.size as f64                    // 합쳐진 입자의 수
+    }
+}
+
+impl TrajectoryAgent for ContPassiveExpAgent{}
+
+impl TrajectoryAgent for ContPassiveLJAgent{
+    fn agent_size(&self) -> f64{
+        self.ptl_size
+    }
+}
+
+impl TrajectoryAgent for ContPassiveFieldAgent{}
+
+impl TrajectoryAgent for ContPassiveHeteroAgent{}
+
+impl TrajectoryAgent for ContPassiveOUAgent{}
+impl TrajectoryAgent for ContPassiveFBMAgent{}
+impl TrajectoryAgent for ContPassiveCTRWAgent{}
+impl TrajectoryAgent for ContActiveIntermittentAgent{}
+impl TrajectoryAgent for ContPassiveLangevinAgent{}
+
+// =====================================================================================
+// ===  Implement TrajectoryWriter =====================================================
+// =====================================================================================
+
+pub struct TrajectoryWriter<W : Write>{
+    pub format      : TrajFormat,               // output format
+    pub rtype       : RecordType,               // recording period
+    pub bounds      : Vec<(f64, f64, bool)>,    // system bounds for each axis
+    pub with_type   : bool,                     // whether to record type of agent
+    pub with_size   : bool,                     // whether to record size of agent
+    pub with_cluster: bool,                     // whether to record cluster id of agent
+    step            : usize,                    // number of steps passed
+    next_time       : f64,                      // next time to record for RecordType::Time
+    writer          : W,
+}
+
+impl<W : Write> TrajectoryWriter<W>{
+    pub fn new(writer : W, format : TrajFormat, rtype : RecordType, sys : &dyn SystemCore<f64>) -> Result<Self, Error>{
+        // writer   : output stream
+        // format   : format of trajectory file
+        // rtype    : recording period
+        // sys      : system providing bounding box and periodicity of each axis
+
+        let rtype : RecordType = rtype.check_period()?;
+
+        let (lower, upper) = sys.bounding_box()?;
+        let bounds : Vec<(f64, f64, bool)> = (0..lower.dim()).map(|i| (lower[i], upper[i], sys.is_periodic(i))).collect();
+        if bounds.is_empty() || bounds.len() > 3{
+            // 3차원보다 큰 공간은 두 형식 모두 표현할 수 없다.
+            return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+        }
+
+        Ok(TrajectoryWriter{
+            format,
+            rtype,
+            bounds,
+            with_type   : false,
+            with_size   : false,
+            with_cluster: false,
+            step        : 0,
+            next_time   : 0f64,
+            writer,
+        })
+    }
+
+    pub fn set_property(&mut self, with_type : bool, with_size : bool, with_cluster : bool){
+        // 위치 외에 함께 기록할 정보를 선택
+        self.with_type = with_type;
+        self.with_size = with_size;
+        self.with_cluster = with_cluster;
+    }
+
+    pub fn renew(&mut self){
+        // 새 ensemble을 시작할 때 step, 시간 기록을 초기화
+        self.step = 0;
+        self.next_time = 0f64;
+    }
+
+    pub fn is_record_time(&mut self, time : f64) -> bool{
+        // 매 step마다 호출되며, 이번 step을 기록해야 하는지 반환
+        let step = self.step;
+        self.step += 1;
+
+        match self.rtype{
+            RecordType::Step(k) => step % k == 0,
+            RecordType::Time(period) => {
+                if time + 1e-10 * period < self.next_time{
+                    return false;
+                }
+                while self.next_time <= time + 1e-10 * period{
+                    self.next_time += period;
+                }
+                true
+            },
+        }
+    }
+
+    pub fn record<'a, A, I>(&mut self, time : f64, agents : I) -> Result<bool, Error>
+        where A : TrajectoryAgent + 'a, I : IntoIterator<Item = (usize, &'a A)>{
+        // 기록할 시점이면 frame을 쓰고 true를 반환
+        // agents : (cluster id, agent) 쌍의 iterator. ex) agents.iter().enumerate()
+
+        if !self.is_record_time(time){
+            return Ok(false);
+        }
+        self.write_frame(time, agents)?;
+        Ok(true)
+    }
+
+    pub fn write_frame<'a, A, I>(&mut self, time : f64, agents : I) -> Result<(), Error>
+        where A : TrajectoryAgent + 'a, I : IntoIterator<Item = (usize, &'a A)>{
+        // 시점과 상관없이 현재 상태를 하나의 frame으로 기록
+
+        let mut body = String::new();
+        let mut num_agent : usize = 0;
+        for (cluster, agent) in agents{
+            num_agent += 1;
+            let pos = agent.pos();
+            if pos.dim() != self.bounds.len(){
+                return Err(Error::make_error_syntax(ErrorCode::InvalidDimension));
+            }
+
+            match self.format{
+                TrajFormat::ExtendedXYZ => {
+                    if self.with_type{
+                        body.push_str(&format!("{}", agent.agent_kind()));
+                    }
+                    else{
+                        body.push('A');
+                    }
+                },
+                TrajFormat::LammpsDump => {
+                    body.push_str(&format!("{}", num_agent));
+                    if self.with_type{
+                        body.push_str(&format!(" {}", agent.agent_kind()));
+                    }
+                },
+            }
+            for i in 0..3{
+                let x = if i < pos.dim() { pos[i] } else { 0f64 };      // 3차원보다 작으면 나머지 좌표는 0
+                body.push_str(&format!(" {:.8e}", x));
+            }
+            if self.with_size{
+                body.push_str(&format!(" {:.8e}", agent.agent_size()));
+            }
+            if self.with_cluster{
+                body.push_str(&format!(" {}", cluster));
+            }
+            body.push('\n');
+        }
+
+        let header = match self.format{
+            TrajFormat::ExtendedXYZ => self.header_xyz(time, num_agent),
+            TrajFormat::LammpsDump => self.header_lammps(time, num_agent),
+        };
+        self.writer.write_all(header.as_bytes()).map_err(Error::make_error_io)?;
+        self.writer.write_all(body.as_bytes()).map_err(Error::make_error_io)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error>{
+        self.writer.flush().map_err(Error::make_error_io)
+    }
+
+    pub fn into_inner(self) -> W{
+        self.writer
+    }
+
+    fn bound(&self, i : usize) -> (f64, f64, bool){
+        // 시스템에 없는 축은 두께 1의 비주기적 축으로 취급
+        if i < self.bounds.len(){
+            self.bounds[i]
+        }
+        else{
+            (-0.5f64, 0.5f64, false)
+        }
+    }
+
+    fn header_xyz(&self, time : f64, num_agent : usize) -> String{
+        let (mut lattice, mut origin, mut pbc) = (Vec::new(), Vec::new(), Vec::new());
+        for i in 0..3{
+            let (lo, hi, periodic) = self.bound(i);
+            let mut row = vec![String::from("0"); 3];
+            row[i] = format!("{:.8e}", hi - lo);
+            lattice.push(row.join(" "));
+            origin.push(format!("{:.8e}", lo));
+            pbc.push(if periodic { "T" } else { "F" });
+        }
+
+        let mut properties = String::from("species:S:1:pos:R:3");
+        if self.with_size{
+            properties.push_str(":size:R:1");
+        }
+        if self.with_cluster{
+            properties.push_str(":cluster:I:1");
+        }
+
+        format!("{}\nLattice=\"{}\" Origin=\"{}\" pbc=\"{}\" Properties={} Time={:.8e} dim={}\n",
+            num_agent, lattice.join(" "), origin.join(" "), pbc.join(" "), properties, time, self.bounds.len())
+    }
+
+    fn header_lammps(&self, time : f64, num_agent : usize) -> String{
+        // 마지막으로 is_record_time을 지난 step. record 없이 write_frame을 먼저 부르면 0
+        let mut header = format!("ITEM: TIME\n{:.8e}\nITEM: TIMESTEP\n{}\nITEM: NUMBER OF ATOMS\n{}\n",
+            time, self.step.saturating_sub(1), num_agent);
+
+        let mut flags = Vec::new();
+        let mut lines = String::new();
+        for i in 0..3{
+            let (lo, hi, periodic) = self.bound(i);
+            flags.push(if periodic { "pp" } else { "ff" });
+            lines.push_str(&format!("{:.8e} {:.8e}\n", lo, hi));
+        }
+        header.push_str(&format!("ITEM: BOX BOUNDS {}\n", flags.join(" ")));
+        header.push_str(&lines);
+
+        header.push_str("ITEM: ATOMS id");
+        if self.with_type{
+            header.push_str(" type");
+        }
+        header.push_str(" x y z");
+        if self.with_size{
+            header.push_str(" size");
+        }
+        if self.with_cluster{
+            header.push_str(" cluster");
+        }
+        header.push('\n');
+        header
+    }
+}
+
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::{impl_fmt_test, impl_fromstr_test};
+
+    impl_fmt_test!(test_fmt_trajformat,
+        TrajFormat::ExtendedXYZ => "Extended XYZ format",
+        TrajFormat::LammpsDump => "LAMMPS dump format");
+
+    impl_fromstr_test!(test_fromstr_trajformat,
+        TrajFormat,
+        TrajFormat::ExtendedXYZ => "Extended XYZ format",
+        TrajFormat::ExtendedXYZ => "xyz",
+        TrajFormat::LammpsDump => "LAMMPS dump format",
+        TrajFormat::LammpsDump => "lammps");
+
+    impl_fmt_test!(test_fmt_recordtype,
+        RecordType::Step(10) => "Record every 10 steps",
+        RecordType::Time(0.5) => "Record every 0.5 time");
+
+    impl_fromstr_test!(test_fromstr_recordtype,
+        RecordType,
+        RecordType::Step(10) => "Record every 10 steps",
+        RecordType::Time(0.5) => "Record every 0.5 time",
+        RecordType::Step(10) => "10",
+        RecordType::Time(0.5) => "0.5");
+
+    #[test]
+    fn test_invalid_recordtype(){
+        for s in ["0", "-0.5", "Record every 0 steps", "Record every 0 time", "Record every -1 time", "Record every NaN time"].iter(){
+            assert_eq!(s.parse::<RecordType>(), Err(Error::make_error_syntax(ErrorCode::InvalidArgumentInput)));
+        }
+
+        let sys = ContCircSystem::new(1.0, 2);
+        for rtype in [RecordType::Step(0), RecordType::Time(0f64), RecordType::Time(-1f64)].iter(){
+            assert!(TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, *rtype, &sys).is_err());
+        }
+    }
+
+    #[test]
+    fn test_system_bounds() -> Result<(), Error>{
+        let sys = ContCubicSystem::new(BoundaryCond::Mixed(1), 2.0, 2);
+        let writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys)?;
+        assert_eq!(writer.bounds, vec![(-2.0, 2.0, false), (-2.0, 2.0, true)]);
+
+        let sys = ContCylindricalSystem::new(2, 1.0, 3.0, 3);
+        let writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys)?;
+        assert_eq!(writer.bounds, vec![(-1.0, 1.0, false), (-1.0, 1.0, false), (-3.0, 3.0, true)]);
+
+        // 양 끝이 닫힌 cylinder는 periodic하지 않다.
+        let sys = ContCylindricalSystem::new_closed(2, 1.0, 3.0, 3);
+        let writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys)?;
+        assert_eq!(writer.bounds, vec![(-1.0, 1.0, false), (-1.0, 1.0, false), (-3.0, 3.0, false)]);
+
+        let sys = ContRectSystem::new_with_center("R:P".parse()?, Position::new(vec![2.0, 0.5]), Position::new(vec![1.0, 0.0]));
+        let writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys)?;
+        assert_eq!(writer.bounds, vec![(-1.0, 3.0, false), (-0.5, 0.5, true)]);
+
+        // bounding box가 없는 시스템과 3차원보다 큰 시스템은 error
+        let sys = ContCubicSystem::new(BoundaryCond::Reflection, 1.0, 4);
+        assert_eq!(TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys).err(),
+                   Some(Error::make_error_syntax(ErrorCode::InvalidDimension)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_period(){
+        let sys = ContCircSystem::new(1.0, 2);
+        let mut writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(3), &sys).unwrap();
+        let result : Vec<bool> = (0..7).map(|i| writer.is_record_time(i as f64)).collect();
+        assert_eq!(result, vec![true, false, false, true, false, false, true]);
+
+        let mut writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Time(0.25), &sys).unwrap();
+        let result : Vec<bool> = (0..7).map(|i| writer.is_record_time(i as f64 * 0.1)).collect();
+        assert_eq!(result, vec![true, false, false, true, false, true, false]);
+    }
+
+    #[test]
+    fn test_write_xyz() -> Result<(), Error>{
+        let sys = ContCircSystem::new(2.0, 2);
+        let agents = vec![ContPassiveIndepAgent::new(MoveType::Brownian(1.0), Position::new(vec![0.5, -0.5])),
+                          ContPassiveIndepAgent::new(MoveType::Brownian(1.0), Position::new(vec![1.0, 0.0]))];
+
+        let mut writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::ExtendedXYZ, RecordType::Step(1), &sys)?;
+        writer.set_property(true, false, true);
+        assert_eq!(writer.record(0.0, agents.iter().enumerate())?, true);
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines : Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "2");
+        assert!(lines[1].starts_with("Lattice=\"4.00000000e0 0 0 0 4.00000000e0 0 0 0 1.00000000e0\""));
+        assert!(lines[1].contains("Properties=species:S:1:pos:R:3:cluster:I:1"));
+        assert_eq!(lines[2], "1 5.00000000e-1 -5.00000000e-1 0.00000000e0 0");
+        assert_eq!(lines[3], "1 1.00000000e0 0.00000000e0 0.00000000e0 1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_lammps() -> Result<(), Error>{
+        let sys = ContCubicSystem::new(BoundaryCond::Periodic, 1.0, 3);
+        let agents = vec![ContPassiveMergeAgent::new(MoveType::Brownian(1.0), Position::new(vec![0.0, 0.5, -0.5]), 0.1, 1.0)];
+
+        let mut writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::LammpsDump, RecordType::Step(2), &sys)?;
+        writer.set_property(false, true, false);
+        assert_eq!(writer.record(0.0, agents.iter().enumerate())?, true);
+        assert_eq!(writer.record(0.1, agents.iter().enumerate())?, false);
+        assert_eq!(writer.record(0.2, agents.iter().enumerate())?, true);
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines : Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(lines[3], "0");
+        assert_eq!(lines[6], "ITEM: BOX BOUNDS pp pp pp");
+        assert_eq!(lines[7], "-1.00000000e0 1.00000000e0");
+        assert_eq!(lines[10], "ITEM: ATOMS id x y z size");
+        assert_eq!(lines[11], "1 0.00000000e0 5.00000000e-1 -5.00000000e-1 1.00000000e0");
+        assert_eq!(lines[15], "2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_lammps_first_frame() -> Result<(), Error>{
+        // record를 거치지 않고 첫 frame을 바로 써도 된다.
+        let sys = ContCubicSystem::new(BoundaryCond::Periodic, 1.0, 3);
+        let agents = vec![ContPassiveMergeAgent::new(MoveType::Brownian(1.0), Position::new(vec![0.0, 0.5, -0.5]), 0.1, 1.0)];
+
+        let mut writer = TrajectoryWriter::new(Vec::<u8>::new(), TrajFormat::LammpsDump, RecordType::Step(1), &sys)?;
+        writer.write_frame(0.0, agents.iter().enumerate())?;
+        assert_eq!(writer.record(0.1, agents.iter().enumerate())?, true);
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        let lines : Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 24);
+        assert_eq!(lines[3], "0");
+        assert_eq!(lines[15], "0");
+        Ok(())
+    }
+}
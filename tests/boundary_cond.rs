@@ -20,7 +20,7 @@ fn test_cont_circ_reflection() -> Result<(), Error>{
             for k in 0..10{
                 let t : f64 = k as f64 * PI * 0.1f64;
                 let dp : Position<f64> = Position::new(vec![y * t.cos(), y * t.sin()]);
-                single_test_cont_circ_exact(sys, &pos, &dp)?;
+                single_test_cont_circ_exact(&sys, &pos, &dp)?;
             }
         }
     }
@@ -51,7 +51,7 @@ fn test_check_bc_realization() -> Result<(), Error>{
 
                 let mut pos1 = pos.clone();
                 let mut dp1 = dp.clone();
-                check_bc_exact(sys, &mut pos1, &mut dp1)?;
+                check_bc_exact(&sys, &mut pos1, &mut dp1)?;
 
                 let mut pos2 = pos.clone();
                 let mut dp2 = dp.clone();
@@ -95,7 +95,7 @@ fn test_error_check_bc()-> Result<(), Error>{
 
                 let mut pos1 = pos.clone();
                 let mut dp1 = dp.clone();
-                check_bc_exact(sys, &mut pos1, &mut dp1)?;
+                check_bc_exact(&sys, &mut pos1, &mut dp1)?;
 
                 let mut pos2 = pos.clone();
                 let mut dp2 = dp.clone();
@@ -103,7 +103,7 @@ fn test_error_check_bc()-> Result<(), Error>{
 
                 let mut pos3 = pos.clone();
                 let mut dp3 = dp.clone();
-                check_bc_first_order(sys, &mut pos3, &mut dp3)?;
+                check_bc_first_order(&sys, &mut pos3, &mut dp3)?;
 
 
                 let dx : f64 = (&pos1 - &pos2).norm();
@@ -121,7 +121,7 @@ fn test_error_check_bc()-> Result<(), Error>{
     return Ok(());
 }
 
-fn single_test_cont_circ_exact(sys: ContCircSystem, pos: &Position<f64>, dp: &Position<f64>) -> Result<(), Error>{
+fn single_test_cont_circ_exact(sys: &ContCircSystem, pos: &Position<f64>, dp: &Position<f64>) -> Result<(), Error>{
     // 가장 정확한 boundary condition checker의 각 계산 단계를 검증하는 테스트
     let pos2 : Position<f64> = pos + dp;
     if sys.check_inclusion(&pos2)?{